@@ -14,7 +14,8 @@ use serde::Deserialize;
 use crate::{
     CapsuleTimestamp, ClientNonce, CryptoDomainLabels, IhpCapsule, IhpConfig, IhpError,
     IhpNetworkContext, PasswordMaterial, ProfileKey, ProtocolVersion, ServerEnvHash,
-    ServerProfileId, SessionKey, derive_session_key, encrypt_capsule, generate_client_nonce,
+    ServerProfileId, SessionKey, derive_client_nonce, derive_session_key, encrypt_capsule,
+    generate_client_nonce,
 };
 
 /// Default hop hint for research scaffolding. Real deployments may overwrite this when a more
@@ -141,6 +142,9 @@ pub struct CapsuleBuildOptions {
     pub rtt_bucket_override: Option<u8>,
     pub path_hint: u16,
     pub header_id_override: Option<u64>,
+    /// Derive the nonce from this per-session counter with [`crate::derive_client_nonce`]
+    /// instead of drawing it from the RNG. Never reuse a counter within a TLS session.
+    pub nonce_counter: Option<u64>,
 }
 
 impl Default for CapsuleBuildOptions {
@@ -149,6 +153,7 @@ impl Default for CapsuleBuildOptions {
             rtt_bucket_override: None,
             path_hint: DEFAULT_PATH_HINT,
             header_id_override: None,
+            nonce_counter: None,
         }
     }
 }
@@ -216,7 +221,10 @@ async fn build_capsule_internal(
     let path_hint = options.path_hint;
     let header_id = options.header_id_override.unwrap_or_else(|| rng.next_u64());
 
-    let client_nonce: ClientNonce = generate_client_nonce(rng);
+    let client_nonce: ClientNonce = match options.nonce_counter {
+        Some(counter) => derive_client_nonce(k_profile, tls_exporter_key, counter)?,
+        None => generate_client_nonce(rng),
+    };
     let network_context = IhpNetworkContext {
         rtt_bucket,
         path_hint,
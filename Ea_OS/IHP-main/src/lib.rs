@@ -61,11 +61,13 @@ pub enum TelemetryCode {
     CodecError,
     NonceReuse,
     NonceCollision,
+    DegenerateNonce,
+    InvalidDerivedNonce,
 }
 
 /// Error variants surfaced by the IHP implementation. Sensitive material never appears in
 /// `Display` or `Debug`.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum IhpError {
     InvalidAeadTag,
     StaleTimestamp,
@@ -76,6 +78,8 @@ pub enum IhpError {
     Codec(String),
     NonceReuse,
     NonceCollision,
+    DegenerateNonce,
+    InvalidDerivedNonce,
     KeyDerivation,
     InvalidNonceLength,
     InvalidTimestamp,
@@ -95,6 +99,8 @@ impl IhpError {
             IhpError::Codec(_) => TelemetryCode::CodecError,
             IhpError::NonceReuse => TelemetryCode::NonceReuse,
             IhpError::NonceCollision => TelemetryCode::NonceCollision,
+            IhpError::DegenerateNonce => TelemetryCode::DegenerateNonce,
+            IhpError::InvalidDerivedNonce => TelemetryCode::InvalidDerivedNonce,
             IhpError::KeyDerivation => TelemetryCode::KeyLength,
             IhpError::InvalidNonceLength | IhpError::InvalidTimestamp => {
                 TelemetryCode::ConfigRejected
@@ -116,6 +122,8 @@ impl fmt::Display for IhpError {
             IhpError::Codec(_) => "encoding or decoding failure",
             IhpError::NonceReuse => "nonce reuse detected",
             IhpError::NonceCollision => "nonce collision detected",
+            IhpError::DegenerateNonce => "rng produced degenerate nonces",
+            IhpError::InvalidDerivedNonce => "nonce was not derived for this session",
             IhpError::KeyDerivation => "hkdf expansion failed",
             IhpError::InvalidNonceLength => "nonce length mismatch",
             IhpError::InvalidTimestamp => "timestamp out of range",
@@ -376,6 +384,111 @@ pub fn generate_client_nonce(rng: &mut (impl RngCore + CryptoRng)) -> ClientNonc
     ClientNonce::new(bytes)
 }
 
+/// Default number of draws made by [`generate_client_nonce_checked`].
+pub const DEFAULT_NONCE_ATTEMPTS: u32 = 4;
+
+/// Generate a client nonce, redrawing if the RNG yields an obviously degenerate value.
+///
+/// All-zero and single-repeated-byte nonces are discarded and redrawn, up to
+/// `max_attempts` draws in total; if every draw is degenerate the RNG is treated as
+/// broken and [`IhpError::DegenerateNonce`] is returned. This is opt-in so fixed-output
+/// test RNGs keep working with [`generate_client_nonce`].
+pub fn generate_client_nonce_checked(
+    rng: &mut (impl RngCore + CryptoRng),
+    max_attempts: u32,
+) -> Result<ClientNonce, IhpError> {
+    for _ in 0..max_attempts {
+        let mut bytes = [0u8; NONCE_LEN];
+        rng.fill_bytes(&mut bytes);
+        if !is_degenerate_nonce(&bytes) {
+            return Ok(ClientNonce::new(bytes));
+        }
+    }
+    #[cfg(feature = "observability")]
+    counter!("ihp.nonce.degenerate", 1);
+    Err(IhpError::DegenerateNonce)
+}
+
+fn is_degenerate_nonce(bytes: &[u8; NONCE_LEN]) -> bool {
+    bytes.iter().all(|byte| *byte == bytes[0])
+}
+
+/// HKDF label for counter-derived client nonces.
+pub const NONCE_DERIVATION_LABEL: &[u8] = b"IHP_NONCE:v1";
+
+/// Leading nonce bytes that carry the counter of a derived nonce.
+const NONCE_COUNTER_BYTES: usize = 8;
+
+/// Derive a client nonce from a per-session counter instead of an RNG.
+///
+/// The nonce is the big-endian `counter` followed by an HKDF tag over the profile
+/// key and TLS exporter material, so distinct counters always give distinct nonces
+/// and the server can recover the counter to check it with a [`DerivedNonceGuard`].
+/// Callers must never reuse a counter within a TLS session; the random path via
+/// [`generate_client_nonce`] remains the default.
+pub fn derive_client_nonce(
+    k_profile: &ProfileKey,
+    tls_exporter_key: &[u8],
+    counter: u64,
+) -> Result<ClientNonce, IhpError> {
+    let counter_bytes = counter.to_be_bytes();
+    let mut info = Vec::with_capacity(NONCE_DERIVATION_LABEL.len() + NONCE_COUNTER_BYTES);
+    info.extend_from_slice(NONCE_DERIVATION_LABEL);
+    info.extend_from_slice(&counter_bytes);
+    let hk = Hkdf::<Sha256>::new(Some(k_profile.expose()), tls_exporter_key);
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce[..NONCE_COUNTER_BYTES].copy_from_slice(&counter_bytes);
+    hk.expand(&info, &mut nonce[NONCE_COUNTER_BYTES..])
+        .map_err(|_| IhpError::KeyDerivation)?;
+    Ok(ClientNonce::new(nonce))
+}
+
+/// Server-side replay guard for counter-derived nonces within one TLS session.
+///
+/// Keep one guard per session; it accepts a nonce only if it was derived for that
+/// session and its counter is higher than every counter accepted before.
+#[derive(Debug, Default)]
+pub struct DerivedNonceGuard {
+    last_counter: Option<u64>,
+}
+
+impl DerivedNonceGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Verify `nonce` and record its counter, returning the counter.
+    ///
+    /// Fails with [`IhpError::InvalidDerivedNonce`] if the tag does not match this
+    /// session and [`IhpError::NonceReuse`] if the counter does not advance.
+    pub fn accept(
+        &mut self,
+        k_profile: &ProfileKey,
+        tls_exporter_key: &[u8],
+        nonce: &ClientNonce,
+    ) -> Result<u64, IhpError> {
+        let mut counter_bytes = [0u8; NONCE_COUNTER_BYTES];
+        counter_bytes.copy_from_slice(&nonce.as_array()[..NONCE_COUNTER_BYTES]);
+        let counter = u64::from_be_bytes(counter_bytes);
+        let expected = derive_client_nonce(k_profile, tls_exporter_key, counter)?;
+        if !constant_time_equal(expected.as_array(), nonce.as_array()) {
+            return Err(IhpError::InvalidDerivedNonce);
+        }
+        if self.last_counter.is_some_and(|last| counter <= last) {
+            #[cfg(feature = "observability")]
+            counter!("ihp.nonce.counter_replay", 1);
+            return Err(IhpError::NonceReuse);
+        }
+        self.last_counter = Some(counter);
+        Ok(counter)
+    }
+
+    /// Highest counter accepted so far.
+    pub fn last_counter(&self) -> Option<u64> {
+        self.last_counter
+    }
+}
+
 /// Timestamp wrapper that documents the capsule creation time in seconds since the Unix epoch.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CapsuleTimestamp(i64);
@@ -773,14 +886,66 @@ fn derive_session_key_inner(
     labels: &CryptoDomainLabels,
 ) -> Result<SessionKey, IhpError> {
     derivation.validate()?;
+    let info = session_info(derivation, labels);
+    let secret = hkdf_expand(&info, k_profile.expose(), derivation.tls_exporter_key)?;
+    Ok(SessionKey::new(secret))
+}
+
+/// HKDF info for a session derivation: the session label followed by the public
+/// binding context.
+fn session_info(derivation: &SessionDerivation<'_>, labels: &CryptoDomainLabels) -> Vec<u8> {
     let mut info = Vec::with_capacity(32);
     info.extend_from_slice(labels.hkdf_session);
     info.extend_from_slice(derivation.client_nonce.as_array());
     info.push(derivation.network_context.rtt_bucket);
     info.extend_from_slice(&derivation.network_context.path_hint.to_le_bytes());
     info.extend_from_slice(&derivation.server_profile_id.0.to_le_bytes());
-    let secret = hkdf_expand(&info, k_profile.expose(), derivation.tls_exporter_key)?;
-    Ok(SessionKey::new(secret))
+    info
+}
+
+/// Derivation step described by a [`DerivationTranscript`].
+#[derive(Clone, Copy)]
+pub enum DerivationStep<'a> {
+    /// Master key to profile key, salted by the server environment hash.
+    Profile,
+    /// Profile key to session key for the given binding inputs.
+    Session(&'a SessionDerivation<'a>),
+}
+
+/// Shape of the inputs fed into one HKDF derivation, for audit.
+///
+/// Only the domain label and input lengths are recorded; salt and IKM bytes never
+/// enter the transcript, so it is safe to log or `Debug`-print.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationTranscript {
+    /// Domain-separation label that prefixes the HKDF info.
+    pub label: &'static [u8],
+    /// Total HKDF info length, label included.
+    pub info_len: usize,
+    /// HKDF salt length.
+    pub salt_len: usize,
+    /// Input keying material length.
+    pub ikm_len: usize,
+}
+
+impl CryptoDomainLabels {
+    /// Describe the inputs `step` feeds into HKDF under these labels.
+    pub fn derivation_transcript(&self, step: DerivationStep<'_>) -> DerivationTranscript {
+        match step {
+            DerivationStep::Profile => DerivationTranscript {
+                label: self.hkdf_profile,
+                info_len: self.hkdf_profile.len(),
+                salt_len: std::mem::size_of::<ServerEnvHash>(),
+                ikm_len: KEY_BYTES,
+            },
+            DerivationStep::Session(derivation) => DerivationTranscript {
+                label: self.hkdf_session,
+                info_len: session_info(derivation, self).len(),
+                salt_len: KEY_BYTES,
+                ikm_len: derivation.tls_exporter_key.len(),
+            },
+        }
+    }
 }
 
 impl<T: MasterKeyProvider> KeyProvider for HkdfKeyProvider<T> {
@@ -845,6 +1010,12 @@ impl<P: KeyProvider> IhpContext<P> {
         self.key_provider
             .session_key(k_profile, &derivation, &self.labels)
     }
+
+    /// Describe the HKDF inputs for `step` under this context's labels without
+    /// exposing key material.
+    pub fn derivation_transcript(&self, step: DerivationStep<'_>) -> DerivationTranscript {
+        self.labels.derivation_transcript(step)
+    }
 }
 
 /// Derive a profile key bound to a server environment hash using a master-key source.
@@ -885,20 +1056,35 @@ pub fn derive_session_key(
 }
 
 /// Assemble authenticated data with explicit domain separation and versioning.
+///
+/// Non-empty `extra_aad` is appended behind a little-endian `u32` length prefix;
+/// empty `extra_aad` leaves the layout byte-identical to the original format.
 fn build_aad(
     version: ProtocolVersion,
     server_profile_id: ServerProfileId,
     network_context: IhpNetworkContext,
     server_env_hash: &ServerEnvHash,
-) -> Vec<u8> {
-    let mut aad = Vec::with_capacity(AAD_DOMAIN.len() + 1 + 8 + 1 + 2 + 32);
+    extra_aad: &[u8],
+) -> Result<Vec<u8>, IhpError> {
+    let extra_len = if extra_aad.is_empty() {
+        0
+    } else {
+        4 + extra_aad.len()
+    };
+    let mut aad = Vec::with_capacity(AAD_DOMAIN.len() + 1 + 8 + 1 + 2 + 32 + extra_len);
     aad.extend_from_slice(AAD_DOMAIN);
     aad.push(version.as_u8());
     aad.extend_from_slice(&server_profile_id.0.to_le_bytes());
     aad.push(network_context.rtt_bucket);
     aad.extend_from_slice(&network_context.path_hint.to_le_bytes());
     aad.extend_from_slice(server_env_hash.as_bytes());
-    aad
+    if !extra_aad.is_empty() {
+        let len = u32::try_from(extra_aad.len())
+            .map_err(|_| IhpError::Codec("extra aad exceeds u32 length".into()))?;
+        aad.extend_from_slice(&len.to_le_bytes());
+        aad.extend_from_slice(extra_aad);
+    }
+    Ok(aad)
 }
 
 fn constant_time_equal(a: &[u8], b: &[u8]) -> bool {
@@ -989,6 +1175,15 @@ fn decrypt_inner(
     ciphertext: &[u8],
 ) -> Result<Vec<u8>, IhpError> {
     let cipher = select_cipher(algorithm, key)?;
+    decrypt_with_cipher(&cipher, aad, nonce, ciphertext)
+}
+
+fn decrypt_with_cipher(
+    cipher: &Aes256Gcm,
+    aad: &[u8],
+    nonce: &SecretNonce<NONCE_LEN>,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, IhpError> {
     let nonce = AesNonce::from_slice(nonce.expose());
     cipher
         .decrypt(
@@ -1021,6 +1216,39 @@ pub struct IhpPlaintext {
 }
 
 /// Encrypt a plaintext into an [`IhpCapsule`] using AES-256-GCM.
+pub fn encrypt_capsule(
+    version: ProtocolVersion,
+    config: &IhpConfig,
+    header_id: u64,
+    client_nonce: ClientNonce,
+    server_profile_id: ServerProfileId,
+    network_context: IhpNetworkContext,
+    server_env_hash: &ServerEnvHash,
+    k_session: &SessionKey,
+    password_material: &PasswordMaterial,
+    timestamp: CapsuleTimestamp,
+) -> Result<IhpCapsule, IhpError> {
+    encrypt_capsule_with_aad(
+        version,
+        config,
+        header_id,
+        client_nonce,
+        server_profile_id,
+        network_context,
+        server_env_hash,
+        k_session,
+        password_material,
+        timestamp,
+        &[],
+    )
+}
+
+/// Encrypt like [`encrypt_capsule`], additionally binding `extra_aad` into the
+/// authenticated data.
+///
+/// The extra bytes are not carried in the capsule; the receiver must supply the
+/// same value to [`decrypt_capsule_with_aad`]. An empty slice is equivalent to
+/// [`encrypt_capsule`].
 #[cfg_attr(
     feature = "observability",
     instrument(
@@ -1029,7 +1257,8 @@ pub struct IhpPlaintext {
         fields(version = %version.as_u8(), server_profile_id = server_profile_id.0)
     )
 )]
-pub fn encrypt_capsule(
+#[allow(clippy::too_many_arguments)]
+pub fn encrypt_capsule_with_aad(
     version: ProtocolVersion,
     config: &IhpConfig,
     header_id: u64,
@@ -1040,6 +1269,7 @@ pub fn encrypt_capsule(
     k_session: &SessionKey,
     password_material: &PasswordMaterial,
     timestamp: CapsuleTimestamp,
+    extra_aad: &[u8],
 ) -> Result<IhpCapsule, IhpError> {
     network_context.validate()?;
     config.validate()?;
@@ -1060,7 +1290,13 @@ pub fn encrypt_capsule(
         config.max_payload_bytes,
     )?;
 
-    let aad = build_aad(version, server_profile_id, network_context, server_env_hash);
+    let aad = build_aad(
+        version,
+        server_profile_id,
+        network_context,
+        server_env_hash,
+        extra_aad,
+    )?;
     let nonce = SecretNonce::from_array(*client_nonce.as_array());
     let ciphertext = encrypt_inner(
         config.aead_algorithm,
@@ -1099,6 +1335,27 @@ pub fn encrypt_capsule(
 }
 
 /// Decrypt an [`IhpCapsule`] and validate protocol invariants.
+pub fn decrypt_capsule(
+    capsule: &IhpCapsule,
+    server_env_hash: &ServerEnvHash,
+    k_session: &SessionKey,
+    now_timestamp: CapsuleTimestamp,
+    config: &IhpConfig,
+) -> Result<IhpPlaintext, IhpError> {
+    decrypt_capsule_with_aad(
+        capsule,
+        server_env_hash,
+        k_session,
+        now_timestamp,
+        config,
+        &[],
+    )
+}
+
+/// Decrypt a capsule sealed by [`encrypt_capsule_with_aad`].
+///
+/// `extra_aad` must match the value used at encryption time; any difference
+/// fails authentication with [`IhpError::InvalidAeadTag`].
 #[cfg_attr(
     feature = "observability",
     instrument(
@@ -1107,14 +1364,105 @@ pub fn encrypt_capsule(
         fields(version = capsule.version, server_profile_id = capsule.server_profile_id.0)
     )
 )]
-pub fn decrypt_capsule(
+pub fn decrypt_capsule_with_aad(
     capsule: &IhpCapsule,
     server_env_hash: &ServerEnvHash,
     k_session: &SessionKey,
     now_timestamp: CapsuleTimestamp,
     config: &IhpConfig,
+    extra_aad: &[u8],
 ) -> Result<IhpPlaintext, IhpError> {
     config.validate()?;
+    let cipher = select_cipher(config.aead_algorithm, k_session)?;
+    open_capsule(
+        capsule,
+        server_env_hash,
+        &cipher,
+        now_timestamp,
+        config,
+        extra_aad,
+    )
+}
+
+/// Decrypt every capsule in `capsules` under one session key.
+///
+/// Results line up with the input; failures carry the index of the capsule that
+/// produced them. The AEAD cipher is initialized once and shared across the batch.
+pub fn decrypt_batch(
+    capsules: &[IhpCapsule],
+    server_env_hash: &ServerEnvHash,
+    k_session: &SessionKey,
+    now_timestamp: CapsuleTimestamp,
+    config: &IhpConfig,
+) -> Vec<Result<IhpPlaintext, (usize, IhpError)>> {
+    let cipher = match config
+        .validate()
+        .and_then(|()| select_cipher(config.aead_algorithm, k_session))
+    {
+        Ok(cipher) => cipher,
+        Err(err) => {
+            return (0..capsules.len())
+                .map(|index| Err((index, err.clone())))
+                .collect();
+        }
+    };
+    capsules
+        .iter()
+        .enumerate()
+        .map(|(index, capsule)| {
+            open_capsule(
+                capsule,
+                server_env_hash,
+                &cipher,
+                now_timestamp,
+                config,
+                &[],
+            )
+            .map_err(|err| (index, err))
+        })
+        .collect()
+}
+
+/// Decrypt a batch of capsules, stopping at the first failure.
+///
+/// Returns every plaintext in input order, or the index and error of the first
+/// capsule that failed; capsules after it are not attempted.
+pub fn decrypt_batch_all_or_nothing(
+    capsules: &[IhpCapsule],
+    server_env_hash: &ServerEnvHash,
+    k_session: &SessionKey,
+    now_timestamp: CapsuleTimestamp,
+    config: &IhpConfig,
+) -> Result<Vec<IhpPlaintext>, (usize, IhpError)> {
+    config.validate().map_err(|err| (0, err))?;
+    let cipher = select_cipher(config.aead_algorithm, k_session).map_err(|err| (0, err))?;
+    capsules
+        .iter()
+        .enumerate()
+        .map(|(index, capsule)| {
+            open_capsule(
+                capsule,
+                server_env_hash,
+                &cipher,
+                now_timestamp,
+                config,
+                &[],
+            )
+            .map_err(|err| (index, err))
+        })
+        .collect()
+}
+
+/// Decrypt and validate one capsule with an already-initialized cipher. `config`
+/// must have been validated by the caller.
+fn open_capsule(
+    capsule: &IhpCapsule,
+    server_env_hash: &ServerEnvHash,
+    cipher: &Aes256Gcm,
+    now_timestamp: CapsuleTimestamp,
+    config: &IhpConfig,
+    extra_aad: &[u8],
+) -> Result<IhpPlaintext, IhpError> {
     let Some(version) = ProtocolVersion::from_wire(capsule.version) else {
         #[cfg(feature = "observability")]
         counter!(
@@ -1142,16 +1490,10 @@ pub fn decrypt_capsule(
         capsule.server_profile_id,
         capsule.network_context,
         server_env_hash,
-    );
+        extra_aad,
+    )?;
 
-    let decrypted = decrypt_inner(
-        config.aead_algorithm,
-        &aad,
-        &nonce,
-        k_session,
-        &capsule.payload,
-    )
-    .map_err(|err| {
+    let decrypted = decrypt_with_cipher(cipher, &aad, &nonce, &capsule.payload).map_err(|err| {
         #[cfg(feature = "observability")]
         counter!(
             "ihp.decrypt.failure",
@@ -1188,6 +1530,73 @@ pub fn decrypt_capsule(
     Ok(plaintext)
 }
 
+/// Re-encrypt a capsule from `old_session` to `new_session` without handing the
+/// plaintext to the caller.
+///
+/// The payload is decrypted and immediately sealed again under the new key; the
+/// intermediate bytes live in a [`Zeroizing`] buffer that is wiped before return.
+/// `header_id`, `client_nonce`, `server_profile_id`, and network context are carried
+/// over unchanged so the AAD stays consistent. Both session keys must be available
+/// for the duration of the call. The embedded timestamp is preserved rather than
+/// checked; drift is enforced by whoever finally decrypts the capsule.
+#[cfg_attr(
+    feature = "observability",
+    instrument(
+        level = "info",
+        skip_all,
+        fields(version = capsule.version, server_profile_id = capsule.server_profile_id.0)
+    )
+)]
+pub fn rewrap_capsule(
+    capsule: &IhpCapsule,
+    old_session: &SessionKey,
+    new_session: &SessionKey,
+    server_env_hash: &ServerEnvHash,
+    config: &IhpConfig,
+) -> Result<IhpCapsule, IhpError> {
+    config.validate()?;
+    let version = ProtocolVersion::from_wire(capsule.version)
+        .filter(|version| config.is_version_allowed(*version))
+        .ok_or(IhpError::InvalidVersion)?;
+    capsule.network_context.validate()?;
+
+    let nonce = SecretNonce::from_array(capsule.client_nonce);
+    let aad = build_aad(
+        version,
+        capsule.server_profile_id,
+        capsule.network_context,
+        server_env_hash,
+        &[],
+    )?;
+    let plaintext_bytes = Zeroizing::new(decrypt_inner(
+        config.aead_algorithm,
+        &aad,
+        &nonce,
+        old_session,
+        &capsule.payload,
+    )?);
+    let plaintext = decode_plaintext(&plaintext_bytes, config.max_payload_bytes)?;
+    if !constant_time_equal(
+        &plaintext.header_id.to_le_bytes(),
+        &capsule.header_id.to_le_bytes(),
+    ) {
+        return Err(IhpError::HeaderIdMismatch);
+    }
+    drop(plaintext);
+
+    let payload = encrypt_inner(
+        config.aead_algorithm,
+        &aad,
+        &nonce,
+        new_session,
+        &plaintext_bytes,
+    )?;
+    Ok(IhpCapsule {
+        payload,
+        ..capsule.clone()
+    })
+}
+
 /// Known-good serialized capsules for compatibility detection.
 pub const GOLDEN_CAPSULE_V1: &str = include_str!("../golden_capsule_v1.json");
 
@@ -1195,6 +1604,7 @@ pub const GOLDEN_CAPSULE_V1: &str = include_str!("../golden_capsule_v1.json");
 mod tests {
     use super::*;
     use proptest::prelude::*;
+    use rand_core::{CryptoRng, RngCore, SeedableRng};
     use serde_json::{from_str, to_string};
     use std::sync::atomic::{AtomicBool, Ordering};
 
@@ -1327,6 +1737,58 @@ mod tests {
         assert!(matches!(result, Err(IhpError::InvalidAeadTag)));
     }
 
+    #[test]
+    fn extra_aad_binds_application_context() {
+        let sep = sample_sep();
+        let env_hash = compute_server_env_hash(&sep).expect("hash");
+        let (_, k_session, client_nonce) = base_keys(&env_hash, 7);
+        let network_context = IhpNetworkContext {
+            rtt_bucket: 7,
+            path_hint: 120,
+        };
+        let timestamp = CapsuleTimestamp::new(1_700_000_000).expect("timestamp");
+        let config = IhpConfig::default();
+        let password = PasswordMaterial::new(b"super-secret").unwrap();
+        let seal = |extra: &[u8]| {
+            encrypt_capsule_with_aad(
+                DEFAULT_PROTOCOL_VERSION,
+                &config,
+                99,
+                client_nonce,
+                ServerProfileId(42),
+                network_context,
+                &env_hash,
+                &k_session,
+                &password,
+                timestamp,
+                extra,
+            )
+            .expect("encrypt capsule")
+        };
+
+        let bound = seal(b"tenant-7/session-3");
+        let plaintext = decrypt_capsule_with_aad(
+            &bound,
+            &env_hash,
+            &k_session,
+            timestamp,
+            &config,
+            b"tenant-7/session-3",
+        )
+        .expect("matching extra aad decrypts");
+        assert_eq!(plaintext.password_material.as_slice(), b"super-secret");
+
+        for wrong in [&b"tenant-8/session-3"[..], b"", b"tenant-7/session-3\0"] {
+            let result =
+                decrypt_capsule_with_aad(&bound, &env_hash, &k_session, timestamp, &config, wrong);
+            assert!(matches!(result, Err(IhpError::InvalidAeadTag)));
+        }
+
+        let (plain, _, _, _) = capsule_round_trip();
+        assert_eq!(seal(&[]), plain);
+        assert_ne!(bound.payload, plain.payload);
+    }
+
     #[test]
     fn fails_on_header_id_tamper() {
         let (mut capsule, k_session, timestamp, env_hash) = capsule_round_trip();
@@ -1336,6 +1798,191 @@ mod tests {
         assert!(matches!(result, Err(IhpError::HeaderIdMismatch)));
     }
 
+    #[test]
+    fn batch_decrypt_tags_failures_with_index() {
+        let (capsule, k_session, timestamp, env_hash) = capsule_round_trip();
+        let config = IhpConfig::default();
+        let mut tampered_header = capsule.clone();
+        tampered_header.header_id ^= 1;
+        let mut tampered_payload = capsule.clone();
+        tampered_payload.payload[0] ^= 0xFF;
+        let batch = vec![
+            capsule.clone(),
+            tampered_header,
+            capsule.clone(),
+            tampered_payload,
+        ];
+
+        let results = decrypt_batch(&batch, &env_hash, &k_session, timestamp, &config);
+        assert_eq!(results.len(), 4);
+        for index in [0, 2] {
+            let plaintext = results[index].as_ref().expect("valid capsule");
+            assert_eq!(plaintext.password_material.as_slice(), b"super-secret");
+        }
+        assert_eq!(results[1], Err((1, IhpError::HeaderIdMismatch)));
+        assert_eq!(results[3], Err((3, IhpError::InvalidAeadTag)));
+
+        let err = decrypt_batch_all_or_nothing(&batch, &env_hash, &k_session, timestamp, &config)
+            .unwrap_err();
+        assert_eq!(err, (1, IhpError::HeaderIdMismatch));
+        let all = decrypt_batch_all_or_nothing(
+            &[capsule.clone(), capsule],
+            &env_hash,
+            &k_session,
+            timestamp,
+            &config,
+        )
+        .unwrap();
+        assert_eq!(all.len(), 2);
+        assert!(all.iter().all(|plaintext| plaintext.header_id == 99));
+    }
+
+    struct RepeatingRng(u8);
+
+    impl RngCore for RepeatingRng {
+        fn next_u32(&mut self) -> u32 {
+            u32::from_le_bytes([self.0; 4])
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            u64::from_le_bytes([self.0; 8])
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            dest.fill(self.0);
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl CryptoRng for RepeatingRng {}
+
+    #[test]
+    fn checked_nonce_rejects_degenerate_rng() {
+        for byte in [0x00, 0xAB] {
+            assert_eq!(
+                generate_client_nonce_checked(&mut RepeatingRng(byte), DEFAULT_NONCE_ATTEMPTS)
+                    .unwrap_err(),
+                IhpError::DegenerateNonce
+            );
+        }
+        // The unchecked generator stays usable with fixed-output test RNGs.
+        assert_eq!(
+            generate_client_nonce(&mut RepeatingRng(0)).as_array(),
+            &[0u8; NONCE_LEN]
+        );
+
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(7);
+        let first = generate_client_nonce_checked(&mut rng, DEFAULT_NONCE_ATTEMPTS).unwrap();
+        let second = generate_client_nonce_checked(&mut rng, DEFAULT_NONCE_ATTEMPTS).unwrap();
+        assert!(!is_degenerate_nonce(first.as_array()));
+        assert_ne!(first.as_array(), second.as_array());
+    }
+
+    #[test]
+    fn derived_nonces_are_unique_across_counters() {
+        let env_hash = compute_server_env_hash(&sample_sep()).expect("hash");
+        let (k_profile, _, _) = base_keys(&env_hash, 7);
+        let tls = b"tls exporter key material";
+        let nonces: HashSet<[u8; NONCE_LEN]> = (0..1_000)
+            .map(|counter| {
+                *derive_client_nonce(&k_profile, tls, counter)
+                    .unwrap()
+                    .as_array()
+            })
+            .collect();
+        assert_eq!(nonces.len(), 1_000);
+
+        // Deterministic per session, but bound to the session's exporter material.
+        let again = derive_client_nonce(&k_profile, tls, 5).unwrap();
+        assert_eq!(again, derive_client_nonce(&k_profile, tls, 5).unwrap());
+        assert_ne!(
+            again,
+            derive_client_nonce(&k_profile, b"other session", 5).unwrap()
+        );
+    }
+
+    #[test]
+    fn derived_nonce_guard_rejects_repeated_counter() {
+        let env_hash = compute_server_env_hash(&sample_sep()).expect("hash");
+        let (k_profile, _, _) = base_keys(&env_hash, 7);
+        let tls = b"tls exporter key material";
+        let nonce = |counter| derive_client_nonce(&k_profile, tls, counter).unwrap();
+
+        let mut guard = DerivedNonceGuard::new();
+        assert_eq!(guard.accept(&k_profile, tls, &nonce(1)), Ok(1));
+        assert_eq!(guard.accept(&k_profile, tls, &nonce(2)), Ok(2));
+        assert_eq!(
+            guard.accept(&k_profile, tls, &nonce(2)),
+            Err(IhpError::NonceReuse)
+        );
+        assert_eq!(
+            guard.accept(&k_profile, tls, &nonce(1)),
+            Err(IhpError::NonceReuse)
+        );
+        // Random nonces and nonces derived for another session carry no valid tag.
+        assert_eq!(
+            guard.accept(&k_profile, tls, &ClientNonce::new([7; NONCE_LEN])),
+            Err(IhpError::InvalidDerivedNonce)
+        );
+        let foreign = derive_client_nonce(&k_profile, b"other session", 9).unwrap();
+        assert_eq!(
+            guard.accept(&k_profile, tls, &foreign),
+            Err(IhpError::InvalidDerivedNonce)
+        );
+        assert_eq!(guard.accept(&k_profile, tls, &nonce(10)), Ok(10));
+        assert_eq!(guard.last_counter(), Some(10));
+    }
+
+    #[test]
+    fn derived_and_random_nonces_both_decrypt() {
+        let env_hash = compute_server_env_hash(&sample_sep()).expect("hash");
+        let (k_profile, _, _) = base_keys(&env_hash, 7);
+        let tls = b"tls exporter key material";
+        let network_context = IhpNetworkContext {
+            rtt_bucket: 7,
+            path_hint: 120,
+        };
+        let timestamp = CapsuleTimestamp::new(1_700_000_000).expect("timestamp");
+        let config = IhpConfig::default();
+        let password = PasswordMaterial::new(b"super-secret").unwrap();
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(11);
+
+        for client_nonce in [
+            derive_client_nonce(&k_profile, tls, 3).unwrap(),
+            generate_client_nonce(&mut rng),
+        ] {
+            let k_session = derive_session_key(
+                &k_profile,
+                tls,
+                &client_nonce,
+                &network_context,
+                ServerProfileId(42),
+                &CryptoDomainLabels::default(),
+            )
+            .expect("session key");
+            let capsule = encrypt_capsule(
+                DEFAULT_PROTOCOL_VERSION,
+                &config,
+                99,
+                client_nonce,
+                ServerProfileId(42),
+                network_context,
+                &env_hash,
+                &k_session,
+                &password,
+                timestamp,
+            )
+            .expect("encrypt capsule");
+            let plaintext = decrypt_capsule(&capsule, &env_hash, &k_session, timestamp, &config)
+                .expect("decrypt capsule");
+            assert_eq!(plaintext.password_material.as_slice(), b"super-secret");
+        }
+    }
+
     #[test]
     fn client_nonce_length_validated() {
         assert!(matches!(
@@ -1430,7 +2077,9 @@ mod tests {
                 path_hint: 120,
             },
             &ServerEnvHash([5u8; 32]),
-        );
+            &[],
+        )
+        .unwrap();
         let mut expected = b"IHP_CAPSULE_AAD:v1".to_vec();
         expected.push(DEFAULT_PROTOCOL_VERSION.as_u8());
         expected.extend_from_slice(&5u64.to_le_bytes());
@@ -1564,6 +2213,87 @@ mod tests {
         assert_eq!(session.expose(), &KAT_SESSION_KEY);
     }
 
+    #[test]
+    fn derivation_transcript_reports_labels_and_lengths() {
+        let ctx = IhpContext::new(
+            IhpConfig::default(),
+            HkdfKeyProvider::new(InMemoryKeyProvider::new(KAT_MASTER_KEY)),
+        )
+        .unwrap();
+        let profile = ctx.derivation_transcript(DerivationStep::Profile);
+        assert_eq!(profile.label, b"IHP_PROFILE_KEY:v1");
+        assert_eq!(profile.info_len, 18);
+        assert_eq!(profile.salt_len, KAT_ENV_HASH.as_bytes().len());
+        assert_eq!(profile.ikm_len, KAT_MASTER_KEY.len());
+
+        let derivation = SessionDerivation {
+            tls_exporter_key: KAT_TLS_EXPORTER,
+            client_nonce: ClientNonce::new(KAT_CLIENT_NONCE),
+            network_context: IhpNetworkContext {
+                rtt_bucket: 5,
+                path_hint: 120,
+            },
+            server_profile_id: ServerProfileId(1),
+        };
+        let session = ctx.derivation_transcript(DerivationStep::Session(&derivation));
+        assert_eq!(session.label, b"IHP_SESSION_KEY:v1");
+        // label || nonce || rtt bucket || path hint || profile id
+        assert_eq!(session.info_len, 18 + NONCE_LEN + 1 + 2 + 8);
+        assert_eq!(session.salt_len, KEY_BYTES);
+        assert_eq!(session.ikm_len, KAT_TLS_EXPORTER.len());
+
+        // The transcript describes inputs without carrying any of their bytes.
+        let rendered = format!("{profile:?} {session:?}");
+        assert!(!rendered.contains(&format!("{:?}", KAT_MASTER_KEY)));
+        assert!(!rendered.contains(&format!("{:?}", KAT_TLS_EXPORTER)));
+        assert!(!rendered.contains(&format!("{:?}", KAT_PROFILE_KEY)));
+    }
+
+    #[test]
+    fn rewrapped_capsule_opens_only_under_new_key() {
+        let env_hash = ServerEnvHash([7u8; 32]);
+        let (_, old_session, client_nonce) = base_keys(&env_hash, 2);
+        let new_session = SessionKey::new(SecretKey::new([9u8; KEY_BYTES]));
+        let config = IhpConfig::default();
+        let network_context = IhpNetworkContext {
+            rtt_bucket: 2,
+            path_hint: 80,
+        };
+        let ts = CapsuleTimestamp::new(1_700_000_000).unwrap();
+        let capsule = encrypt_capsule(
+            DEFAULT_PROTOCOL_VERSION,
+            &config,
+            55,
+            client_nonce,
+            ServerProfileId(1),
+            network_context,
+            &env_hash,
+            &old_session,
+            &PasswordMaterial::new(b"rotate-me").unwrap(),
+            ts,
+        )
+        .unwrap();
+
+        let rewrapped =
+            rewrap_capsule(&capsule, &old_session, &new_session, &env_hash, &config).unwrap();
+        assert_ne!(rewrapped.payload, capsule.payload);
+        assert_eq!(rewrapped.header_id, capsule.header_id);
+        assert_eq!(rewrapped.client_nonce, capsule.client_nonce);
+        assert_eq!(rewrapped.network_context, capsule.network_context);
+
+        let plaintext = decrypt_capsule(&rewrapped, &env_hash, &new_session, ts, &config).unwrap();
+        assert_eq!(plaintext.password_material.as_slice(), b"rotate-me");
+        assert_eq!(plaintext.header_id, 55);
+        assert_eq!(
+            decrypt_capsule(&rewrapped, &env_hash, &old_session, ts, &config).unwrap_err(),
+            IhpError::InvalidAeadTag
+        );
+        assert_eq!(
+            rewrap_capsule(&rewrapped, &old_session, &new_session, &env_hash, &config).unwrap_err(),
+            IhpError::InvalidAeadTag
+        );
+    }
+
     #[test]
     fn ciphertext_known_answer_matches_fixture() {
         let labels = CryptoDomainLabels::default();
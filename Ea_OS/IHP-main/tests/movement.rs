@@ -49,6 +49,7 @@ async fn stopped_profile_allows_auth() {
             rtt_bucket_override: Some(12),
             path_hint: DEFAULT_PATH_HINT,
             header_id_override: Some(0xAA11),
+            nonce_counter: None,
         },
     )
     .await
@@ -110,6 +111,7 @@ async fn moving_to_new_environment_is_rejected() {
             rtt_bucket_override: Some(8),
             path_hint: DEFAULT_PATH_HINT,
             header_id_override: Some(0xBB22),
+            nonce_counter: None,
         },
     )
     .await
@@ -158,6 +160,7 @@ async fn tampering_triggers_rejection() {
             rtt_bucket_override: Some(5),
             path_hint: DEFAULT_PATH_HINT,
             header_id_override: Some(0xCC33),
+            nonce_counter: None,
         },
     )
     .await
@@ -5,15 +5,17 @@ use std::sync::Arc;
 
 use anyhow::Context;
 use ed25519_dalek::SigningKey;
+use futures::Stream;
 use ledger_core::{signing, AppendError, AppendLog, MerkleReceipt, ReplayValidator};
 use ledger_spec::{
-    envelope_hash, Envelope, EnvelopeBody, EnvelopeHeader, SchemaVersion, Timestamp, ValidationError,
+    envelope_hash, ChannelState, Envelope, EnvelopeBody, EnvelopeHeader, Hash, SchemaVersion,
+    Timestamp, ValidationError,
 };
 use ledger_transport::Transport;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use tokio::sync::{broadcast, mpsc};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 /// Default schema version for Arda envelopes.
 pub const DEFAULT_SCHEMA_VERSION: SchemaVersion = 1;
@@ -264,11 +266,133 @@ impl ArdaUi {
     }
 }
 
+/// One envelope as shown by the [`AuditTerminal`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditLine {
+    /// Log offset, known for replayed entries only.
+    pub index: Option<usize>,
+    /// Computed envelope hash.
+    pub hash: Hash,
+    /// Whether body hash, signatures, and attestations check out against the registry.
+    pub verified: bool,
+    /// Envelope content.
+    pub envelope: Envelope,
+}
+
+impl AuditLine {
+    fn new(
+        index: Option<usize>,
+        envelope: Envelope,
+        registry: &ledger_spec::ChannelRegistry,
+    ) -> Self {
+        // Chain position is the log's concern; audit lines only vouch for the envelope itself.
+        let state = ChannelState {
+            last_hash: envelope.header.prev,
+            last_timestamp: None,
+        };
+        let verified = ledger_spec::validate_envelope(&envelope, registry, &state).is_ok();
+        Self {
+            index,
+            hash: envelope_hash(&envelope),
+            verified,
+            envelope,
+        }
+    }
+}
+
+impl std::fmt::Display for AuditLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(index) = self.index {
+            write!(f, "[#{index}] ")?;
+        }
+        write!(
+            f,
+            "channel={} ts={} hash={} {}",
+            self.envelope.header.channel,
+            self.envelope.header.timestamp,
+            blake3::Hash::from_bytes(self.hash).to_hex(),
+            if self.verified {
+                "verified"
+            } else {
+                "UNVERIFIED"
+            }
+        )
+    }
+}
+
+/// Read-only audit view over a transport: live tail and historical replay.
+#[derive(Clone)]
+pub struct AuditTerminal {
+    transport: Arc<dyn Transport>,
+    registry: ledger_spec::ChannelRegistry,
+}
+
+impl AuditTerminal {
+    /// Create a terminal that checks envelopes against `registry`.
+    pub fn new(transport: Arc<dyn Transport>, registry: ledger_spec::ChannelRegistry) -> Self {
+        Self {
+            transport,
+            registry,
+        }
+    }
+
+    /// Follow new envelopes, optionally restricted to `channel`.
+    ///
+    /// The stream ends when the transport closes the subscription. Envelopes
+    /// dropped because the tail fell behind are skipped with a warning.
+    pub async fn tail(
+        &self,
+        channel: Option<String>,
+    ) -> anyhow::Result<impl Stream<Item = AuditLine>> {
+        let rx = self.transport.subscribe().await?;
+        let registry = self.registry.clone();
+        Ok(futures::stream::unfold(rx, move |mut rx| {
+            let channel = channel.clone();
+            let registry = registry.clone();
+            async move {
+                loop {
+                    match rx.recv().await {
+                        Ok(env) => {
+                            if channel.as_ref().is_some_and(|c| *c != env.header.channel) {
+                                continue;
+                            }
+                            return Some((AuditLine::new(None, env, &registry), rx));
+                        }
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("audit tail lagged; skipped {skipped} envelopes");
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            }
+        }))
+    }
+
+    /// Read the historical window `from_offset..to_offset`.
+    pub async fn replay(
+        &self,
+        from_offset: usize,
+        to_offset: usize,
+    ) -> anyhow::Result<Vec<AuditLine>> {
+        let limit = to_offset.saturating_sub(from_offset);
+        let envelopes = self
+            .transport
+            .read(from_offset, limit)
+            .await
+            .with_context(|| format!("audit replay offset={from_offset} limit={limit}"))?;
+        Ok(envelopes
+            .into_iter()
+            .enumerate()
+            .map(|(i, env)| AuditLine::new(Some(from_offset + i), env, &self.registry))
+            .collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use ed25519_dalek::SigningKey;
-    use ledger_spec::{ChannelPolicy, ChannelSpec};
+    use ledger_spec::{ChannelPolicy, ChannelSpec, OrderingStrategy};
     use ledger_transport::InVmQueue;
     use rand_core::OsRng;
 
@@ -281,6 +405,11 @@ mod tests {
                 allowed_signers: vec![pk],
                 require_attestations: false,
                 enforce_timestamp_ordering: true,
+                max_attestations_per_envelope: ledger_spec::DEFAULT_MAX_ATTESTATIONS,
+                max_future_skew: None,
+                ordering: OrderingStrategy::StrictChain,
+                allowed_payload_types: None,
+                require_payload_type: false,
             },
         });
         reg
@@ -313,4 +442,82 @@ mod tests {
         assert!(entry.receipt.verify());
         orchestrator.replay().unwrap();
     }
+
+    fn signed_envelope(sk: &SigningKey, prev: Option<Hash>, ts: Timestamp) -> Envelope {
+        let body = EnvelopeBody {
+            payload: serde_json::json!({"ts": ts}),
+            payload_type: Some("ea.event.v1".into()),
+        };
+        let mut env = Envelope {
+            header: EnvelopeHeader {
+                channel: "arda.commands".into(),
+                version: DEFAULT_SCHEMA_VERSION,
+                prev,
+                body_hash: ledger_spec::hash_body(&body),
+                timestamp: ts,
+            },
+            body,
+            signatures: Vec::new(),
+            attestations: Vec::new(),
+        };
+        signing::sign_envelope(&mut env, sk);
+        env
+    }
+
+    #[tokio::test]
+    async fn audit_terminal_tails_and_replays_with_verification() {
+        use futures::StreamExt;
+
+        let trusted = SigningKey::generate(&mut OsRng);
+        let stranger = SigningKey::generate(&mut OsRng);
+        // The transport accepts both signers; the auditor only trusts one.
+        let mut transport_registry = registry_for(trusted.verifying_key().to_bytes());
+        transport_registry.upsert(ChannelSpec {
+            name: "arda.commands".into(),
+            policy: ChannelPolicy {
+                min_signers: 1,
+                allowed_signers: vec![
+                    trusted.verifying_key().to_bytes(),
+                    stranger.verifying_key().to_bytes(),
+                ],
+                require_attestations: false,
+                enforce_timestamp_ordering: true,
+                max_attestations_per_envelope: ledger_spec::DEFAULT_MAX_ATTESTATIONS,
+                max_future_skew: None,
+                ordering: OrderingStrategy::StrictChain,
+                allowed_payload_types: None,
+                require_payload_type: false,
+            },
+        });
+        let transport: Arc<dyn Transport> =
+            Arc::new(ledger_transport::Loopback::new(transport_registry, None).unwrap());
+        let terminal = AuditTerminal::new(
+            transport.clone(),
+            registry_for(trusted.verifying_key().to_bytes()),
+        );
+        let tail = terminal.tail(Some("arda.commands".into())).await.unwrap();
+        futures::pin_mut!(tail);
+
+        let first = signed_envelope(&trusted, None, 1);
+        let second = signed_envelope(&stranger, Some(envelope_hash(&first)), 2);
+        transport.append(first.clone()).await.unwrap();
+        transport.append(second.clone()).await.unwrap();
+
+        let line = tail.next().await.unwrap();
+        assert_eq!(line.hash, envelope_hash(&first));
+        assert!(line.verified);
+        let line = tail.next().await.unwrap();
+        assert_eq!(line.hash, envelope_hash(&second));
+        assert!(!line.verified);
+        assert!(line.to_string().ends_with("UNVERIFIED"));
+
+        let replayed = terminal.replay(0, 2).await.unwrap();
+        assert_eq!(replayed.len(), 2);
+        assert_eq!(replayed[1].index, Some(1));
+        assert_eq!(replayed[1].hash, envelope_hash(&second));
+        assert_eq!(
+            replayed.iter().map(|l| l.verified).collect::<Vec<_>>(),
+            vec![true, false]
+        );
+    }
 }
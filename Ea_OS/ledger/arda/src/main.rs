@@ -5,7 +5,7 @@ use std::sync::Arc;
 use clap::{Parser, Subcommand};
 use ed25519_dalek::SigningKey;
 use ledger_arda::{ArdaOrchestrator, ArdaUi, UiEvent, DEFAULT_SCHEMA_VERSION};
-use ledger_spec::{ChannelPolicy, ChannelSpec};
+use ledger_spec::{ChannelPolicy, ChannelSpec, OrderingStrategy};
 use ledger_transport::{InVmQueue, Transport};
 use rand_core::OsRng;
 use tokio::{io::AsyncBufReadExt, io::BufReader, select};
@@ -69,6 +69,11 @@ async fn main() -> anyhow::Result<()> {
             allowed_signers: vec![signing_key.verifying_key().to_bytes()],
             require_attestations: false,
             enforce_timestamp_ordering: true,
+            max_attestations_per_envelope: ledger_spec::DEFAULT_MAX_ATTESTATIONS,
+            max_future_skew: None,
+            ordering: OrderingStrategy::StrictChain,
+            allowed_payload_types: None,
+            require_payload_type: false,
         },
     });
 
@@ -52,6 +52,11 @@ fn bench_append_latency(c: &mut Criterion) {
             allowed_signers: vec![signer.verifying_key().to_bytes()],
             require_attestations: false,
             enforce_timestamp_ordering: true,
+            max_attestations_per_envelope: ledger_spec::DEFAULT_MAX_ATTESTATIONS,
+            max_future_skew: None,
+            ordering: ledger_spec::OrderingStrategy::StrictChain,
+            allowed_payload_types: None,
+            require_payload_type: false,
         },
     });
     let mut env = Envelope {
@@ -99,6 +104,11 @@ fn bench_receipt_generation(c: &mut Criterion) {
             allowed_signers: vec![signer.verifying_key().to_bytes()],
             require_attestations: false,
             enforce_timestamp_ordering: true,
+            max_attestations_per_envelope: ledger_spec::DEFAULT_MAX_ATTESTATIONS,
+            max_future_skew: None,
+            ordering: ledger_spec::OrderingStrategy::StrictChain,
+            allowed_payload_types: None,
+            require_payload_type: false,
         },
     });
     let log = AppendLog::new();
@@ -144,6 +154,11 @@ fn bench_transport_loopback_latency(c: &mut Criterion) {
             allowed_signers: vec![signer.verifying_key().to_bytes()],
             require_attestations: false,
             enforce_timestamp_ordering: true,
+            max_attestations_per_envelope: ledger_spec::DEFAULT_MAX_ATTESTATIONS,
+            max_future_skew: None,
+            ordering: ledger_spec::OrderingStrategy::StrictChain,
+            allowed_payload_types: None,
+            require_payload_type: false,
         },
     });
     let transport = Loopback::new(registry.clone(), None).expect("loopback");
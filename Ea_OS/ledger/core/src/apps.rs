@@ -13,7 +13,7 @@ use ledger_spec::events::{
     AgencyEvent, Audience, AuditEvent, ContentRef, DataSensitivity, EventId, EventKind,
     LedgerEvent, LifecycleStage, MuscleEvent, PrivacyAction, PrivacyEvent,
 };
-use ledger_spec::{Attestation, Channel, Hash, PublicKey, SchemaVersion, Timestamp};
+use ledger_spec::{Attestation, Channel, Envelope, Hash, PublicKey, SchemaVersion, Timestamp};
 
 use crate::brainstem::{Alert, AppendReceipt, Ledger, SliceQuery};
 use crate::lifecycle::MuscleLifecycleManager;
@@ -243,15 +243,124 @@ impl AuditTerminal {
     }
 }
 
+/// Placeholder substituted for redacted payload values.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Sensitive payload value located by [`PrivacyAnalyzer::scan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// JSON pointer to the value within the envelope payload.
+    pub pointer: String,
+    /// Configured matcher that flagged the value.
+    pub matcher: String,
+}
+
 /// Privacy Analyzer orchestrator: document submit → scan → findings → action.
+///
+/// Also redacts configured payload fields from envelope copies handed to
+/// readers; the stored log is never modified.
 pub struct PrivacyAnalyzer {
     ctx: AppContext,
+    pointers: Vec<String>,
+    field_patterns: Vec<regex::Regex>,
 }
 
 impl PrivacyAnalyzer {
     /// Create a new Privacy Analyzer.
     pub fn new(ctx: AppContext) -> Self {
-        Self { ctx }
+        Self {
+            ctx,
+            pointers: Vec::new(),
+            field_patterns: Vec::new(),
+        }
+    }
+
+    /// Treat the payload value at JSON `pointer` (e.g. `/user/ssn`) as sensitive.
+    pub fn redact_pointer(mut self, pointer: impl Into<String>) -> Self {
+        self.pointers.push(pointer.into());
+        self
+    }
+
+    /// Treat values under any object key matching `pattern` as sensitive, at any depth.
+    pub fn redact_fields(mut self, pattern: &str) -> Result<Self, regex::Error> {
+        self.field_patterns.push(regex::Regex::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Report the payload values [`Self::redact`] would replace.
+    pub fn scan(&self, env: &Envelope) -> Vec<Finding> {
+        let payload = &env.body.payload;
+        let mut findings: Vec<Finding> = self
+            .pointers
+            .iter()
+            .filter(|pointer| payload.pointer(pointer).is_some())
+            .map(|pointer| Finding {
+                pointer: pointer.clone(),
+                matcher: format!("pointer:{pointer}"),
+            })
+            .collect();
+        if !self.field_patterns.is_empty() {
+            self.scan_fields(payload, &mut String::new(), &mut findings);
+        }
+        findings
+    }
+
+    /// Copy of `env` with every [`Finding`] replaced by [`REDACTED`].
+    ///
+    /// The header, signatures, and attestations are kept as-is so the copy
+    /// still names the original entry; its body no longer matches `body_hash`.
+    pub fn redact(&self, env: &Envelope) -> Envelope {
+        let mut copy = env.clone();
+        for finding in self.scan(env) {
+            // Values nested under an already-redacted field are gone; skip them.
+            if let Some(value) = copy.body.payload.pointer_mut(&finding.pointer) {
+                *value = serde_json::Value::String(REDACTED.into());
+            }
+        }
+        copy
+    }
+
+    /// Read a slice of the ledger with sensitive values redacted.
+    pub fn read_redacted(&self, from: usize, limit: usize) -> Result<Vec<Envelope>, AppError> {
+        let slice = self
+            .ctx
+            .ledger
+            .query(SliceQuery {
+                from,
+                limit,
+                include_payloads: false,
+            })
+            .map_err(AppError::Ledger)?;
+        Ok(slice.envelopes.iter().map(|env| self.redact(env)).collect())
+    }
+
+    fn scan_fields(&self, value: &serde_json::Value, path: &mut String, out: &mut Vec<Finding>) {
+        let len = path.len();
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, child) in map {
+                    path.push('/');
+                    path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                    match self.field_patterns.iter().find(|re| re.is_match(key)) {
+                        Some(re) if !out.iter().any(|f| f.pointer == *path) => out.push(Finding {
+                            pointer: path.clone(),
+                            matcher: format!("field:{}", re.as_str()),
+                        }),
+                        Some(_) => {}
+                        None => self.scan_fields(child, path, out),
+                    }
+                    path.truncate(len);
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for (i, child) in items.iter().enumerate() {
+                    path.push_str(&format!("/{i}"));
+                    self.scan_fields(child, path, out);
+                    path.truncate(len);
+                }
+            }
+            _ => {}
+        }
     }
 
     /// Submit a document for scanning through a designated muscle.
@@ -587,6 +696,11 @@ mod tests {
                 allowed_signers: Vec::new(),
                 require_attestations: false,
                 enforce_timestamp_ordering: true,
+                max_attestations_per_envelope: ledger_spec::DEFAULT_MAX_ATTESTATIONS,
+                max_future_skew: None,
+                ordering: ledger_spec::OrderingStrategy::StrictChain,
+                allowed_payload_types: None,
+                require_payload_type: false,
             },
         });
         let ledger = Ledger::new(registry);
@@ -668,6 +782,73 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn privacy_analyzer_redacts_reads_without_touching_the_log() {
+        let ctx = base_context();
+        let analyzer = PrivacyAnalyzer::new(ctx.clone())
+            .redact_pointer("/kind/data/InferenceRequested/input/locator")
+            .redact_fields("(?i)^justification$")
+            .unwrap();
+        let audit = AuditTerminal::new(ctx.clone());
+        audit
+            .request_inference(
+                ledger_spec::events::MuscleRef {
+                    id: [4u8; 32],
+                    version: 1,
+                },
+                b"patient record".to_vec(),
+                "diagnosis for J. Doe".into(),
+                "results".into(),
+            )
+            .unwrap();
+
+        let stored = ctx
+            .ledger
+            .query(SliceQuery {
+                from: 0,
+                limit: 1,
+                include_payloads: false,
+            })
+            .unwrap()
+            .envelopes
+            .remove(0);
+        let findings = analyzer.scan(&stored);
+        assert_eq!(
+            findings
+                .iter()
+                .map(|f| f.pointer.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "/kind/data/InferenceRequested/input/locator",
+                "/kind/data/InferenceRequested/justification"
+            ]
+        );
+
+        let redacted = analyzer.read_redacted(0, 1).unwrap().remove(0);
+        let request = &redacted.body.payload["kind"]["data"]["InferenceRequested"];
+        assert_eq!(request["justification"], REDACTED);
+        assert_eq!(request["input"]["locator"], REDACTED);
+        // Structure and unrelated fields survive.
+        assert_eq!(request["return_channel"], "results");
+        assert_eq!(redacted.header, stored.header);
+
+        let reread = ctx
+            .ledger
+            .query(SliceQuery {
+                from: 0,
+                limit: 1,
+                include_payloads: false,
+            })
+            .unwrap()
+            .envelopes
+            .remove(0);
+        assert_eq!(reread, stored);
+        assert_eq!(
+            reread.body.payload["kind"]["data"]["InferenceRequested"]["justification"],
+            "diagnosis for J. Doe"
+        );
+    }
+
     fn lifecycle_manager() -> MuscleLifecycleManager {
         let signer = SigningKey::generate(&mut OsRng);
         MuscleLifecycleManager::new(
@@ -8,11 +8,19 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use blake3::Hasher;
+use ed25519_dalek::SigningKey;
+use ledger_spec::events::{Audience, DataSensitivity, EventKind, LedgerEvent, PolicyEvent};
+use ledger_spec::policy::{PolicyBinding, PolicyDecision, PolicyEffect, PolicyScope};
+use ledger_spec::SchemaVersion;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info, warn};
 
-use crate::{envelope_hash, hash_body, AppendLog, ChannelRegistry, Envelope, MerkleReceipt};
+use crate::policy::{AppendContext, AppendDecision, AppendPolicy};
+use crate::{
+    envelope_hash, hash_body, signing, verify_signers, AppendLog, AppendLogStorage,
+    ChannelRegistry, Envelope, MerkleReceipt,
+};
 
 /// Content-addressed payload store (blake3 digest).
 #[derive(Debug, Default, Clone)]
@@ -95,6 +103,13 @@ pub enum Alert {
         /// Limit requested by the caller.
         limit: usize,
     },
+    /// Append policy denied the envelope.
+    PolicyDenied {
+        /// Rule that denied the append.
+        rule_id: Option<String>,
+        /// Why the append was denied.
+        reason: String,
+    },
 }
 
 /// Query slice request with proofs.
@@ -119,6 +134,15 @@ pub struct SliceResponse {
     pub payloads: HashMap<[u8; 32], Vec<u8>>,
 }
 
+/// Append policy plus the identity used to record its denials.
+#[derive(Debug, Clone)]
+struct AppendGate {
+    policy: Arc<dyn AppendPolicy>,
+    auditor: Arc<SigningKey>,
+    audit_channel: String,
+    schema_version: SchemaVersion,
+}
+
 /// Ledger orchestration façade.
 #[derive(Debug, Clone)]
 pub struct Ledger {
@@ -126,6 +150,7 @@ pub struct Ledger {
     log: AppendLog,
     store: ContentStore,
     index: DomainIndex,
+    gate: Option<AppendGate>,
 }
 
 impl Ledger {
@@ -136,9 +161,30 @@ impl Ledger {
             log: AppendLog::new(),
             store: ContentStore::default(),
             index: DomainIndex::default(),
+            gate: None,
         }
     }
 
+    /// Gate appends through `policy`.
+    ///
+    /// Each denial is recorded as a policy decision signed by `auditor` on
+    /// `audit_channel`, which the registry must accept from that key.
+    pub fn with_append_policy(
+        mut self,
+        policy: Arc<dyn AppendPolicy>,
+        auditor: SigningKey,
+        audit_channel: impl Into<String>,
+        schema_version: SchemaVersion,
+    ) -> Self {
+        self.gate = Some(AppendGate {
+            policy,
+            auditor: Arc::new(auditor),
+            audit_channel: audit_channel.into(),
+            schema_version,
+        });
+        self
+    }
+
     /// Access the underlying content-addressed store for attaching blobs.
     pub fn content_store(&self) -> ContentStore {
         self.store.clone()
@@ -154,7 +200,89 @@ impl Ledger {
     }
 
     /// Append an envelope, enforce invariants, and return a receipt.
+    ///
+    /// With an append policy, signatures are verified before the policy runs,
+    /// so only authenticated envelopes can cause a denial to be recorded.
     pub fn append(&self, env: Envelope) -> Result<AppendReceipt, Alert> {
+        if let Some(gate) = &self.gate {
+            verify_signers(&env, &self.registry).map_err(|err| {
+                warn!("append rejected channel={}: {err}", env.header.channel);
+                Alert::ValidationFailed(err.to_string())
+            })?;
+            let ctx = AppendContext {
+                log_len: self.log.len(),
+                tail: self.tail_hash(),
+            };
+            let decision = gate.policy.evaluate(&env, &ctx);
+            if !decision.allowed {
+                warn!(
+                    "append denied channel={} rule={:?}: {}",
+                    env.header.channel, decision.rule_id, decision.reason
+                );
+                if let Err(err) = self.record_denial(gate, &env, &decision) {
+                    error!("failed to record policy denial: {err:?}");
+                }
+                return Err(Alert::PolicyDenied {
+                    rule_id: decision.rule_id,
+                    reason: decision.reason,
+                });
+            }
+        }
+        self.commit(env)
+    }
+
+    fn record_denial(
+        &self,
+        gate: &AppendGate,
+        env: &Envelope,
+        decision: &AppendDecision,
+    ) -> Result<AppendReceipt, Alert> {
+        let effect = PolicyEffect::Block {
+            reason: decision.reason.clone(),
+        };
+        let record = PolicyDecision {
+            subject: envelope_hash(env),
+            scope: PolicyScope::Any,
+            bindings: vec![PolicyBinding {
+                policy: gate.policy.id(),
+                rule_id: decision.rule_id.clone(),
+                effect: effect.clone(),
+            }],
+            final_effect: effect,
+            observed_tags: Vec::new(),
+            origin: env
+                .signatures
+                .first()
+                .map(|sig| sig.signer)
+                .unwrap_or_default(),
+            routed_audience: None,
+            justification: None,
+            created_at: env.header.timestamp,
+        };
+        // The denied envelope may be stale; never regress the audit channel's clock.
+        let tail = self.log.read(self.log.len().saturating_sub(1), 1);
+        let created_at = tail.first().map_or(env.header.timestamp, |tail| {
+            tail.header.timestamp.max(env.header.timestamp)
+        });
+        let event = LedgerEvent::new(
+            EventKind::Policy(PolicyEvent::DecisionRecorded { decision: record }),
+            gate.auditor.verifying_key().to_bytes(),
+            Audience::Broadcast,
+            created_at,
+            DataSensitivity::Internal,
+            Vec::new(),
+            None,
+        )
+        .map_err(|err| Alert::ValidationFailed(err.to_string()))?;
+        let mut audit = event
+            .into_envelope(gate.audit_channel.clone(), gate.schema_version)
+            .map_err(|err| Alert::ValidationFailed(err.to_string()))?;
+        audit.header.prev = self.tail_hash();
+        signing::sign_envelope(&mut audit, &gate.auditor);
+        self.commit(audit)
+    }
+
+    fn commit(&self, env: Envelope) -> Result<AppendReceipt, Alert> {
         let index = self
             .log
             .append_with_index(env.clone(), &self.registry)
@@ -224,6 +352,104 @@ impl Ledger {
     pub fn offsets_for_channel(&self, channel: &str) -> Vec<usize> {
         self.index.offsets_for_channel(channel)
     }
+
+    /// Structured read-only queries backed by this ledger's domain index.
+    pub fn brainstem_query(&self) -> BrainstemQuery {
+        BrainstemQuery::new(Arc::new(self.log.clone())).with_index(self.index.clone())
+    }
+}
+
+/// Entries read per call when scanning a log without a channel index.
+const SCAN_CHUNK: usize = 256;
+
+/// Read-only per-channel queries over any append log.
+///
+/// Scans read the log in bounded chunks so writers are never blocked for
+/// the length of a whole query. When a [`DomainIndex`] is attached, only the
+/// channel's own offsets are read.
+#[derive(Clone)]
+pub struct BrainstemQuery {
+    log: Arc<dyn AppendLogStorage>,
+    index: Option<DomainIndex>,
+}
+
+impl BrainstemQuery {
+    /// Query `log` by scanning it.
+    pub fn new(log: Arc<dyn AppendLogStorage>) -> Self {
+        Self { log, index: None }
+    }
+
+    /// Resolve channels through `index` instead of scanning.
+    ///
+    /// The index must have been built from the same log.
+    pub fn with_index(mut self, index: DomainIndex) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Hash of the latest envelope on `channel`.
+    pub fn channel_tip(&self, channel: &str) -> Option<[u8; 32]> {
+        if let Some(index) = &self.index {
+            let offset = *index.offsets_for_channel(channel).last()?;
+            return self.log.read(offset, 1).first().map(envelope_hash);
+        }
+        let mut end = self.log.len();
+        while end > 0 {
+            let start = end.saturating_sub(SCAN_CHUNK);
+            let chunk = self.log.read(start, end - start);
+            if let Some(env) = chunk.iter().rev().find(|env| env.header.channel == channel) {
+                return Some(envelope_hash(env));
+            }
+            end = start;
+        }
+        None
+    }
+
+    /// Number of envelopes on `channel` with a timestamp at or after `ts`.
+    pub fn count_since(&self, channel: &str, ts: u64) -> usize {
+        let mut count = 0;
+        self.for_each_in_channel(channel, |env| {
+            if env.header.timestamp >= ts {
+                count += 1;
+            }
+        });
+        count
+    }
+
+    /// Envelopes on `channel` timestamped within `from_ts..=to_ts`, in log order.
+    pub fn range(&self, channel: &str, from_ts: u64, to_ts: u64) -> Vec<Envelope> {
+        let mut out = Vec::new();
+        self.for_each_in_channel(channel, |env| {
+            if (from_ts..=to_ts).contains(&env.header.timestamp) {
+                out.push(env.clone());
+            }
+        });
+        out
+    }
+
+    fn for_each_in_channel(&self, channel: &str, mut visit: impl FnMut(&Envelope)) {
+        if let Some(index) = &self.index {
+            for offset in index.offsets_for_channel(channel) {
+                if let Some(env) = self.log.read(offset, 1).first() {
+                    visit(env);
+                }
+            }
+            return;
+        }
+        let len = self.log.len();
+        let mut start = 0;
+        while start < len {
+            let chunk = self.log.read(start, SCAN_CHUNK);
+            if chunk.is_empty() {
+                break;
+            }
+            start += chunk.len();
+            chunk
+                .iter()
+                .filter(|env| env.header.channel == channel)
+                .for_each(&mut visit);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -242,19 +468,33 @@ mod tests {
                 allowed_signers: vec![pk],
                 require_attestations: false,
                 enforce_timestamp_ordering: true,
+                max_attestations_per_envelope: ledger_spec::DEFAULT_MAX_ATTESTATIONS,
+                max_future_skew: None,
+                ordering: ledger_spec::OrderingStrategy::StrictChain,
+                allowed_payload_types: None,
+                require_payload_type: false,
             },
         });
         reg
     }
 
     fn make_envelope(sk: &SigningKey, ts: u64, prev: Option<[u8; 32]>) -> (Envelope, [u8; 32]) {
+        make_channel_envelope(sk, "test", ts, prev)
+    }
+
+    fn make_channel_envelope(
+        sk: &SigningKey,
+        channel: &str,
+        ts: u64,
+        prev: Option<[u8; 32]>,
+    ) -> (Envelope, [u8; 32]) {
         let body = ledger_spec::EnvelopeBody {
             payload: serde_json::json!({"ts": ts}),
             payload_type: Some("telemetry".into()),
         };
         let body_hash = ledger_spec::hash_body(&body);
         let header = ledger_spec::EnvelopeHeader {
-            channel: "test".into(),
+            channel: channel.into(),
             version: 1,
             prev,
             body_hash,
@@ -308,4 +548,128 @@ mod tests {
         let err = ledger.append(env).unwrap_err();
         matches!(err, Alert::ValidationFailed(_));
     }
+
+    /// Appends ts 1..=6 alternating between `alpha` (odd) and `beta` (even).
+    fn multi_channel_ledger() -> (Ledger, Vec<Envelope>) {
+        let sk = SigningKey::generate(&mut OsRng);
+        let mut reg = registry_with(sk.verifying_key().to_bytes());
+        for name in ["alpha", "beta"] {
+            let policy = reg.policy_for("test").cloned().unwrap();
+            reg.upsert(ledger_spec::ChannelSpec {
+                name: name.into(),
+                policy,
+            });
+        }
+        let ledger = Ledger::new(reg);
+        let mut envs = Vec::new();
+        let mut prev = None;
+        for ts in 1..=6 {
+            let channel = if ts % 2 == 1 { "alpha" } else { "beta" };
+            let (env, _) = make_channel_envelope(&sk, channel, ts, prev);
+            prev = Some(envelope_hash(&env));
+            ledger.append(env.clone()).expect("append");
+            envs.push(env);
+        }
+        (ledger, envs)
+    }
+
+    #[test]
+    fn brainstem_query_tips_counts_and_ranges() {
+        let (ledger, envs) = multi_channel_ledger();
+        let scanned = BrainstemQuery::new(Arc::new(ledger.log.clone()));
+        for query in [scanned, ledger.brainstem_query()] {
+            assert_eq!(query.channel_tip("alpha"), Some(envelope_hash(&envs[4])));
+            assert_eq!(query.channel_tip("beta"), Some(envelope_hash(&envs[5])));
+            assert_eq!(query.channel_tip("gamma"), None);
+
+            assert_eq!(query.count_since("alpha", 0), 3);
+            assert_eq!(query.count_since("alpha", 3), 2);
+            assert_eq!(query.count_since("beta", 7), 0);
+
+            let range = query.range("beta", 2, 4);
+            assert_eq!(range, vec![envs[1].clone(), envs[3].clone()]);
+            assert_eq!(query.range("alpha", 3, 3), vec![envs[2].clone()]);
+            assert!(query.range("alpha", 6, 1).is_empty());
+        }
+    }
+
+    #[test]
+    fn denied_append_records_policy_decision() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let auditor = SigningKey::generate(&mut OsRng);
+        let mut reg = registry_with(sk.verifying_key().to_bytes());
+        reg.upsert(ledger_spec::ChannelSpec {
+            name: "policy.audit".into(),
+            policy: ledger_spec::ChannelPolicy {
+                min_signers: 1,
+                allowed_signers: vec![auditor.verifying_key().to_bytes()],
+                require_attestations: false,
+                enforce_timestamp_ordering: true,
+                max_attestations_per_envelope: ledger_spec::DEFAULT_MAX_ATTESTATIONS,
+                max_future_skew: None,
+                ordering: ledger_spec::OrderingStrategy::StrictChain,
+                allowed_payload_types: None,
+                require_payload_type: false,
+            },
+        });
+        let policy = crate::policy::ChannelRulePolicy::new().require_attestations("test");
+        let ledger = Ledger::new(reg).with_append_policy(
+            Arc::new(policy),
+            auditor.clone(),
+            "policy.audit",
+            1,
+        );
+
+        let (env, _) = make_envelope(&sk, 1, None);
+        let subject = envelope_hash(&env);
+
+        // Forged or unsigned envelopes are rejected before the policy runs
+        // and leave nothing on the log.
+        let mut forged = env.clone();
+        forged.signatures[0].signer = auditor.verifying_key().to_bytes();
+        let mut unsigned = env.clone();
+        unsigned.signatures.clear();
+        for bad in [forged, unsigned] {
+            assert!(matches!(
+                ledger.append(bad).unwrap_err(),
+                Alert::ValidationFailed(_)
+            ));
+        }
+        assert_eq!(ledger.log.len(), 0);
+
+        let err = ledger.append(env).unwrap_err();
+        assert_eq!(
+            err,
+            Alert::PolicyDenied {
+                rule_id: Some("require-attestations".into()),
+                reason: "channel test requires an attestation".into(),
+            }
+        );
+
+        // Only the audit record made it onto the log.
+        let resp = ledger
+            .query(SliceQuery {
+                from: 0,
+                limit: 10,
+                include_payloads: false,
+            })
+            .unwrap();
+        assert_eq!(resp.envelopes.len(), 1);
+        let audit = &resp.envelopes[0];
+        assert_eq!(audit.header.channel, "policy.audit");
+        assert_eq!(
+            audit.signatures[0].signer,
+            auditor.verifying_key().to_bytes()
+        );
+        let event = LedgerEvent::from_envelope(audit).unwrap();
+        let EventKind::Policy(PolicyEvent::DecisionRecorded { decision }) = event.kind else {
+            panic!("unexpected audit event: {:?}", event.kind);
+        };
+        assert_eq!(decision.subject, subject);
+        assert_eq!(
+            decision.bindings[0].rule_id.as_deref(),
+            Some("require-attestations")
+        );
+        assert!(matches!(decision.final_effect, PolicyEffect::Block { .. }));
+    }
 }
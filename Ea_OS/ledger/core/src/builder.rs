@@ -0,0 +1,230 @@
+//! Envelope construction with automatic chaining, timestamps, and signing.
+//!
+//! [`EnvelopeBuilder`] fills in everything `validate_envelope` checks that a
+//! producer would otherwise compute by hand: `body_hash`, the `prev` link to
+//! the current tip, a timestamp that never regresses, and the signature.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ed25519_dalek::SigningKey;
+use ledger_spec::{
+    envelope_hash, hash_body, Attestation, Channel, ChannelState, Envelope, EnvelopeBody,
+    EnvelopeHeader, SchemaVersion, Timestamp, ValidationError,
+};
+
+use crate::{signing, AppendLogStorage};
+
+/// Builds a correctly chained, signed envelope ready to append.
+///
+/// By default the envelope chains onto an empty log and is stamped with the
+/// current Unix time in milliseconds, raised to the previous timestamp if the
+/// clock is behind it.
+#[derive(Debug, Clone)]
+pub struct EnvelopeBuilder {
+    channel: Channel,
+    payload: serde_json::Value,
+    payload_type: Option<String>,
+    signer: SigningKey,
+    state: ChannelState,
+    timestamp: Option<Timestamp>,
+    version: SchemaVersion,
+    attestations: Vec<Attestation>,
+}
+
+impl EnvelopeBuilder {
+    /// Start an envelope on `channel` carrying `payload`, signed by `signer`.
+    pub fn new(
+        channel: impl Into<Channel>,
+        payload: serde_json::Value,
+        signer: &SigningKey,
+    ) -> Self {
+        Self {
+            channel: channel.into(),
+            payload,
+            payload_type: None,
+            signer: signer.clone(),
+            state: ChannelState::default(),
+            timestamp: None,
+            version: 1,
+            attestations: Vec::new(),
+        }
+    }
+
+    /// Chain onto `state`, typically the value returned by the last
+    /// `validate_envelope`.
+    pub fn after(mut self, state: ChannelState) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Chain onto the current tip of `log`.
+    pub fn after_log(self, log: &dyn AppendLogStorage) -> Self {
+        self.after_envelope(log.last_entry().as_ref())
+    }
+
+    /// Chain onto `prev`, or onto an empty log when `None`.
+    pub fn after_envelope(self, prev: Option<&Envelope>) -> Self {
+        self.after(ChannelState {
+            last_hash: prev.map(envelope_hash),
+            last_timestamp: prev.map(|env| env.header.timestamp),
+        })
+    }
+
+    /// Tag the payload with a type for schema validation and filtering.
+    pub fn payload_type(mut self, payload_type: impl Into<String>) -> Self {
+        self.payload_type = Some(payload_type.into());
+        self
+    }
+
+    /// Use `timestamp` instead of the current time.
+    pub fn timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Override the header schema version (default `1`).
+    pub fn version(mut self, version: SchemaVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Attach an already-signed attestation.
+    pub fn attestation(mut self, attestation: Attestation) -> Self {
+        self.attestations.push(attestation);
+        self
+    }
+
+    /// Produce the signed envelope.
+    ///
+    /// Fails with [`ValidationError::TimestampRegression`] if an explicit
+    /// timestamp is older than the one it chains onto.
+    pub fn build(self) -> Result<Envelope, ValidationError> {
+        let last = self.state.last_timestamp;
+        let timestamp = match self.timestamp {
+            Some(ts) if last.is_some_and(|last| ts < last) => {
+                return Err(ValidationError::TimestampRegression);
+            }
+            Some(ts) => ts,
+            None => now_ms().max(last.unwrap_or_default()),
+        };
+        let body = EnvelopeBody {
+            payload: self.payload,
+            payload_type: self.payload_type,
+        };
+        let mut env = Envelope {
+            header: EnvelopeHeader {
+                channel: self.channel,
+                version: self.version,
+                prev: self.state.last_hash,
+                body_hash: hash_body(&body),
+                timestamp,
+            },
+            body,
+            signatures: Vec::new(),
+            attestations: self.attestations,
+        };
+        signing::sign_envelope(&mut env, &self.signer);
+        Ok(env)
+    }
+}
+
+fn now_ms() -> Timestamp {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as Timestamp)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppendLog;
+    use ledger_spec::{
+        hash_attestation_statement, validate_envelope, AttestationKind, ChannelPolicy,
+        ChannelRegistry, ChannelSpec, OrderingStrategy,
+    };
+    use rand_core::OsRng;
+
+    fn registry(sk: &SigningKey, require_attestations: bool) -> ChannelRegistry {
+        let mut registry = ChannelRegistry::new();
+        registry.upsert(ChannelSpec {
+            name: "muscle_io".into(),
+            policy: ChannelPolicy {
+                min_signers: 1,
+                allowed_signers: vec![sk.verifying_key().to_bytes()],
+                require_attestations,
+                enforce_timestamp_ordering: true,
+                max_attestations_per_envelope: ledger_spec::DEFAULT_MAX_ATTESTATIONS,
+                max_future_skew: None,
+                ordering: OrderingStrategy::StrictChain,
+                allowed_payload_types: None,
+                require_payload_type: false,
+            },
+        });
+        registry
+    }
+
+    #[test]
+    fn builder_chains_three_envelopes_that_validate() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk, false);
+        let mut state = ChannelState::default();
+        for n in 0..3 {
+            let env = EnvelopeBuilder::new("muscle_io", serde_json::json!({ "n": n }), &sk)
+                .payload_type("test")
+                .after(state.clone())
+                .build()
+                .unwrap();
+            state = validate_envelope(&env, &reg, &state).unwrap();
+        }
+
+        // The same chain built against a log's tip appends cleanly.
+        let log = AppendLog::new();
+        for n in 0..3 {
+            let env = EnvelopeBuilder::new("muscle_io", serde_json::json!({ "n": n }), &sk)
+                .after_log(&log)
+                .build()
+                .unwrap();
+            log.append(env, &reg).unwrap();
+        }
+        assert_eq!(log.len(), 3);
+    }
+
+    #[test]
+    fn builder_rejects_regressing_timestamp_and_attaches_attestations() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let first = EnvelopeBuilder::new("muscle_io", serde_json::json!({}), &sk)
+            .timestamp(100)
+            .build()
+            .unwrap();
+        let err = EnvelopeBuilder::new("muscle_io", serde_json::json!({}), &sk)
+            .after_envelope(Some(&first))
+            .timestamp(99)
+            .build()
+            .unwrap_err();
+        assert_eq!(err, ValidationError::TimestampRegression);
+
+        let statement = AttestationKind::Custom {
+            label: "build".into(),
+            payload_hash: [7u8; 32],
+        };
+        let mut att = Attestation {
+            issuer: [0u8; 32],
+            statement_hash: hash_attestation_statement(&statement),
+            statement,
+            signature: [0u8; 64],
+        };
+        signing::sign_attestation(&mut att, &sk);
+        let env = EnvelopeBuilder::new("muscle_io", serde_json::json!({}), &sk)
+            .after_envelope(Some(&first))
+            .attestation(att)
+            .build()
+            .unwrap();
+        assert!(env.header.timestamp >= 100);
+        let state = ChannelState {
+            last_hash: Some(envelope_hash(&first)),
+            last_timestamp: Some(first.header.timestamp),
+        };
+        validate_envelope(&env, &registry(&sk, true), &state).unwrap();
+    }
+}
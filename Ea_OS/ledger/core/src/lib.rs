@@ -2,28 +2,37 @@
 //! Merkle segmenter, checkpoint writer, and replay validator.
 #![deny(missing_docs)]
 
-use std::collections::VecDeque;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::Context;
 use blake3::Hasher;
+use ed25519_dalek::{SigningKey, VerifyingKey};
 use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
+use serde_big_array::BigArray;
 
 use ledger_spec::{
-    envelope_hash, hash_body, Attestation, ChannelRegistry, ChannelState, Envelope, Signature,
-    ValidationError,
+    envelope_hash, hash_body, Attestation, ChannelPolicy, ChannelRegistry, ChannelState, Envelope,
+    EnvelopeBody, EnvelopeHeader, OrderingStrategy, Signature, Timestamp, ValidationError,
 };
 
 /// Base application orchestrators (audit terminal, privacy analyzer, agency assistant).
 pub mod apps;
 /// Brainstem ledger orchestration: append flow, query surfaces, and receipts.
 pub mod brainstem;
+/// Envelope construction with automatic chaining and signing.
+pub mod builder;
 /// Lifecycle management and enforcement for muscles.
 pub mod lifecycle;
+/// Append/read latency and append outcome metrics.
+#[cfg(feature = "observability")]
+pub mod observe;
 /// Pluggable policy enforcement and decision emission.
 pub mod policy;
 
@@ -39,6 +48,293 @@ pub enum AppendError {
     /// Storage or I/O failure.
     #[error("storage error: {0}")]
     Storage(#[from] anyhow::Error),
+    /// Payload rejected by the configured [`PayloadValidator`].
+    #[error("payload schema violation: {0}")]
+    Schema(String),
+    /// The log tip moved since the writer built its envelope.
+    #[error(transparent)]
+    Conflict(#[from] ConflictError),
+}
+
+/// Returned by [`AppendLog::append_expecting`] when the tip is not the one the
+/// writer chained onto; refetch the tip, rebuild the envelope, and retry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("append conflict: expected tip {expected:?}, found {actual:?}")]
+pub struct ConflictError {
+    /// Tip hash the writer expected.
+    pub expected: Option<[u8; 32]>,
+    /// Tip hash actually present in the log.
+    pub actual: Option<[u8; 32]>,
+}
+
+/// Checks envelope payloads against their declared `payload_type` before commit.
+///
+/// Envelopes without a `payload_type` are not passed to the validator.
+pub trait PayloadValidator: Send + Sync + std::fmt::Debug {
+    /// Accept `payload` for `payload_type`, or describe why it is malformed.
+    fn validate(&self, payload_type: &str, payload: &serde_json::Value) -> Result<(), String>;
+}
+
+/// Reject envelopes carrying fewer signatures than the channel requires
+/// before paying for body hashing and signature verification.
+///
+/// Under-signed envelopes report `InsufficientSignatures` even if they would
+/// also fail an earlier check (body hash, chain) in full validation.
+fn precheck_signers(env: &Envelope, registry: &ChannelRegistry) -> Result<(), ValidationError> {
+    let min_signers = match registry.policy_for(&env.header.channel) {
+        Some(policy) => policy.min_signers,
+        None => ChannelPolicy::default().min_signers,
+    };
+    if env.signatures.len() < min_signers {
+        return Err(ValidationError::InsufficientSignatures(
+            env.signatures.len(),
+        ));
+    }
+    Ok(())
+}
+
+/// Verify every signature on `env` and check its signers against the channel
+/// allowlist, without touching chain state.
+///
+/// Lets callers trust `env`'s signers before acting on it, e.g. recording a
+/// policy denial attributed to them.
+pub(crate) fn verify_signers(
+    env: &Envelope,
+    registry: &ChannelRegistry,
+) -> Result<(), ValidationError> {
+    precheck_signers(env, registry)?;
+    let policy = registry
+        .policy_for(&env.header.channel)
+        .cloned()
+        .unwrap_or_default();
+    let hash = envelope_hash(env);
+    for sig in &env.signatures {
+        let key =
+            VerifyingKey::from_bytes(&sig.signer).map_err(|_| ValidationError::SignatureInvalid)?;
+        key.verify_strict(&hash, &ed25519_dalek::Signature::from_bytes(&sig.signature))
+            .map_err(|_| ValidationError::SignatureInvalid)?;
+        if !policy.allowed_signers.is_empty() && !policy.allowed_signers.contains(&sig.signer) {
+            return Err(ValidationError::UnauthorizedSigner);
+        }
+    }
+    Ok(())
+}
+
+fn check_payload(
+    validator: &Option<Arc<dyn PayloadValidator>>,
+    env: &Envelope,
+) -> Result<(), AppendError> {
+    if let (Some(validator), Some(payload_type)) = (validator, &env.body.payload_type) {
+        validator
+            .validate(payload_type, &env.body.payload)
+            .map_err(AppendError::Schema)?;
+    }
+    Ok(())
+}
+
+/// Chain state `env` must validate against.
+///
+/// That is `tip`, unless `env`'s channel uses [`OrderingStrategy::Dag`] and its
+/// `prev` names an earlier entry; `ancestor` looks up that entry's timestamp.
+fn link_state(
+    tip: ChannelState,
+    env: &Envelope,
+    registry: &ChannelRegistry,
+    ancestor: impl FnOnce(&[u8; 32]) -> Option<Timestamp>,
+) -> ChannelState {
+    let dag = registry
+        .policy_for(&env.header.channel)
+        .is_some_and(|policy| policy.ordering == OrderingStrategy::Dag);
+    match env.header.prev {
+        Some(prev) if dag && tip.last_hash != Some(prev) => match ancestor(&prev) {
+            Some(timestamp) => ChannelState {
+                last_hash: Some(prev),
+                last_timestamp: Some(timestamp),
+            },
+            None => tip,
+        },
+        _ => tip,
+    }
+}
+
+/// Hash index over a log: where each entry hash first appears, and which
+/// entries no later entry names as `prev`.
+///
+/// Updated on append so [`OrderingStrategy::Dag`] ancestor lookups and
+/// [`AppendLogStorage::tips`] do not scan the log.
+#[derive(Debug, Default, Clone)]
+struct DagIndex {
+    /// Entry hash to the index of its first occurrence and its timestamp.
+    entries: HashMap<[u8; 32], (usize, Timestamp)>,
+    /// Tips by the index of their newest occurrence, so they iterate in log
+    /// order.
+    tips: BTreeMap<usize, [u8; 32]>,
+    /// Reverse of `tips`.
+    tip_at: HashMap<[u8; 32], usize>,
+}
+
+impl DagIndex {
+    fn build<'a>(entries: impl IntoIterator<Item = &'a Envelope>) -> Self {
+        let mut dag = Self::default();
+        for (index, env) in entries.into_iter().enumerate() {
+            dag.push(index, env, envelope_hash(env));
+        }
+        dag
+    }
+
+    /// Record `env`, hashing to `hash`, at `index`.
+    fn push(&mut self, index: usize, env: &Envelope, hash: [u8; 32]) {
+        if let Some(prev) = env.header.prev {
+            if let Some(parent) = self.tip_at.remove(&prev) {
+                self.tips.remove(&parent);
+            }
+        }
+        if let Some(earlier) = self.tip_at.insert(hash, index) {
+            self.tips.remove(&earlier);
+        }
+        self.tips.insert(index, hash);
+        self.entries
+            .entry(hash)
+            .or_insert((index, env.header.timestamp));
+    }
+
+    /// Forget `env`, which was at `index`, once it has left the log.
+    fn evict(&mut self, index: usize, env: &Envelope) {
+        let hash = envelope_hash(env);
+        if self.entries.get(&hash).is_some_and(|&(at, _)| at == index) {
+            self.entries.remove(&hash);
+        }
+        if self.tip_at.get(&hash) == Some(&index) {
+            self.tip_at.remove(&hash);
+            self.tips.remove(&index);
+        }
+    }
+
+    fn timestamp(&self, hash: &[u8; 32]) -> Option<Timestamp> {
+        self.entries.get(hash).map(|&(_, timestamp)| timestamp)
+    }
+
+    fn tips(&self) -> Vec<[u8; 32]> {
+        self.tips.values().copied().collect()
+    }
+}
+
+/// [`PayloadValidator`] backed by one JSON Schema per payload type.
+///
+/// Payload types without a registered schema are accepted.
+#[cfg(feature = "json-schema")]
+#[derive(Debug, Default)]
+pub struct JsonSchemaValidator {
+    schemas: HashMap<String, jsonschema::Validator>,
+}
+
+#[cfg(feature = "json-schema")]
+impl JsonSchemaValidator {
+    /// Create a validator with no schemas.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `schema` for `payload_type`, replacing any previous schema.
+    pub fn with_schema(
+        mut self,
+        payload_type: impl Into<String>,
+        schema: &serde_json::Value,
+    ) -> Result<Self, String> {
+        let compiled = jsonschema::validator_for(schema).map_err(|err| err.to_string())?;
+        self.schemas.insert(payload_type.into(), compiled);
+        Ok(self)
+    }
+}
+
+#[cfg(feature = "json-schema")]
+impl PayloadValidator for JsonSchemaValidator {
+    fn validate(&self, payload_type: &str, payload: &serde_json::Value) -> Result<(), String> {
+        match self.schemas.get(payload_type) {
+            Some(schema) => schema
+                .validate(payload)
+                .map_err(|err| format!("{payload_type}: {err}")),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Errors emitted when exporting or importing a ledger archive.
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+pub enum ArchiveError {
+    /// Requested range is empty or extends past the end of the log.
+    #[error("invalid export range {from}..{to} for log of length {len}")]
+    InvalidRange {
+        /// Start index (inclusive).
+        from: usize,
+        /// End index (exclusive).
+        to: usize,
+        /// Log length at export time.
+        len: usize,
+    },
+    /// Archive root does not match the root the caller trusts.
+    #[error("archive root does not match expected root")]
+    RootMismatch,
+    /// Archive is missing a receipt or an envelope for some position.
+    #[error("archive has {envelopes} envelopes but {receipts} receipts")]
+    Incomplete {
+        /// Number of envelopes in the archive.
+        envelopes: usize,
+        /// Number of receipts in the archive.
+        receipts: usize,
+    },
+    /// Entry at this log index fails its inclusion proof or chain link.
+    #[error("archive entry {0} failed verification")]
+    InvalidEntry(usize),
+}
+
+/// Self-contained, verifiable slice of a log: envelopes `from..from + n`
+/// plus per-entry receipts anchoring them to `root`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LedgerArchive {
+    /// Log index of the first envelope.
+    pub from: usize,
+    /// Log length the receipts were generated against.
+    pub leaf_count: usize,
+    /// Merkle root covering the whole log at export time.
+    pub root: [u8; 32],
+    /// Exported envelopes in log order.
+    pub envelopes: Vec<Envelope>,
+    /// Inclusion receipt for each exported envelope.
+    pub receipts: Vec<MerkleReceipt>,
+}
+
+/// Verify an archive against a trusted root and return its envelopes.
+///
+/// Every receipt must prove its envelope against `expected_root`, and the
+/// envelopes must form an unbroken hash chain.
+pub fn import_and_verify(
+    archive: &LedgerArchive,
+    expected_root: [u8; 32],
+) -> Result<Vec<Envelope>, ArchiveError> {
+    if archive.root != expected_root {
+        return Err(ArchiveError::RootMismatch);
+    }
+    if archive.envelopes.len() != archive.receipts.len() {
+        return Err(ArchiveError::Incomplete {
+            envelopes: archive.envelopes.len(),
+            receipts: archive.receipts.len(),
+        });
+    }
+    let mut prev: Option<[u8; 32]> = None;
+    for (offset, (env, receipt)) in archive.envelopes.iter().zip(&archive.receipts).enumerate() {
+        let index = archive.from + offset;
+        let linked = prev.is_none_or(|hash| env.header.prev == Some(hash));
+        if receipt.index != index
+            || receipt.leaf_count != archive.leaf_count
+            || !linked
+            || !receipt_covers(env, receipt, &expected_root)
+        {
+            return Err(ArchiveError::InvalidEntry(index));
+        }
+        prev = Some(envelope_hash(env));
+    }
+    Ok(archive.envelopes.clone())
 }
 
 /// Common log operations shared by in-memory and persistent implementations.
@@ -63,12 +359,402 @@ pub trait AppendLogStorage: Send + Sync {
     fn storage_usage_bytes(&self) -> Option<u64> {
         None
     }
+    /// Force every committed entry to durable storage.
+    ///
+    /// In-memory logs have nothing to flush.
+    fn flush(&self) -> Result<(), AppendError> {
+        Ok(())
+    }
+    /// Verify every entry's receipt against the current root.
+    ///
+    /// Returns `Err(index)` for the first entry whose receipt does not verify
+    /// or whose stored envelope no longer matches its committed leaf.
+    fn verify_integrity(&self) -> Result<(), usize> {
+        let Some(root) = self.merkle_root() else {
+            return Ok(());
+        };
+        for index in 0..self.len() {
+            let entry = self.read(index, 1).pop();
+            let receipt = self.receipt_for(index);
+            match (entry, receipt) {
+                (Some(env), Some(receipt)) if receipt_covers(&env, &receipt, &root) => {}
+                _ => return Err(index),
+            }
+        }
+        Ok(())
+    }
+    /// The newest entry, if any.
+    fn last_entry(&self) -> Option<Envelope> {
+        self.len()
+            .checked_sub(1)
+            .and_then(|last| self.read(last, 1).pop())
+    }
+    /// Iterate entries in order, reading them in chunks on demand so the
+    /// whole log is never held at once.
+    fn entries<'a>(&'a self) -> Box<dyn Iterator<Item = Envelope> + 'a> {
+        let mut offset = 0;
+        let mut pending = VecDeque::new();
+        Box::new(std::iter::from_fn(move || {
+            if pending.is_empty() {
+                pending.extend(self.read(offset, READ_CHUNK));
+                offset += pending.len();
+            }
+            pending.pop_front()
+        }))
+    }
+    /// Position in [`entries`](Self::entries) order and timestamp of the first
+    /// entry hashing to `hash`.
+    ///
+    /// The default scans the log; the built-in logs answer from an index kept
+    /// up to date on append.
+    fn find_entry(&self, hash: &[u8; 32]) -> Option<(usize, Timestamp)> {
+        self.entries()
+            .enumerate()
+            .find(|(_, env)| envelope_hash(env) == *hash)
+            .map(|(position, env)| (position, env.header.timestamp))
+    }
+    /// Iterate entries in order, validating each against its predecessor and
+    /// channel policy.
+    ///
+    /// Yields `Err` for the first entry that fails and then stops, so callers
+    /// can consume the valid prefix. Entries are read in chunks on demand and
+    /// not kept; [`OrderingStrategy::Dag`] branches are checked against
+    /// earlier entries through [`find_entry`](Self::find_entry).
+    fn validating_iter<'a>(
+        &'a self,
+        registry: &'a ChannelRegistry,
+    ) -> Box<dyn Iterator<Item = Result<Envelope, ValidationError>> + 'a> {
+        let mut entries = self.entries();
+        let mut state = Some(ChannelState::default());
+        let mut position = 0;
+        Box::new(std::iter::from_fn(move || {
+            let tip = state.take()?;
+            let env = entries.next()?;
+            let prev = link_state(tip, &env, registry, |hash| {
+                self.find_entry(hash)
+                    .filter(|&(at, _)| at < position)
+                    .map(|(_, timestamp)| timestamp)
+            });
+            match ledger_spec::validate_envelope(&env, registry, &prev) {
+                Ok(next) => {
+                    state = Some(next);
+                    position += 1;
+                    Some(Ok(env))
+                }
+                Err(err) => Some(Err(err)),
+            }
+        }))
+    }
+    /// Hashes of entries no later entry names as `prev`, in log order.
+    ///
+    /// A strict chain has a single tip; each branch opened on an
+    /// [`OrderingStrategy::Dag`] channel adds one until a later envelope
+    /// extends it. Merkle roots and receipts already bind this structure:
+    /// every leaf commits to its entry's `prev`.
+    ///
+    /// The default scans the log; the built-in logs keep the tip set up to
+    /// date on append.
+    fn tips(&self) -> Vec<[u8; 32]> {
+        let mut tips: Vec<[u8; 32]> = Vec::new();
+        for env in self.entries() {
+            if let Some(prev) = env.header.prev {
+                tips.retain(|tip| *tip != prev);
+            }
+            tips.push(envelope_hash(&env));
+        }
+        tips
+    }
+    /// Bundle envelopes `from..to` with receipts against the current root.
+    fn export_segment(&self, from: usize, to: usize) -> Result<LedgerArchive, ArchiveError> {
+        let len = self.len();
+        let invalid = ArchiveError::InvalidRange { from, to, len };
+        if from >= to || to > len {
+            return Err(invalid);
+        }
+        let root = self.merkle_root().ok_or(invalid.clone())?;
+        let receipts = (from..to)
+            .map(|index| self.receipt_for(index))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(invalid)?;
+        Ok(LedgerArchive {
+            from,
+            leaf_count: len,
+            root,
+            envelopes: self.read(from, to - from),
+            receipts,
+        })
+    }
+}
+
+/// Entries read per step by [`AppendLogStorage::entries`].
+const READ_CHUNK: usize = 256;
+
+/// Folds over an append log's entries into a materialized view.
+///
+/// Implemented for every [`AppendLogStorage`], including trait objects.
+/// Entries are streamed through [`AppendLogStorage::entries`], so only one
+/// chunk is held in memory at a time.
+pub trait AppendLogFold {
+    /// Fold every entry, in log order, into `init`.
+    fn fold_log<S>(&self, init: S, f: impl FnMut(S, &Envelope) -> S) -> S;
+    /// Fold the entries on `channel`, in log order, into `init`.
+    fn fold_channel<S>(&self, channel: &str, init: S, f: impl FnMut(S, &Envelope) -> S) -> S;
+}
+
+impl<T: AppendLogStorage + ?Sized> AppendLogFold for T {
+    fn fold_log<S>(&self, init: S, mut f: impl FnMut(S, &Envelope) -> S) -> S {
+        self.entries().fold(init, |acc, env| f(acc, &env))
+    }
+
+    fn fold_channel<S>(&self, channel: &str, init: S, mut f: impl FnMut(S, &Envelope) -> S) -> S {
+        self.entries()
+            .filter(|env| env.header.channel == channel)
+            .fold(init, |acc, env| f(acc, &env))
+    }
+}
+
+fn receipt_covers(env: &Envelope, receipt: &MerkleReceipt, root: &[u8; 32]) -> bool {
+    // Receipts may be untrusted input; never hash under an algorithm this
+    // build cannot compute.
+    receipt.algorithm.is_supported()
+        && receipt.root == *root
+        && receipt.leaf == receipt.algorithm.leaf_hash(env)
+        && hash_body(&env.body) == env.header.body_hash
+        && receipt.verify()
+}
+
+/// Bounded LRU of recently appended envelope hashes and their log indices.
+///
+/// Used on append paths to turn client retries of an already-committed
+/// envelope into a no-op that reports the original index. Lookups compare
+/// the full 32-byte envelope hash.
+#[derive(Debug, Clone)]
+pub struct DedupCache {
+    inner: Arc<Mutex<DedupState>>,
+}
+
+#[derive(Debug)]
+struct DedupState {
+    capacity: usize,
+    /// Recency stamp -> hash, oldest first
+    order: BTreeMap<u64, [u8; 32]>,
+    /// Hash -> (log index, current recency stamp)
+    indices: HashMap<[u8; 32], (usize, u64)>,
+    next_stamp: u64,
+}
+
+impl DedupCache {
+    /// Create a cache that remembers at most `capacity` hashes (minimum 1).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            inner: Arc::new(Mutex::new(DedupState {
+                capacity,
+                order: BTreeMap::new(),
+                indices: HashMap::with_capacity(capacity),
+                next_stamp: 0,
+            })),
+        }
+    }
+
+    /// Maximum number of hashes retained.
+    pub fn capacity(&self) -> usize {
+        self.inner.lock().capacity
+    }
+
+    /// Look up a previously recorded hash, refreshing its recency on hit.
+    pub fn lookup(&self, hash: &[u8; 32]) -> Option<usize> {
+        self.inner.lock().lookup(hash)
+    }
+
+    /// Record the index an envelope hash was committed at, evicting the
+    /// least recently used entry when full.
+    pub fn record(&self, hash: [u8; 32], index: usize) {
+        self.inner.lock().record(hash, index);
+    }
+
+    /// Run `append` unless `hash` is already recorded, then record the index
+    /// it returns. The cache stays locked throughout, so concurrent retries of
+    /// one envelope commit it once; the others get the original index back.
+    /// The flag is `false` when the append was skipped.
+    pub fn append_once<E>(
+        &self,
+        hash: [u8; 32],
+        append: impl FnOnce() -> Result<usize, E>,
+    ) -> Result<(usize, bool), E> {
+        let mut state = self.inner.lock();
+        if let Some(existing) = state.lookup(&hash) {
+            return Ok((existing, false));
+        }
+        let index = append()?;
+        state.record(hash, index);
+        Ok((index, true))
+    }
+}
+
+impl DedupState {
+    fn stamp(&mut self) -> u64 {
+        let stamp = self.next_stamp;
+        self.next_stamp += 1;
+        stamp
+    }
+
+    fn lookup(&mut self, hash: &[u8; 32]) -> Option<usize> {
+        let stamp = self.stamp();
+        let (index, last) = self.indices.get_mut(hash)?;
+        self.order.remove(last);
+        *last = stamp;
+        let index = *index;
+        self.order.insert(stamp, *hash);
+        Some(index)
+    }
+
+    fn record(&mut self, hash: [u8; 32], index: usize) {
+        let stamp = self.stamp();
+        match self.indices.insert(hash, (index, stamp)) {
+            Some((_, last)) => {
+                self.order.remove(&last);
+            }
+            None if self.indices.len() > self.capacity => {
+                if let Some((_, evicted)) = self.order.pop_first() {
+                    self.indices.remove(&evicted);
+                }
+            }
+            None => {}
+        }
+        self.order.insert(stamp, hash);
+    }
+}
+
+/// Hash function used for Merkle commitments and WAL checksums.
+///
+/// Chain links (`header.prev`) and body hashes are defined by `ledger-spec`
+/// and stay BLAKE3 regardless; this selects how leaves, interior nodes, and
+/// on-disk record checksums are computed.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MerkleAlgorithm {
+    /// BLAKE3 (default); leaves are exactly `envelope_hash`.
+    #[default]
+    Blake3,
+    /// SHA-256; requires the `sha256` feature.
+    Sha256,
+}
+
+impl MerkleAlgorithm {
+    /// Whether this build can compute the algorithm.
+    pub fn is_supported(self) -> bool {
+        match self {
+            MerkleAlgorithm::Blake3 => true,
+            MerkleAlgorithm::Sha256 => cfg!(feature = "sha256"),
+        }
+    }
+
+    fn ensure_supported(self) -> Result<(), AppendError> {
+        if !self.is_supported() {
+            return Err(
+                anyhow::anyhow!("merkle algorithm {self:?} requires the `sha256` feature").into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Hash `parts` in order under this algorithm.
+    fn digest(self, parts: &[&[u8]]) -> [u8; 32] {
+        match self {
+            MerkleAlgorithm::Blake3 => {
+                let mut hasher = Hasher::new();
+                for part in parts {
+                    hasher.update(part);
+                }
+                *hasher.finalize().as_bytes()
+            }
+            #[cfg(feature = "sha256")]
+            MerkleAlgorithm::Sha256 => {
+                use sha2::Digest;
+                let mut hasher = sha2::Sha256::new();
+                for part in parts {
+                    hasher.update(part);
+                }
+                hasher.finalize().into()
+            }
+            #[cfg(not(feature = "sha256"))]
+            // Logs reject unsupported algorithms at construction and receipts
+            // are checked before hashing, so this arm is never taken.
+            MerkleAlgorithm::Sha256 => unreachable!("sha256 callers check is_supported first"),
+        }
+    }
+
+    /// Merkle leaf for an envelope.
+    pub fn leaf_hash(self, env: &Envelope) -> [u8; 32] {
+        match self {
+            MerkleAlgorithm::Blake3 => envelope_hash(env),
+            MerkleAlgorithm::Sha256 => {
+                let header = serde_json::to_vec(&env.header)
+                    .expect("EnvelopeHeader serialization should not fail");
+                let header_hash = self.digest(&[b"ea-ledger:header", &header]);
+                let prev = env.header.prev.as_ref().map_or(&[][..], |hash| &hash[..]);
+                self.digest(&[
+                    b"ea-ledger:envelope",
+                    &header_hash,
+                    &env.header.body_hash,
+                    prev,
+                ])
+            }
+        }
+    }
+
+    fn parent(self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        self.digest(&[b"ea-ledger:merkle", left, right])
+    }
+
+    fn checksum(self, stored: &[u8]) -> [u8; 32] {
+        self.digest(&[CHECKSUM_DOMAIN, stored])
+    }
+
+    fn root_for(self, entries: &[Envelope]) -> Option<[u8; 32]> {
+        let leaves: Vec<[u8; 32]> = entries.iter().map(|env| self.leaf_hash(env)).collect();
+        self.root(&leaves)
+    }
+
+    fn root(self, items: &[[u8; 32]]) -> Option<[u8; 32]> {
+        let mut leaves = items.to_vec();
+        if leaves.is_empty() {
+            return None;
+        }
+        while leaves.len() > 1 {
+            leaves = leaves
+                .chunks(2)
+                .map(|chunk| match chunk {
+                    [left, right] => self.parent(left, right),
+                    [solo] => self.parent(solo, solo),
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+        leaves.into_iter().next()
+    }
+}
+
+/// Serializable copy of an in-memory log, used to fork or restore state.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LogSnapshot {
+    /// Entries in log order.
+    pub entries: Vec<Envelope>,
+    /// Merkle root over `entries` when the snapshot was taken.
+    pub root: Option<[u8; 32]>,
+    /// Algorithm `root` was computed with.
+    #[serde(default)]
+    pub algorithm: MerkleAlgorithm,
 }
 
 /// In-memory append-only log with hash chaining and Merkle checkpoints.
 #[derive(Debug, Default, Clone)]
 pub struct AppendLog {
     entries: Arc<RwLock<Vec<Envelope>>>,
+    /// Locked after `entries` when both are held.
+    dag: Arc<RwLock<DagIndex>>,
+    algorithm: MerkleAlgorithm,
+    validator: Option<Arc<dyn PayloadValidator>>,
 }
 
 impl AppendLog {
@@ -76,7 +762,69 @@ impl AppendLog {
     pub fn new() -> Self {
         Self {
             entries: Arc::new(RwLock::new(Vec::new())),
+            dag: Arc::default(),
+            algorithm: MerkleAlgorithm::default(),
+            validator: None,
+        }
+    }
+
+    /// Create a new empty log committing with `algorithm`.
+    pub fn with_algorithm(algorithm: MerkleAlgorithm) -> Result<Self, AppendError> {
+        algorithm.ensure_supported()?;
+        Ok(Self {
+            entries: Arc::new(RwLock::new(Vec::new())),
+            dag: Arc::default(),
+            algorithm,
+            validator: None,
+        })
+    }
+
+    /// Check typed payloads with `validator` before they are committed.
+    pub fn with_payload_validator(mut self, validator: Arc<dyn PayloadValidator>) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    /// Algorithm used for Merkle roots and receipts.
+    pub fn algorithm(&self) -> MerkleAlgorithm {
+        self.algorithm
+    }
+
+    /// Capture the current entries and root.
+    pub fn snapshot(&self) -> LogSnapshot {
+        let entries = self.entries.read().clone();
+        let root = self.algorithm.root_for(&entries);
+        LogSnapshot {
+            entries,
+            root,
+            algorithm: self.algorithm,
+        }
+    }
+
+    /// Rebuild a log from a snapshot without re-validating its entries.
+    pub fn from_snapshot(snapshot: LogSnapshot) -> Self {
+        Self {
+            dag: Arc::new(RwLock::new(DagIndex::build(&snapshot.entries))),
+            entries: Arc::new(RwLock::new(snapshot.entries)),
+            algorithm: snapshot.algorithm,
+            validator: None,
+        }
+    }
+
+    /// Rebuild a log from a snapshot, re-validating every entry against
+    /// `registry` and checking the recorded root.
+    pub fn from_snapshot_verified(
+        snapshot: LogSnapshot,
+        registry: &ChannelRegistry,
+    ) -> Result<Self, AppendError> {
+        let log = Self::with_algorithm(snapshot.algorithm)?;
+        for env in snapshot.entries {
+            log.validate_and_append(env, registry)?;
         }
+        if log.merkle_root() != snapshot.root {
+            return Err(anyhow::anyhow!("snapshot root does not match its entries").into());
+        }
+        Ok(log)
     }
 
     /// Append an envelope after validation.
@@ -84,19 +832,61 @@ impl AppendLog {
         self.append_with_index(env, registry).map(|_| ())
     }
 
+    /// Append only if the current tip hash is `expected_prev`.
+    ///
+    /// A writer that lost a race to another writer gets
+    /// [`AppendError::Conflict`] instead of a chain validation failure.
+    pub fn append_expecting(
+        &self,
+        env: Envelope,
+        registry: &ChannelRegistry,
+        expected_prev: Option<[u8; 32]>,
+    ) -> Result<usize, AppendError> {
+        self.commit(env, registry, Some(expected_prev))
+    }
+
     fn validate_and_append(
         &self,
         env: Envelope,
         registry: &ChannelRegistry,
+    ) -> Result<usize, AppendError> {
+        self.commit(env, registry, None)
+    }
+
+    /// Validate and push `env`, first checking the tip against
+    /// `expected_prev` when one is given.
+    fn commit(
+        &self,
+        env: Envelope,
+        registry: &ChannelRegistry,
+        expected_prev: Option<Option<[u8; 32]>>,
     ) -> Result<usize, AppendError> {
         let mut entries = self.entries.write();
         let prev_hash = entries.last().map(envelope_hash);
-        let prev_state = ChannelState {
+        if let Some(expected) = expected_prev {
+            if expected != prev_hash {
+                return Err(ConflictError {
+                    expected,
+                    actual: prev_hash,
+                }
+                .into());
+            }
+        }
+        let tip = ChannelState {
             last_hash: prev_hash,
             last_timestamp: entries.last().map(|e| e.header.timestamp),
         };
-        let _ = ledger_spec::validate_envelope(&env, registry, &prev_state)?;
+        let mut dag = self.dag.write();
+        let prev_state = link_state(tip, &env, registry, |hash| dag.timestamp(hash));
+        precheck_signers(&env, registry)?;
+        let next = ledger_spec::validate_envelope(&env, registry, &prev_state)?;
+        check_payload(&self.validator, &env)?;
         let index = entries.len();
+        dag.push(
+            index,
+            &env,
+            next.last_hash.unwrap_or_else(|| envelope_hash(&env)),
+        );
         entries.push(env);
         Ok(index)
     }
@@ -115,7 +905,11 @@ impl AppendLog {
         );
         let _guard = span.enter();
         let start = std::time::Instant::now();
+        #[cfg(feature = "observability")]
+        let channel = env.header.channel.clone();
         let res = self.validate_and_append(env, registry);
+        #[cfg(feature = "observability")]
+        observe::record_append(&channel, start.elapsed(), &res);
         let elapsed = start.elapsed().as_millis() as u64;
         span.record("latency_ms", &elapsed);
         match &res {
@@ -140,6 +934,8 @@ impl AppendLog {
         let start = std::time::Instant::now();
         let entries = self.entries.read();
         let out: Vec<_> = entries.iter().skip(offset).take(limit).cloned().collect();
+        #[cfg(feature = "observability")]
+        observe::record_read(start.elapsed());
         let elapsed = start.elapsed().as_millis() as u64;
         span.record("latency_ms", &elapsed);
         tracing::debug!(result_len = out.len(), "read completed");
@@ -154,11 +950,7 @@ impl AppendLog {
     /// Compute a Merkle root over current entries.
     pub fn merkle_root(&self) -> Option<[u8; 32]> {
         let entries = self.entries.read();
-        if entries.is_empty() {
-            return None;
-        }
-        let leaves: Vec<[u8; 32]> = entries.iter().map(envelope_hash).collect();
-        compute_merkle_root(&leaves)
+        self.algorithm.root_for(&entries)
     }
 
     /// Produce a Merkle receipt for a specific log entry.
@@ -167,8 +959,11 @@ impl AppendLog {
         if index >= entries.len() {
             return None;
         }
-        let leaves: Vec<[u8; 32]> = entries.iter().map(envelope_hash).collect();
-        MerkleReceipt::from_leaves(&leaves, index)
+        let leaves: Vec<[u8; 32]> = entries
+            .iter()
+            .map(|env| self.algorithm.leaf_hash(env))
+            .collect();
+        MerkleReceipt::from_leaves_with(self.algorithm, &leaves, index)
     }
 }
 
@@ -204,191 +999,129 @@ impl AppendLogStorage for AppendLog {
     fn storage_usage_bytes(&self) -> Option<u64> {
         Some(0)
     }
-}
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
-struct PersistentMetadata {
-    length: usize,
-    root: Option<[u8; 32]>,
+    fn find_entry(&self, hash: &[u8; 32]) -> Option<(usize, Timestamp)> {
+        self.dag.read().entries.get(hash).copied()
+    }
+
+    fn tips(&self) -> Vec<[u8; 32]> {
+        self.dag.read().tips()
+    }
 }
 
+/// Bounded in-memory log for ephemeral channels.
+///
+/// Keeps at most `capacity` of the most recent envelopes and drops the oldest
+/// on overflow. Indices stay absolute: [`AppendLogStorage::append_with_index`]
+/// returns the envelope's position since the log was created, `read` and
+/// `receipt_for` take the same positions and return nothing for entries that
+/// have aged out, and [`len`](AppendLogStorage::len) is the number of retained
+/// entries. New envelopes still chain onto the newest appended entry, and the
+/// Merkle root covers only the current window.
+///
+/// With a TTL set via [`with_ttl`](Self::with_ttl), [`sweep`](Self::sweep)
+/// also ages out envelopes by timestamp, whether or not the ring is full.
 #[derive(Debug)]
-struct PersistentState {
-    entries: Vec<Envelope>,
-    wal_entries: usize,
+pub struct RingAppendLog {
+    state: RwLock<RingState>,
+    capacity: usize,
+    ttl: Option<Duration>,
+    algorithm: MerkleAlgorithm,
 }
 
-impl PersistentMetadata {
-    fn from_state(state: &PersistentState) -> Self {
+#[derive(Debug, Default)]
+struct RingState {
+    entries: VecDeque<Envelope>,
+    /// Absolute index of `entries[0]`.
+    first_index: usize,
+    /// Newest appended entry, kept after it ages out so the chain continues.
+    tip: ChannelState,
+    /// Index over the retained entries only.
+    dag: DagIndex,
+}
+
+impl RingAppendLog {
+    /// Create an empty log retaining at most `capacity` envelopes (at least one).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
         Self {
-            length: state.entries.len(),
-            root: merkle_root_for(&state.entries),
+            state: RwLock::new(RingState {
+                entries: VecDeque::with_capacity(capacity),
+                first_index: 0,
+                tip: ChannelState::default(),
+                dag: DagIndex::default(),
+            }),
+            capacity,
+            ttl: None,
+            algorithm: MerkleAlgorithm::default(),
         }
     }
-}
-
-/// Disk-backed append log with checksummed WAL and segment compaction.
-#[derive(Debug, Clone)]
-pub struct PersistentAppendLog {
-    state: Arc<RwLock<PersistentState>>,
-    wal: Arc<Mutex<File>>,
-    segments: Arc<Mutex<File>>,
-    dir: PathBuf,
-    meta_path: PathBuf,
-    wal_path: PathBuf,
-    segment_size: usize,
-}
 
-const DEFAULT_SEGMENT_SIZE: usize = 1024;
-const CHECKSUM_DOMAIN: &[u8] = b"ea-ledger:wal:v1";
+    /// Let [`sweep`](Self::sweep) drop envelopes older than `ttl`.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
 
-fn read_metadata_file(path: &Path) -> Option<PersistentMetadata> {
-    fs::read(path)
-        .ok()
-        .and_then(|bytes| serde_json::from_slice::<PersistentMetadata>(&bytes).ok())
-}
+    /// Maximum number of envelopes retained.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
 
-impl PersistentAppendLog {
-    /// Open (or create) a persistent log at `dir` with the default segment size.
-    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, AppendError> {
-        Self::open_with_segment_size(dir, DEFAULT_SEGMENT_SIZE)
+    /// Age after which [`sweep`](Self::sweep) drops an envelope, if any.
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
     }
 
-    /// Open (or create) a persistent log at `dir` with a custom segment size.
-    pub fn open_with_segment_size<P: AsRef<Path>>(
-        dir: P,
-        segment_size: usize,
-    ) -> Result<Self, AppendError> {
-        let dir = dir.as_ref();
-        let segment_size = segment_size.max(1);
-        fs::create_dir_all(dir)
-            .with_context(|| format!("failed to create log directory {}", dir.display()))?;
-        let wal_path = dir.join("append.wal");
-        let segments_path = dir.join("segments.bin");
-        let meta_path = dir.join("meta.json");
-        let wal_entries = read_records(&wal_path)?;
-        let mut entries = read_records(&segments_path)?;
-        let wal_count = wal_entries.len();
-        entries.extend(wal_entries);
-        let current_meta = PersistentMetadata {
-            length: entries.len(),
-            root: merkle_root_for(&entries),
-        };
-        if let Some(on_disk) = read_metadata_file(&meta_path) {
-            if on_disk != current_meta {
-                return Err(anyhow::anyhow!("persistent log metadata mismatch during recovery").into());
-            }
-        }
-
-        let wal = Arc::new(Mutex::new(
-            OpenOptions::new()
-                .create(true)
-                .append(true)
-                .read(true)
-                .open(&wal_path)
-                .with_context(|| format!("failed to open WAL {}", wal_path.display()))?,
-        ));
-        let segments = Arc::new(Mutex::new(
-            OpenOptions::new()
-                .create(true)
-                .append(true)
-                .read(true)
-                .open(&segments_path)
-                .with_context(|| format!("failed to open segments {}", segments_path.display()))?,
-        ));
-        let log = Self {
-            state: Arc::new(RwLock::new(PersistentState {
-                entries,
-                wal_entries: wal_count,
-            })),
-            wal,
-            segments,
-            dir: dir.to_path_buf(),
-            meta_path,
-            wal_path,
-            segment_size,
+    /// Drop envelopes whose timestamp is older than `now - ttl`, returning how
+    /// many were removed.
+    ///
+    /// `now` is in the same unix-epoch milliseconds as envelope timestamps.
+    /// Indices stay absolute, so swept offsets read as empty like ones that
+    /// overflowed the ring. Does nothing without a TTL.
+    pub fn sweep(&self, now: Timestamp) -> usize {
+        let Some(ttl) = self.ttl else {
+            return 0;
         };
-        log.ensure_metadata()?;
-        Ok(log)
-    }
-
-    fn ensure_metadata(&self) -> Result<(), AppendError> {
-        let state = self.state.read();
-        let expected = PersistentMetadata::from_state(&state);
-        match read_metadata_file(&self.meta_path) {
-            Some(on_disk) if on_disk == expected => Ok(()),
-            _ => self.persist_metadata(&expected),
+        let ttl_ms = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX);
+        let cutoff = now.saturating_sub(ttl_ms);
+        let mut state = self.state.write();
+        let mut removed = 0;
+        while state
+            .entries
+            .front()
+            .is_some_and(|env| env.header.timestamp < cutoff)
+        {
+            state.evict_oldest();
+            removed += 1;
         }
+        removed
     }
 
-    fn persist_metadata(&self, meta: &PersistentMetadata) -> Result<(), AppendError> {
-        let tmp = self.meta_path.with_extension("tmp");
-        let encoded =
-            serde_json::to_vec(meta).context("failed to serialize persistent log metadata")?;
-        fs::write(&tmp, encoded)
-            .with_context(|| format!("failed to write metadata {}", tmp.display()))?;
-        fs::rename(&tmp, &self.meta_path).with_context(|| {
-            format!(
-                "failed to atomically persist metadata {} -> {}",
-                tmp.display(),
-                self.meta_path.display()
-            )
-        })?;
-        Ok(())
+    /// Absolute index of the oldest retained envelope.
+    pub fn first_index(&self) -> usize {
+        self.state.read().first_index
     }
 
-    fn write_wal(&self, env: &Envelope) -> Result<(), AppendError> {
-        let mut wal = self.wal.lock();
-        let bytes = serde_json::to_vec(env).context("failed to serialize envelope")?;
-        let mut hasher = Hasher::new();
-        hasher.update(CHECKSUM_DOMAIN);
-        hasher.update(&bytes);
-        let digest = hasher.finalize();
-        let len = (bytes.len() as u32).to_be_bytes();
-        wal.write_all(&len)
-            .context("failed to write wal length prefix")?;
-        wal.write_all(digest.as_bytes())
-            .context("failed to write wal checksum")?;
-        wal.write_all(&bytes)
-            .context("failed to write wal entry body")?;
-        wal.flush().context("failed to flush wal")?;
-        wal.sync_all().context("failed to sync wal to disk")?;
-        Ok(())
+    fn window_leaves(&self, state: &RingState) -> Vec<[u8; 32]> {
+        state
+            .entries
+            .iter()
+            .map(|env| self.algorithm.leaf_hash(env))
+            .collect()
     }
+}
 
-    fn compact_segments(&self) -> Result<(), AppendError> {
-        let wal_bytes = fs::read(&self.wal_path).unwrap_or_default();
-        if wal_bytes.is_empty() {
-            return Ok(());
-        }
-        {
-            let mut segments = self.segments.lock();
-            segments
-                .write_all(&wal_bytes)
-                .context("failed to write compacted wal into segments")?;
-            segments
-                .sync_all()
-                .context("failed to sync compacted segments")?;
-        }
-        {
-            let mut wal = self.wal.lock();
-            wal.set_len(0).context("failed to truncate wal")?;
-            wal.seek(SeekFrom::End(0))
-                .context("failed to reset wal cursor")?;
-            wal.sync_all().context("failed to sync truncated wal")?;
+impl RingState {
+    fn evict_oldest(&mut self) {
+        if let Some(env) = self.entries.pop_front() {
+            self.dag.evict(self.first_index, &env);
+            self.first_index += 1;
         }
-        let mut state = self.state.write();
-        state.wal_entries = 0;
-        Ok(())
-    }
-
-    #[cfg(test)]
-    fn metadata(&self) -> Option<PersistentMetadata> {
-        read_metadata_file(&self.meta_path)
     }
 }
 
-impl AppendLogStorage for PersistentAppendLog {
+impl AppendLogStorage for RingAppendLog {
     fn append(&self, env: Envelope, registry: &ChannelRegistry) -> Result<(), AppendError> {
         self.append_with_index(env, registry).map(|_| ())
     }
@@ -398,62 +1131,37 @@ impl AppendLogStorage for PersistentAppendLog {
         env: Envelope,
         registry: &ChannelRegistry,
     ) -> Result<usize, AppendError> {
-        let span = tracing::info_span!(
-            "append_persistent_log",
-            channel = %env.header.channel,
-            offset = tracing::field::Empty,
-            latency_ms = tracing::field::Empty
-        );
-        let _guard = span.enter();
-        let start = std::time::Instant::now();
         let mut state = self.state.write();
-        let prev_hash = state.entries.last().map(envelope_hash);
-        let prev_state = ChannelState {
-            last_hash: prev_hash,
-            last_timestamp: state.entries.last().map(|e| e.header.timestamp),
-        };
-        let _ = ledger_spec::validate_envelope(&env, registry, &prev_state)?;
-        let index = state.entries.len();
-        self.write_wal(&env)?;
-        state.entries.push(env);
-        state.wal_entries += 1;
-        let meta = PersistentMetadata {
-            length: state.entries.len(),
-            root: merkle_root_for(&state.entries),
-        };
-        drop(state);
-        self.persist_metadata(&meta)?;
-        if meta.length % self.segment_size == 0 {
-            self.compact_segments()?;
+        // Parents that have aged out can no longer be branched from.
+        let prev_state = link_state(state.tip.clone(), &env, registry, |hash| {
+            state.dag.timestamp(hash)
+        });
+        precheck_signers(&env, registry)?;
+        state.tip = ledger_spec::validate_envelope(&env, registry, &prev_state)?;
+        let index = state.first_index + state.entries.len();
+        let hash = state.tip.last_hash.unwrap_or_else(|| envelope_hash(&env));
+        state.dag.push(index, &env, hash);
+        state.entries.push_back(env);
+        if state.entries.len() > self.capacity {
+            state.evict_oldest();
         }
-        let elapsed = start.elapsed().as_millis() as u64;
-        span.record("offset", &(index as u64));
-        span.record("latency_ms", &elapsed);
-        tracing::debug!("append committed to wal");
         Ok(index)
     }
 
     fn read(&self, offset: usize, limit: usize) -> Vec<Envelope> {
-        let span = tracing::info_span!(
-            "read_persistent_log",
-            offset = offset as u64,
-            limit = limit as u64,
-            latency_ms = tracing::field::Empty
-        );
-        let _guard = span.enter();
-        let start = std::time::Instant::now();
         let state = self.state.read();
-        let out: Vec<Envelope> = state
+        let end = offset.saturating_add(limit);
+        let start = offset.max(state.first_index);
+        if start >= end {
+            return Vec::new();
+        }
+        state
             .entries
             .iter()
-            .skip(offset)
-            .take(limit)
+            .skip(start - state.first_index)
+            .take(end - start)
             .cloned()
-            .collect();
-        let elapsed = start.elapsed().as_millis() as u64;
-        span.record("latency_ms", &elapsed);
-        tracing::debug!(result_len = out.len(), "read completed");
-        out
+            .collect()
     }
 
     fn len(&self) -> usize {
@@ -462,406 +1170,2828 @@ impl AppendLogStorage for PersistentAppendLog {
 
     fn merkle_root(&self) -> Option<[u8; 32]> {
         let state = self.state.read();
-        merkle_root_for(&state.entries)
+        self.algorithm.root(&self.window_leaves(&state))
     }
 
     fn receipt_for(&self, index: usize) -> Option<MerkleReceipt> {
         let state = self.state.read();
-        if index >= state.entries.len() {
+        let position = index.checked_sub(state.first_index)?;
+        if position >= state.entries.len() {
             return None;
         }
-        let leaves: Vec<[u8; 32]> = state.entries.iter().map(envelope_hash).collect();
-        MerkleReceipt::from_leaves(&leaves, index)
+        let leaves = self.window_leaves(&state);
+        MerkleReceipt::from_leaves_with(self.algorithm, &leaves, position)
     }
 
     fn storage_usage_bytes(&self) -> Option<u64> {
-        let wal = std::fs::metadata(&self.wal_path)
-            .map(|m| m.len())
-            .unwrap_or(0);
-        let seg = std::fs::metadata(&self.dir.join("segments.bin"))
-            .map(|m| m.len())
-            .unwrap_or(0);
-        let meta = std::fs::metadata(&self.meta_path)
-            .map(|m| m.len())
-            .unwrap_or(0);
-        Some(wal + seg + meta)
+        Some(0)
     }
-}
 
-fn read_records(path: &Path) -> Result<Vec<Envelope>, AppendError> {
-    if !path.exists() {
-        return Ok(Vec::new());
-    }
-    let mut file =
-        File::open(path).with_context(|| format!("failed to open log file {}", path.display()))?;
-    let mut buf = Vec::new();
-    file.read_to_end(&mut buf)
-        .with_context(|| format!("failed to read log file {}", path.display()))?;
-    let mut cursor = 0usize;
-    let mut items = Vec::new();
-    while cursor < buf.len() {
-        if cursor + 4 > buf.len() {
-            return Err(anyhow::anyhow!("truncated record length in {}", path.display()).into());
-        }
-        let len = u32::from_be_bytes(buf[cursor..cursor + 4].try_into().unwrap()) as usize;
-        cursor += 4;
-        if cursor + 32 + len > buf.len() {
-            return Err(anyhow::anyhow!("truncated record body in {}", path.display()).into());
-        }
-        let checksum: [u8; 32] = buf[cursor..cursor + 32].try_into().unwrap();
-        cursor += 32;
-        let payload = &buf[cursor..cursor + len];
-        cursor += len;
-        let mut hasher = Hasher::new();
-        hasher.update(CHECKSUM_DOMAIN);
-        hasher.update(payload);
-        let digest = hasher.finalize();
-        if *digest.as_bytes() != checksum {
-            return Err(anyhow::anyhow!("checksum mismatch in {}", path.display()).into());
+    fn verify_integrity(&self) -> Result<(), usize> {
+        let Some(root) = self.merkle_root() else {
+            return Ok(());
+        };
+        let first = self.first_index();
+        for index in first..first + self.len() {
+            let entry = self.read(index, 1).pop();
+            let receipt = self.receipt_for(index);
+            match (entry, receipt) {
+                (Some(env), Some(receipt)) if receipt_covers(&env, &receipt, &root) => {}
+                _ => return Err(index),
+            }
         }
-        let env: Envelope =
-            serde_json::from_slice(payload).context("failed to decode envelope from wal")?;
-        items.push(env);
+        Ok(())
     }
-    Ok(items)
-}
-/// Checkpoint record capturing merkle root and length.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct Checkpoint {
-    /// Log length at checkpoint.
-    pub length: usize,
-    /// Merkle root.
-    pub root: [u8; 32],
-}
 
-/// Checkpoint writer produces periodic checkpoints.
-#[derive(Debug, Default)]
-pub struct CheckpointWriter {
-    last_len: usize,
-}
+    fn entries<'a>(&'a self) -> Box<dyn Iterator<Item = Envelope> + 'a> {
+        let window: Vec<_> = self.state.read().entries.iter().cloned().collect();
+        Box::new(window.into_iter())
+    }
 
-impl CheckpointWriter {
-    /// Create new writer.
-    pub fn new() -> Self {
-        Self { last_len: 0 }
+    fn last_entry(&self) -> Option<Envelope> {
+        self.state.read().entries.back().cloned()
     }
 
-    /// Emit a checkpoint if log advanced by at least `interval`.
-    pub fn maybe_checkpoint(&mut self, log: &AppendLog, interval: usize) -> Option<Checkpoint> {
-        let len = log.len();
-        if len >= self.last_len + interval {
-            let root = log.merkle_root()?;
-            self.last_len = len;
-            return Some(Checkpoint { length: len, root });
-        }
-        None
+    fn find_entry(&self, hash: &[u8; 32]) -> Option<(usize, Timestamp)> {
+        let state = self.state.read();
+        let &(index, timestamp) = state.dag.entries.get(hash)?;
+        Some((index - state.first_index, timestamp))
+    }
+
+    fn tips(&self) -> Vec<[u8; 32]> {
+        self.state.read().dag.tips()
     }
 }
 
-/// Replay validator detects tampering or reordering.
-pub struct ReplayValidator {
-    registry: ChannelRegistry,
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+struct PersistentMetadata {
+    length: usize,
+    root: Option<[u8; 32]>,
+    #[serde(default)]
+    algorithm: MerkleAlgorithm,
 }
 
-impl ReplayValidator {
-    /// Create new validator.
-    pub fn new(registry: ChannelRegistry) -> Self {
-        Self { registry }
-    }
+#[derive(Debug)]
+struct PersistentState {
+    entries: Vec<Envelope>,
+    wal_entries: usize,
+    dag: DagIndex,
+}
 
-    /// Validate a sequence of envelopes starting from empty state.
-    pub fn validate_sequence(&self, seq: &[Envelope]) -> Result<(), ValidationError> {
-        let mut state = ChannelState::default();
-        for env in seq {
-            state = ledger_spec::validate_envelope(env, &self.registry, &state)?;
+impl PersistentMetadata {
+    fn from_state(state: &PersistentState, algorithm: MerkleAlgorithm) -> Self {
+        Self {
+            length: state.entries.len(),
+            root: algorithm.root_for(&state.entries),
+            algorithm,
         }
-        Ok(())
     }
 }
 
-/// Envelope signer and verifier helpers.
-pub mod signing {
-    use super::*;
-    use ed25519_dalek::{Signer, SigningKey};
-
-    /// Sign an envelope (header/body) with the provided key.
-    pub fn sign_envelope(env: &mut Envelope, signer: &SigningKey) {
-        let env_hash = envelope_hash(env);
-        let sig = signer.sign(&env_hash);
-        env.signatures.push(Signature {
-            signer: signer.verifying_key().to_bytes(),
-            signature: sig.to_bytes(),
-        });
-    }
+/// When [`PersistentAppendLog`] fsyncs its WAL.
+///
+/// Weaker policies trade durability for throughput: appends acknowledged
+/// since the last fsync can be lost on power failure or kernel crash (a
+/// process crash alone loses nothing, since writes are already in the page
+/// cache). Recovery still sees a checksummed prefix, never a torn record.
+/// Compaction and [`AppendLogStorage::flush`] always fsync first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SyncPolicy {
+    /// Fsync after every append; an acknowledged append is durable.
+    #[default]
+    Always,
+    /// Fsync after every `n`th append; up to `n - 1` appends are at risk.
+    /// Pending appends are also synced when the log is dropped.
+    EveryN(usize),
+    /// Fsync on the first append at least this long after the previous fsync.
+    /// There is no background timer, so the tail after the last append stays
+    /// at risk until the next append, compaction, flush, or drop.
+    Interval(Duration),
+    /// Leave WAL writeback to the OS except during compaction and `flush`.
+    /// Only suitable for logs that can be rebuilt from elsewhere.
+    Never,
+}
 
-    /// Attach an attestation signature over its statement hash.
-    pub fn sign_attestation(att: &mut Attestation, signer: &SigningKey) {
-        let sig = signer.sign(&att.statement_hash);
-        att.signature = sig.to_bytes();
-        att.issuer = signer.verifying_key().to_bytes();
-    }
+/// How [`PersistentAppendLog`] treats a damaged WAL tail when opening.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WalRecovery {
+    /// Fail the open on any truncated or checksum-failing WAL record.
+    #[default]
+    Strict,
+    /// Keep the WAL records before the first damaged one and cut the WAL
+    /// back to them, so a torn final write does not make the log unreadable.
+    /// Segments and metadata are still checked strictly.
+    RecoverPrefix,
 }
 
-/// Append-only log segmenter that emits Merkle checkpoints.
+/// Shared fsync bookkeeping for one WAL, applying its [`SyncPolicy`].
 #[derive(Debug)]
-pub struct MerkleSegmenter {
-    window: usize,
-    queue: VecDeque<[u8; 32]>,
+struct WalSync {
+    wal: Arc<Mutex<File>>,
+    policy: SyncPolicy,
+    state: Mutex<WalSyncState>,
 }
 
-impl MerkleSegmenter {
-    /// Create a new segmenter with a fixed window size.
-    pub fn new(window: usize) -> Self {
+#[derive(Debug)]
+struct WalSyncState {
+    pending: usize,
+    last_sync: Instant,
+    syncs: u64,
+}
+
+impl WalSync {
+    fn new(wal: Arc<Mutex<File>>, policy: SyncPolicy) -> Self {
         Self {
-            window,
-            queue: VecDeque::new(),
+            wal,
+            policy,
+            state: Mutex::new(WalSyncState {
+                pending: 0,
+                last_sync: Instant::now(),
+                syncs: 0,
+            }),
         }
     }
 
-    /// Push a new envelope hash and emit a segment root if window filled.
-    pub fn push(&mut self, env_hash: [u8; 32]) -> Option<[u8; 32]> {
-        self.queue.push_back(env_hash);
-        if self.queue.len() == self.window {
-            let root = compute_merkle(&self.queue.make_contiguous());
-            self.queue.clear();
-            Some(root)
-        } else {
-            None
+    /// Count a record just written to `wal` and fsync if the policy says so.
+    fn record_write(&self, wal: &File) -> Result<(), AppendError> {
+        let mut state = self.state.lock();
+        state.pending += 1;
+        let due = match self.policy {
+            SyncPolicy::Always => true,
+            SyncPolicy::EveryN(n) => state.pending >= n.max(1),
+            SyncPolicy::Interval(interval) => state.last_sync.elapsed() >= interval,
+            SyncPolicy::Never => false,
+        };
+        if due {
+            Self::sync(wal, &mut state)?;
         }
+        Ok(())
     }
-}
 
-fn compute_merkle(items: &[[u8; 32]]) -> [u8; 32] {
-    compute_merkle_root(items).unwrap_or([0u8; 32])
+    /// Fsync `wal` if any write is not yet durable.
+    fn sync_pending(&self, wal: &File) -> Result<(), AppendError> {
+        let mut state = self.state.lock();
+        if state.pending > 0 {
+            Self::sync(wal, &mut state)?;
+        }
+        Ok(())
+    }
+
+    fn sync(wal: &File, state: &mut WalSyncState) -> Result<(), AppendError> {
+        wal.sync_all().context("failed to sync wal to disk")?;
+        state.pending = 0;
+        state.last_sync = Instant::now();
+        state.syncs += 1;
+        Ok(())
+    }
 }
 
-fn merkle_root_for(entries: &[Envelope]) -> Option<[u8; 32]> {
-    if entries.is_empty() {
-        return None;
+impl Drop for WalSync {
+    fn drop(&mut self) {
+        if matches!(self.policy, SyncPolicy::EveryN(_) | SyncPolicy::Interval(_)) {
+            let wal = self.wal.lock();
+            if let Err(err) = self.sync_pending(&wal) {
+                tracing::warn!(error = %err, "failed to sync pending wal writes on drop");
+            }
+        }
     }
-    let leaves: Vec<[u8; 32]> = entries.iter().map(envelope_hash).collect();
-    compute_merkle_root(&leaves)
 }
 
-fn merkle_parent(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
-    let mut hasher = Hasher::new();
-    hasher.update(b"ea-ledger:merkle");
-    hasher.update(left);
-    hasher.update(right);
-    *hasher.finalize().as_bytes()
+/// Disk-backed append log with checksummed WAL and segment compaction.
+#[derive(Debug, Clone)]
+pub struct PersistentAppendLog {
+    state: Arc<RwLock<PersistentState>>,
+    wal: Arc<Mutex<File>>,
+    wal_sync: Arc<WalSync>,
+    segments: Arc<Mutex<File>>,
+    dir: PathBuf,
+    meta_path: PathBuf,
+    meta_log: Arc<Mutex<File>>,
+    meta_log_path: PathBuf,
+    wal_path: PathBuf,
+    bodies: Arc<BodyStore>,
+    segment_size: usize,
+    dedup: Option<DedupCache>,
+    compression: Option<i32>,
+    body_dedup: bool,
+    algorithm: MerkleAlgorithm,
+    validator: Option<Arc<dyn PayloadValidator>>,
+    async_order: Arc<tokio::sync::Mutex<()>>,
+    wal_truncated: bool,
+    checksummed_on_open: usize,
 }
 
-fn compute_merkle_root(items: &[[u8; 32]]) -> Option<[u8; 32]> {
-    let mut leaves = items.to_vec();
-    if leaves.is_empty() {
-        return None;
-    }
-    while leaves.len() > 1 {
-        leaves = leaves
-            .chunks(2)
-            .map(|chunk| match chunk {
-                [left, right] => merkle_parent(left, right),
-                [solo] => merkle_parent(solo, solo),
-                _ => unreachable!(),
-            })
-            .collect();
-    }
-    leaves.into_iter().next()
+/// Content-addressed envelope bodies referenced by body-ref records.
+#[derive(Debug)]
+struct BodyStore {
+    file: Mutex<File>,
+    path: PathBuf,
+    known: Mutex<HashSet<[u8; 32]>>,
 }
 
-/// Merkle path position for a sibling hash.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
-pub enum ProofPosition {
-    /// Sibling sits to the left of the node being proven.
-    Left,
-    /// Sibling sits to the right of the node being proven.
-    Right,
+/// Envelope minus its body, which lives in the [`BodyStore`] under `header.body_hash`.
+#[derive(Serialize, Deserialize)]
+struct BodyRefRecord {
+    header: EnvelopeHeader,
+    signatures: Vec<Signature>,
+    attestations: Vec<Attestation>,
 }
 
-/// A node along a Merkle proof path.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct ProofNode {
-    /// The sibling hash at this level.
-    pub sibling: [u8; 32],
-    /// Whether the sibling is left or right of the path node.
-    pub position: ProofPosition,
+const DEFAULT_SEGMENT_SIZE: usize = 1024;
+const CHECKSUM_DOMAIN: &[u8] = b"ea-ledger:wal:v1";
+/// High bit of the length prefix marks records that carry a flag byte.
+const RECORD_FLAGGED: u32 = 0x8000_0000;
+const RECORD_RAW: u8 = 0;
+const RECORD_ZSTD: u8 = 1;
+const RECORD_BODY_REF: u8 = 2;
+/// Sidecar record: length (8) + root flag (1) + root (32) + checksum (32).
+const META_RECORD_LEN: usize = 73;
+
+fn read_metadata_file(path: &Path) -> Option<PersistentMetadata> {
+    fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<PersistentMetadata>(&bytes).ok())
 }
 
-/// Receipt proving inclusion of a log entry.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct MerkleReceipt {
-    /// Index of the leaf in the log.
-    pub index: usize,
-    /// Total leaf count at time of receipt generation.
-    pub leaf_count: usize,
-    /// Hash of the leaf being proven (envelope hash).
-    pub leaf: [u8; 32],
-    /// Merkle root over the log at generation time.
-    pub root: [u8; 32],
-    /// Proof path from leaf to root.
-    pub path: Vec<ProofNode>,
+fn encode_meta_record(meta: &PersistentMetadata) -> [u8; META_RECORD_LEN] {
+    let mut record = [0u8; META_RECORD_LEN];
+    record[..8].copy_from_slice(&(meta.length as u64).to_be_bytes());
+    if let Some(root) = meta.root {
+        record[8] = 1;
+        record[9..41].copy_from_slice(&root);
+    }
+    let checksum = meta.algorithm.checksum(&record[..41]);
+    record[41..].copy_from_slice(&checksum);
+    record
 }
 
-impl MerkleReceipt {
-    /// Build a receipt from a set of leaves and a target index.
-    pub fn from_leaves(leaves: &[[u8; 32]], index: usize) -> Option<Self> {
-        if leaves.is_empty() || index >= leaves.len() {
-            return None;
+/// Metadata recorded in the sidecar log, oldest first.
+///
+/// Every record must be whole and checksummed; anything else is corruption.
+fn read_meta_log(
+    path: &Path,
+    algorithm: MerkleAlgorithm,
+) -> Result<Vec<PersistentMetadata>, AppendError> {
+    let bytes = fs::read(path).unwrap_or_default();
+    if !bytes.len().is_multiple_of(META_RECORD_LEN) {
+        return Err(anyhow::anyhow!("truncated metadata log {}", path.display()).into());
+    }
+    let mut records = Vec::with_capacity(bytes.len() / META_RECORD_LEN);
+    for record in bytes.chunks_exact(META_RECORD_LEN) {
+        if algorithm.checksum(&record[..41]) != record[41..] {
+            return Err(anyhow::anyhow!("checksum mismatch in {}", path.display()).into());
         }
+        let length = u64::from_be_bytes(record[..8].try_into().unwrap()) as usize;
+        let root = (record[8] == 1).then(|| record[9..41].try_into().unwrap());
+        records.push(PersistentMetadata {
+            length,
+            root,
+            algorithm,
+        });
+    }
+    Ok(records)
+}
 
-        let mut path = Vec::new();
-        let mut current_index = index;
-        let mut level = leaves.to_vec();
+impl PersistentAppendLog {
+    /// Open (or create) a persistent log at `dir` with the default segment size.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, AppendError> {
+        Self::open_with_segment_size(dir, DEFAULT_SEGMENT_SIZE)
+    }
 
-        while level.len() > 1 {
-            let sibling_index = if current_index % 2 == 0 {
-                current_index + 1
-            } else {
-                current_index - 1
-            };
-            let sibling = if sibling_index < level.len() {
-                level[sibling_index]
-            } else {
-                level[current_index]
-            };
-            let position = if current_index % 2 == 0 {
-                ProofPosition::Right
-            } else {
-                ProofPosition::Left
-            };
-            path.push(ProofNode { sibling, position });
+    /// Open (or create) a persistent log at `dir` with a custom segment size.
+    pub fn open_with_segment_size<P: AsRef<Path>>(
+        dir: P,
+        segment_size: usize,
+    ) -> Result<Self, AppendError> {
+        Self::open_inner(
+            dir.as_ref(),
+            segment_size,
+            MerkleAlgorithm::default(),
+            SyncPolicy::default(),
+            WalRecovery::default(),
+            None,
+        )
+    }
 
-            let mut next_level = Vec::with_capacity((level.len() + 1) / 2);
-            for chunk in level.chunks(2) {
-                match chunk {
-                    [left, right] => next_level.push(merkle_parent(left, right)),
-                    [solo] => next_level.push(merkle_parent(solo, solo)),
-                    _ => unreachable!(),
-                }
+    /// Open (or create) a persistent log at `dir` committing with `algorithm`.
+    ///
+    /// The algorithm is recorded in `meta.json`; reopening with a different
+    /// one is rejected.
+    pub fn open_with_algorithm<P: AsRef<Path>>(
+        dir: P,
+        algorithm: MerkleAlgorithm,
+    ) -> Result<Self, AppendError> {
+        Self::open_inner(
+            dir.as_ref(),
+            DEFAULT_SEGMENT_SIZE,
+            algorithm,
+            SyncPolicy::default(),
+            WalRecovery::default(),
+            None,
+        )
+    }
+
+    /// Open (or create) a persistent log at `dir` fsyncing its WAL per `sync`.
+    ///
+    /// See [`SyncPolicy`] for what each policy risks on power loss.
+    pub fn open_with_sync_policy<P: AsRef<Path>>(
+        dir: P,
+        sync: SyncPolicy,
+    ) -> Result<Self, AppendError> {
+        Self::open_inner(
+            dir.as_ref(),
+            DEFAULT_SEGMENT_SIZE,
+            MerkleAlgorithm::default(),
+            sync,
+            WalRecovery::default(),
+            None,
+        )
+    }
+
+    /// Open (or create) a persistent log at `dir` handling a damaged WAL tail
+    /// per `recovery`.
+    ///
+    /// Check [`wal_truncated`](Self::wal_truncated) to learn whether records
+    /// were dropped.
+    pub fn open_with_recovery<P: AsRef<Path>>(
+        dir: P,
+        recovery: WalRecovery,
+    ) -> Result<Self, AppendError> {
+        Self::open_inner(
+            dir.as_ref(),
+            DEFAULT_SEGMENT_SIZE,
+            MerkleAlgorithm::default(),
+            SyncPolicy::default(),
+            recovery,
+            None,
+        )
+    }
+
+    /// Open a persistent log at `dir`, trusting the prefix covered by a
+    /// signed checkpoint.
+    ///
+    /// When `checkpoint` verifies under `anchor` and its root matches the
+    /// first `checkpoint.length` records, those records skip their per-record
+    /// checksum. Every record is still decoded, and the leaf hashes that
+    /// confirm the checkpoint are the ones every open computes for the log's
+    /// root, so the saving is one checksum per trusted record. An invalid
+    /// signature or a root mismatch falls back to the full verification done
+    /// by [`open`](Self::open); see
+    /// [`records_checksummed_on_open`](Self::records_checksummed_on_open).
+    pub fn open_with_checkpoint<P: AsRef<Path>>(
+        dir: P,
+        segment_size: usize,
+        algorithm: MerkleAlgorithm,
+        checkpoint: &SignedCheckpoint,
+        anchor: &VerifyingKey,
+    ) -> Result<Self, AppendError> {
+        let trusted = if verify_signed(checkpoint, anchor) {
+            Some(checkpoint.checkpoint.clone())
+        } else {
+            tracing::warn!("ignoring checkpoint with invalid signature");
+            None
+        };
+        Self::open_inner(
+            dir.as_ref(),
+            segment_size,
+            algorithm,
+            SyncPolicy::default(),
+            WalRecovery::default(),
+            trusted,
+        )
+    }
+
+    fn open_inner(
+        dir: &Path,
+        segment_size: usize,
+        algorithm: MerkleAlgorithm,
+        sync: SyncPolicy,
+        recovery: WalRecovery,
+        checkpoint: Option<Checkpoint>,
+    ) -> Result<Self, AppendError> {
+        algorithm.ensure_supported()?;
+        let segment_size = segment_size.max(1);
+        fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create log directory {}", dir.display()))?;
+        let wal_path = dir.join("append.wal");
+        let segments_path = dir.join("segments.bin");
+        let meta_path = dir.join("meta.json");
+        let meta_log_path = dir.join("meta.log");
+        let bodies_path = dir.join("bodies.bin");
+        let on_disk_meta = read_metadata_file(&meta_path);
+        if let Some(on_disk) = &on_disk_meta {
+            if on_disk.algorithm != algorithm {
+                return Err(anyhow::anyhow!(
+                    "persistent log uses {:?} but was opened with {:?}",
+                    on_disk.algorithm,
+                    algorithm
+                )
+                .into());
             }
-            current_index /= 2;
-            level = next_level;
+        }
+        let bodies = read_bodies(&bodies_path, algorithm)?;
+        // Segments hold the oldest records, so a trusted prefix covers them first.
+        let load = |trusted: usize| -> Result<_, AppendError> {
+            let (mut entries, _) = read_records(
+                &segments_path,
+                algorithm,
+                &bodies,
+                WalRecovery::Strict,
+                trusted,
+            )?;
+            let wal_trusted = trusted.saturating_sub(entries.len());
+            let (wal_entries, wal_valid_len) =
+                read_records(&wal_path, algorithm, &bodies, recovery, wal_trusted)?;
+            let wal_count = wal_entries.len();
+            entries.extend(wal_entries);
+            Ok((entries, wal_count, wal_valid_len))
+        };
+        // Leaf hashes are computed once and shared by every root check below.
+        let leaves_of =
+            |entries: &[Envelope]| entries.iter().map(|env| algorithm.leaf_hash(env)).collect();
+        let mut trusted = checkpoint.as_ref().map_or(0, |cp| cp.length);
+        let mut loaded = load(trusted);
+        let mut leaves: Vec<[u8; 32]> = loaded
+            .as_ref()
+            .map_or_else(|_| Vec::new(), |(entries, _, _)| leaves_of(entries));
+        if let Some(cp) = &checkpoint {
+            let confirmed = loaded.is_ok()
+                && leaves
+                    .get(..cp.length)
+                    .is_some_and(|prefix| algorithm.root(prefix) == Some(cp.root));
+            if !confirmed {
+                tracing::warn!(
+                    length = cp.length,
+                    "checkpoint does not match log; verifying every record"
+                );
+                trusted = 0;
+                loaded = load(0);
+                leaves = loaded
+                    .as_ref()
+                    .map_or_else(|_| Vec::new(), |(entries, _, _)| leaves_of(entries));
+            }
+        }
+        let (entries, wal_count, wal_valid_len) = loaded?;
+        let checksummed_on_open = entries.len() - trusted;
+        if let Some(valid_len) = wal_valid_len {
+            tracing::warn!(
+                path = %wal_path.display(),
+                recovered = wal_count,
+                "discarding damaged wal tail"
+            );
+            OpenOptions::new()
+                .write(true)
+                .open(&wal_path)
+                .and_then(|wal| wal.set_len(valid_len as u64))
+                .with_context(|| format!("failed to truncate WAL {}", wal_path.display()))?;
+        }
+        let current_meta = PersistentMetadata {
+            length: entries.len(),
+            root: algorithm.root(&leaves),
+            algorithm,
+        };
+        let mismatch = || anyhow::anyhow!("persistent log metadata mismatch during recovery");
+        // Metadata must describe a prefix of the recovered entries, except
+        // that a discarded WAL tail may take committed entries with it.
+        let tail_discarded = wal_valid_len.is_some();
+        let describes_prefix = |meta: &PersistentMetadata| match leaves.get(..meta.length) {
+            Some(prefix) => algorithm.root(prefix) == meta.root,
+            None => tail_discarded,
+        };
+        // `meta.json` may lag the sidecar log but must still describe a prefix.
+        if let Some(on_disk) = &on_disk_meta {
+            if !describes_prefix(on_disk) {
+                return Err(mismatch().into());
+            }
+        }
+        let mut logged_meta = read_meta_log(&meta_log_path, algorithm)?;
+        if tail_discarded {
+            let survivor = logged_meta
+                .iter()
+                .rev()
+                .find(|meta| meta.length <= current_meta.length);
+            if survivor.is_some_and(|meta| !describes_prefix(meta)) {
+                return Err(mismatch().into());
+            }
+        }
+        let latest = match (on_disk_meta, logged_meta.pop()) {
+            (Some(on_disk), Some(logged)) if logged.length < on_disk.length => Some(on_disk),
+            (on_disk, logged) => logged.or(on_disk),
+        };
+        // Metadata ahead of a discarded tail is rewritten by `ensure_metadata`.
+        if latest.is_some_and(|latest| {
+            latest != current_meta && !(tail_discarded && latest.length > current_meta.length)
+        }) {
+            return Err(mismatch().into());
         }
 
-        Some(MerkleReceipt {
-            index,
-            leaf_count: leaves.len(),
-            leaf: leaves[index],
-            root: level[0],
-            path,
-        })
+        let wal = Arc::new(Mutex::new(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .read(true)
+                .open(&wal_path)
+                .with_context(|| format!("failed to open WAL {}", wal_path.display()))?,
+        ));
+        let segments = Arc::new(Mutex::new(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .read(true)
+                .open(&segments_path)
+                .with_context(|| format!("failed to open segments {}", segments_path.display()))?,
+        ));
+        let meta_log = Arc::new(Mutex::new(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&meta_log_path)
+                .with_context(|| {
+                    format!("failed to open metadata log {}", meta_log_path.display())
+                })?,
+        ));
+        let body_file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&bodies_path)
+            .with_context(|| format!("failed to open body store {}", bodies_path.display()))?;
+        let bodies = Arc::new(BodyStore {
+            file: Mutex::new(body_file),
+            path: bodies_path,
+            known: Mutex::new(bodies.into_keys().collect()),
+        });
+        let log = Self {
+            state: Arc::new(RwLock::new(PersistentState {
+                dag: DagIndex::build(&entries),
+                entries,
+                wal_entries: wal_count,
+            })),
+            wal_sync: Arc::new(WalSync::new(wal.clone(), sync)),
+            wal,
+            segments,
+            dir: dir.to_path_buf(),
+            meta_path,
+            meta_log,
+            meta_log_path,
+            wal_path,
+            bodies,
+            segment_size,
+            dedup: None,
+            compression: None,
+            body_dedup: false,
+            algorithm,
+            validator: None,
+            async_order: Arc::new(tokio::sync::Mutex::new(())),
+            wal_truncated: wal_valid_len.is_some(),
+            checksummed_on_open,
+        };
+        log.ensure_metadata()?;
+        Ok(log)
     }
 
-    /// Verify this receipt against the embedded root.
-    pub fn verify(&self) -> bool {
-        if self.path.is_empty() && self.leaf_count != 1 {
-            return false;
+    /// Enable append-side deduplication over the last `capacity` envelope hashes.
+    ///
+    /// Re-appending an envelope still in the cache returns its original index
+    /// without touching the WAL.
+    pub fn with_dedup_capacity(mut self, capacity: usize) -> Self {
+        self.dedup = Some(DedupCache::new(capacity));
+        self
+    }
+
+    /// Compress newly written records with zstd at `level`.
+    ///
+    /// Records that do not shrink are stored raw; existing records are left
+    /// as-is and logs may mix compressed and uncompressed records freely.
+    pub fn with_compression(mut self, level: i32) -> Self {
+        self.compression = Some(level);
+        self
+    }
+
+    /// Store each distinct envelope body once, keyed by `body_hash`.
+    ///
+    /// New records reference their body in `bodies.bin` instead of carrying
+    /// it; reads and Merkle roots still see full envelopes. Bodies are written
+    /// and synced before the record that references them.
+    pub fn with_body_dedup(mut self) -> Self {
+        self.body_dedup = true;
+        self
+    }
+
+    /// Check typed payloads with `validator` before they reach the WAL.
+    ///
+    /// Entries recovered from disk are not re-checked.
+    pub fn with_payload_validator(mut self, validator: Arc<dyn PayloadValidator>) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    /// Whether opening discarded a damaged WAL tail under
+    /// [`WalRecovery::RecoverPrefix`].
+    pub fn wal_truncated(&self) -> bool {
+        self.wal_truncated
+    }
+
+    /// Records whose checksum was verified while opening; records trusted via
+    /// [`open_with_checkpoint`](Self::open_with_checkpoint) are not counted.
+    pub fn records_checksummed_on_open(&self) -> usize {
+        self.checksummed_on_open
+    }
+
+    /// Append without blocking the async runtime, returning the assigned index.
+    ///
+    /// The WAL write and fsync run on tokio's blocking pool. Async appends are
+    /// admitted in the order their futures are first polled, and each resolves
+    /// only once the entry is as durable as the [`SyncPolicy`] makes it,
+    /// exactly like `append_with_index`.
+    pub async fn append_async(
+        &self,
+        env: Envelope,
+        registry: &ChannelRegistry,
+    ) -> Result<usize, AppendError> {
+        let _turn = self.async_order.lock().await;
+        let log = self.clone();
+        let registry = registry.clone();
+        tokio::task::spawn_blocking(move || log.append_with_index(env, &registry))
+            .await
+            .map_err(|err| anyhow::anyhow!("blocking append task failed: {err}"))?
+    }
+
+    fn ensure_metadata(&self) -> Result<(), AppendError> {
+        let state = self.state.read();
+        let expected = PersistentMetadata::from_state(&state, self.algorithm);
+        let log_empty = fs::metadata(&self.meta_log_path).map_or(true, |m| m.len() == 0);
+        match read_metadata_file(&self.meta_path) {
+            Some(on_disk) if on_disk == expected && log_empty => Ok(()),
+            _ => self.compact_metadata(&expected),
         }
-        let mut hash = self.leaf;
-        for node in &self.path {
-            hash = match node.position {
-                ProofPosition::Left => merkle_parent(&node.sibling, &hash),
-                ProofPosition::Right => merkle_parent(&hash, &node.sibling),
-            };
+    }
+
+    /// Record `meta` on the hot path with one small sidecar write.
+    fn log_metadata(&self, meta: &PersistentMetadata) -> Result<(), AppendError> {
+        let record = encode_meta_record(meta);
+        self.meta_log
+            .lock()
+            .write_all(&record)
+            .context("failed to append metadata record")?;
+        Ok(())
+    }
+
+    /// Fold the sidecar into `meta.json` and start a fresh sidecar.
+    ///
+    /// A crash between the two steps leaves older sidecar records behind,
+    /// which recovery ignores because `meta.json` is further ahead.
+    fn compact_metadata(&self, meta: &PersistentMetadata) -> Result<(), AppendError> {
+        self.persist_metadata(meta)?;
+        self.meta_log
+            .lock()
+            .set_len(0)
+            .context("failed to truncate metadata log")?;
+        Ok(())
+    }
+
+    fn persist_metadata(&self, meta: &PersistentMetadata) -> Result<(), AppendError> {
+        let tmp = self.meta_path.with_extension("tmp");
+        let encoded =
+            serde_json::to_vec(meta).context("failed to serialize persistent log metadata")?;
+        fs::write(&tmp, encoded)
+            .with_context(|| format!("failed to write metadata {}", tmp.display()))?;
+        fs::rename(&tmp, &self.meta_path).with_context(|| {
+            format!(
+                "failed to atomically persist metadata {} -> {}",
+                tmp.display(),
+                self.meta_path.display()
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Validate `env` against the tip and commit it to the WAL.
+    fn append_to_wal(
+        &self,
+        env: Envelope,
+        registry: &ChannelRegistry,
+        span: &tracing::Span,
+    ) -> Result<usize, AppendError> {
+        let mut state = self.state.write();
+        let env_hash = self.dedup.as_ref().map(|_| envelope_hash(&env));
+        if let (Some(cache), Some(hash)) = (&self.dedup, &env_hash) {
+            if let Some(existing) = cache.lookup(hash) {
+                span.record("offset", existing as u64);
+                tracing::debug!("duplicate append skipped");
+                return Ok(existing);
+            }
+        }
+        let tip = ChannelState {
+            last_hash: state.entries.last().map(envelope_hash),
+            last_timestamp: state.entries.last().map(|e| e.header.timestamp),
+        };
+        let prev_state = link_state(tip, &env, registry, |hash| state.dag.timestamp(hash));
+        precheck_signers(&env, registry)?;
+        let next = ledger_spec::validate_envelope(&env, registry, &prev_state)?;
+        check_payload(&self.validator, &env)?;
+        let index = state.entries.len();
+        self.write_wal(&env)?;
+        if let (Some(cache), Some(hash)) = (&self.dedup, env_hash) {
+            cache.record(hash, index);
+        }
+        let hash = next.last_hash.unwrap_or_else(|| envelope_hash(&env));
+        state.dag.push(index, &env, hash);
+        state.entries.push(env);
+        state.wal_entries += 1;
+        let meta = PersistentMetadata::from_state(&state, self.algorithm);
+        drop(state);
+        self.log_metadata(&meta)?;
+        if meta.length % self.segment_size == 0 {
+            self.compact_segments()?;
+            self.compact_metadata(&meta)?;
+        }
+        span.record("offset", &(index as u64));
+        tracing::debug!("append committed to wal");
+        Ok(index)
+    }
+
+    fn write_wal(&self, env: &Envelope) -> Result<(), AppendError> {
+        let record = if self.body_dedup {
+            self.store_body(env)?;
+            encode_body_ref_record(env, self.algorithm)?
+        } else {
+            encode_record(env, self.compression, self.algorithm)?
+        };
+        let mut wal = self.wal.lock();
+        wal.write_all(&record)
+            .context("failed to write wal record")?;
+        wal.flush().context("failed to flush wal")?;
+        self.wal_sync.record_write(&wal)?;
+        Ok(())
+    }
+
+    fn store_body(&self, env: &Envelope) -> Result<(), AppendError> {
+        let mut known = self.bodies.known.lock();
+        if known.contains(&env.header.body_hash) {
+            return Ok(());
+        }
+        let json = serde_json::to_vec(&env.body).context("failed to serialize envelope body")?;
+        let record = frame_record(&pack_payload(&json, self.compression)?, self.algorithm);
+        let mut file = self.bodies.file.lock();
+        file.write_all(&record)
+            .context("failed to write body store record")?;
+        file.sync_all().context("failed to sync body store")?;
+        known.insert(env.header.body_hash);
+        Ok(())
+    }
+
+    fn compact_segments(&self) -> Result<(), AppendError> {
+        let wal_bytes = fs::read(&self.wal_path).unwrap_or_default();
+        if wal_bytes.is_empty() {
+            return Ok(());
+        }
+        self.wal_sync.sync_pending(&self.wal.lock())?;
+        {
+            let mut segments = self.segments.lock();
+            segments
+                .write_all(&wal_bytes)
+                .context("failed to write compacted wal into segments")?;
+            segments
+                .sync_all()
+                .context("failed to sync compacted segments")?;
+        }
+        {
+            let mut wal = self.wal.lock();
+            wal.set_len(0).context("failed to truncate wal")?;
+            wal.seek(SeekFrom::End(0))
+                .context("failed to reset wal cursor")?;
+            wal.sync_all().context("failed to sync truncated wal")?;
+        }
+        let mut state = self.state.write();
+        state.wal_entries = 0;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    fn wal_syncs(&self) -> u64 {
+        self.wal_sync.state.lock().syncs
+    }
+
+    #[cfg(test)]
+    fn metadata(&self) -> Option<PersistentMetadata> {
+        let on_disk = read_metadata_file(&self.meta_path);
+        let logged = read_meta_log(&self.meta_log_path, self.algorithm).ok()?;
+        match (on_disk, logged.into_iter().last()) {
+            (Some(on_disk), Some(logged)) if logged.length < on_disk.length => Some(on_disk),
+            (on_disk, logged) => logged.or(on_disk),
+        }
+    }
+}
+
+impl AppendLogStorage for PersistentAppendLog {
+    fn append(&self, env: Envelope, registry: &ChannelRegistry) -> Result<(), AppendError> {
+        self.append_with_index(env, registry).map(|_| ())
+    }
+
+    fn append_with_index(
+        &self,
+        env: Envelope,
+        registry: &ChannelRegistry,
+    ) -> Result<usize, AppendError> {
+        let span = tracing::info_span!(
+            "append_persistent_log",
+            channel = %env.header.channel,
+            offset = tracing::field::Empty,
+            latency_ms = tracing::field::Empty
+        );
+        let _guard = span.enter();
+        let start = std::time::Instant::now();
+        #[cfg(feature = "observability")]
+        let channel = env.header.channel.clone();
+        let res = self.append_to_wal(env, registry, &span);
+        #[cfg(feature = "observability")]
+        observe::record_append(&channel, start.elapsed(), &res);
+        let elapsed = start.elapsed().as_millis() as u64;
+        span.record("latency_ms", &elapsed);
+        res
+    }
+
+    fn read(&self, offset: usize, limit: usize) -> Vec<Envelope> {
+        let span = tracing::info_span!(
+            "read_persistent_log",
+            offset = offset as u64,
+            limit = limit as u64,
+            latency_ms = tracing::field::Empty
+        );
+        let _guard = span.enter();
+        let start = std::time::Instant::now();
+        let state = self.state.read();
+        let out: Vec<Envelope> = state
+            .entries
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect();
+        #[cfg(feature = "observability")]
+        observe::record_read(start.elapsed());
+        let elapsed = start.elapsed().as_millis() as u64;
+        span.record("latency_ms", &elapsed);
+        tracing::debug!(result_len = out.len(), "read completed");
+        out
+    }
+
+    fn len(&self) -> usize {
+        self.state.read().entries.len()
+    }
+
+    fn merkle_root(&self) -> Option<[u8; 32]> {
+        let state = self.state.read();
+        self.algorithm.root_for(&state.entries)
+    }
+
+    fn receipt_for(&self, index: usize) -> Option<MerkleReceipt> {
+        let state = self.state.read();
+        MerkleReceipt::from_leaf_fn(self.algorithm, state.entries.len(), index, |i| {
+            state
+                .entries
+                .get(i)
+                .map(|env| self.algorithm.leaf_hash(env))
+        })
+    }
+
+    fn verify_integrity(&self) -> Result<(), usize> {
+        // Hash each envelope once instead of once per receipt.
+        let state = self.state.read();
+        let leaves: Vec<[u8; 32]> = state
+            .entries
+            .iter()
+            .map(|env| self.algorithm.leaf_hash(env))
+            .collect();
+        let Some(root) = self.algorithm.root(&leaves) else {
+            return Ok(());
+        };
+        for (index, env) in state.entries.iter().enumerate() {
+            match MerkleReceipt::from_leaves_with(self.algorithm, &leaves, index) {
+                Some(receipt) if receipt_covers(env, &receipt, &root) => {}
+                _ => return Err(index),
+            }
+        }
+        Ok(())
+    }
+
+    fn storage_usage_bytes(&self) -> Option<u64> {
+        let wal = std::fs::metadata(&self.wal_path)
+            .map(|m| m.len())
+            .unwrap_or(0);
+        let seg = std::fs::metadata(&self.dir.join("segments.bin"))
+            .map(|m| m.len())
+            .unwrap_or(0);
+        let meta = std::fs::metadata(&self.meta_path)
+            .map(|m| m.len())
+            .unwrap_or(0);
+        let meta_log = std::fs::metadata(&self.meta_log_path)
+            .map(|m| m.len())
+            .unwrap_or(0);
+        let bodies = std::fs::metadata(&self.bodies.path)
+            .map(|m| m.len())
+            .unwrap_or(0);
+        Some(wal + seg + meta + meta_log + bodies)
+    }
+
+    fn flush(&self) -> Result<(), AppendError> {
+        {
+            let wal = self.wal.lock();
+            wal.sync_all().context("failed to sync wal")?;
+            self.wal_sync.state.lock().pending = 0;
+        }
+        self.segments
+            .lock()
+            .sync_all()
+            .context("failed to sync segments")?;
+        self.meta_log
+            .lock()
+            .sync_all()
+            .context("failed to sync metadata log")?;
+        self.bodies
+            .file
+            .lock()
+            .sync_all()
+            .context("failed to sync body store")?;
+        File::open(&self.meta_path)
+            .and_then(|meta| meta.sync_all())
+            .context("failed to sync metadata")?;
+        // Metadata is replaced by rename; sync the directory so it survives a crash.
+        #[cfg(unix)]
+        File::open(&self.dir)
+            .and_then(|dir| dir.sync_all())
+            .context("failed to sync log directory")?;
+        Ok(())
+    }
+
+    fn find_entry(&self, hash: &[u8; 32]) -> Option<(usize, Timestamp)> {
+        self.state.read().dag.entries.get(hash).copied()
+    }
+
+    fn tips(&self) -> Vec<[u8; 32]> {
+        self.state.read().dag.tips()
+    }
+}
+
+fn encode_record(
+    env: &Envelope,
+    compression: Option<i32>,
+    algorithm: MerkleAlgorithm,
+) -> Result<Vec<u8>, AppendError> {
+    let json = serde_json::to_vec(env).context("failed to serialize envelope")?;
+    Ok(frame_record(&pack_payload(&json, compression)?, algorithm))
+}
+
+fn encode_body_ref_record(
+    env: &Envelope,
+    algorithm: MerkleAlgorithm,
+) -> Result<Vec<u8>, AppendError> {
+    let stub = BodyRefRecord {
+        header: env.header.clone(),
+        signatures: env.signatures.clone(),
+        attestations: env.attestations.clone(),
+    };
+    let json = serde_json::to_vec(&stub).context("failed to serialize envelope")?;
+    let mut stored = Vec::with_capacity(json.len() + 1);
+    stored.push(RECORD_BODY_REF);
+    stored.extend_from_slice(&json);
+    Ok(frame_record(&stored, algorithm))
+}
+
+/// Flag byte plus `json`, zstd-compressed when that makes it smaller.
+fn pack_payload(json: &[u8], compression: Option<i32>) -> Result<Vec<u8>, AppendError> {
+    let mut stored = Vec::with_capacity(json.len() + 1);
+    match compression {
+        Some(level) => {
+            let compressed =
+                zstd::bulk::compress(json, level).context("failed to compress envelope")?;
+            if compressed.len() < json.len() {
+                stored.push(RECORD_ZSTD);
+                stored.extend_from_slice(&compressed);
+            } else {
+                stored.push(RECORD_RAW);
+                stored.extend_from_slice(json);
+            }
+        }
+        None => {
+            stored.push(RECORD_RAW);
+            stored.extend_from_slice(json);
+        }
+    }
+    Ok(stored)
+}
+
+fn frame_record(stored: &[u8], algorithm: MerkleAlgorithm) -> Vec<u8> {
+    let digest = algorithm.checksum(stored);
+    let mut record = Vec::with_capacity(4 + 32 + stored.len());
+    record.extend_from_slice(&(stored.len() as u32 | RECORD_FLAGGED).to_be_bytes());
+    record.extend_from_slice(&digest);
+    record.extend_from_slice(stored);
+    record
+}
+
+/// JSON bytes of a raw or compressed record.
+fn unpack_payload(stored: &[u8], flagged: bool) -> Result<Cow<'_, [u8]>, AppendError> {
+    if !flagged {
+        return Ok(Cow::Borrowed(stored));
+    }
+    match stored.split_first() {
+        Some((&RECORD_RAW, body)) => Ok(Cow::Borrowed(body)),
+        Some((&RECORD_ZSTD, body)) => Ok(Cow::Owned(
+            zstd::stream::decode_all(body).context("failed to decompress envelope from wal")?,
+        )),
+        Some((flag, _)) => Err(anyhow::anyhow!("unknown record flag {flag}").into()),
+        None => Err(anyhow::anyhow!("flagged record missing flag byte").into()),
+    }
+}
+
+fn decode_record(
+    stored: &[u8],
+    flagged: bool,
+    bodies: &HashMap<[u8; 32], EnvelopeBody>,
+) -> Result<Envelope, AppendError> {
+    if let (true, Some((&RECORD_BODY_REF, json))) = (flagged, stored.split_first()) {
+        let stub: BodyRefRecord =
+            serde_json::from_slice(json).context("failed to decode envelope from wal")?;
+        let body = bodies.get(&stub.header.body_hash).cloned().ok_or_else(|| {
+            anyhow::anyhow!(
+                "body {} missing from body store",
+                blake3::Hash::from_bytes(stub.header.body_hash).to_hex()
+            )
+        })?;
+        return Ok(Envelope {
+            header: stub.header,
+            body,
+            signatures: stub.signatures,
+            attestations: stub.attestations,
+        });
+    }
+    let json = unpack_payload(stored, flagged)?;
+    Ok(serde_json::from_slice(&json).context("failed to decode envelope from wal")?)
+}
+
+/// Call `visit` with the payload and flag of every checksummed record in `path`.
+///
+/// Under [`WalRecovery::RecoverPrefix`] a truncated or checksum-failing record
+/// ends the scan instead of failing it, and the byte length of the valid
+/// prefix is returned. Checksums of the first `trusted` records are not
+/// checked; their framing still is.
+fn read_frames(
+    path: &Path,
+    algorithm: MerkleAlgorithm,
+    recovery: WalRecovery,
+    trusted: usize,
+    mut visit: impl FnMut(&[u8], bool) -> Result<(), AppendError>,
+) -> Result<Option<usize>, AppendError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let mut file =
+        File::open(path).with_context(|| format!("failed to open log file {}", path.display()))?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)
+        .with_context(|| format!("failed to read log file {}", path.display()))?;
+    let mut cursor = 0usize;
+    let mut index = 0usize;
+    while cursor < buf.len() {
+        let start = cursor;
+        let damaged = |what: &str| match recovery {
+            WalRecovery::Strict => Err(anyhow::anyhow!("{what} in {}", path.display()).into()),
+            WalRecovery::RecoverPrefix => Ok(Some(start)),
+        };
+        if cursor + 4 > buf.len() {
+            return damaged("truncated record length");
+        }
+        let prefix = u32::from_be_bytes(buf[cursor..cursor + 4].try_into().unwrap());
+        let flagged = prefix & RECORD_FLAGGED != 0;
+        let len = (prefix & !RECORD_FLAGGED) as usize;
+        cursor += 4;
+        if cursor + 32 + len > buf.len() {
+            return damaged("truncated record body");
+        }
+        let checksum: [u8; 32] = buf[cursor..cursor + 32].try_into().unwrap();
+        cursor += 32;
+        let payload = &buf[cursor..cursor + len];
+        cursor += len;
+        if index >= trusted && algorithm.checksum(payload) != checksum {
+            return damaged("checksum mismatch");
+        }
+        index += 1;
+        visit(payload, flagged)?;
+    }
+    Ok(None)
+}
+
+/// Decode every record in `path`, with the valid prefix length if `recovery`
+/// stopped at a damaged record. The first `trusted` records skip checksum
+/// verification.
+fn read_records(
+    path: &Path,
+    algorithm: MerkleAlgorithm,
+    bodies: &HashMap<[u8; 32], EnvelopeBody>,
+    recovery: WalRecovery,
+    trusted: usize,
+) -> Result<(Vec<Envelope>, Option<usize>), AppendError> {
+    let mut items = Vec::new();
+    let valid_len = read_frames(path, algorithm, recovery, trusted, |payload, flagged| {
+        items.push(decode_record(payload, flagged, bodies)?);
+        Ok(())
+    })?;
+    Ok((items, valid_len))
+}
+
+/// Load the body store, keying each body by its recomputed hash.
+fn read_bodies(
+    path: &Path,
+    algorithm: MerkleAlgorithm,
+) -> Result<HashMap<[u8; 32], EnvelopeBody>, AppendError> {
+    let mut bodies = HashMap::new();
+    read_frames(
+        path,
+        algorithm,
+        WalRecovery::Strict,
+        0,
+        |payload, flagged| {
+            let json = unpack_payload(payload, flagged)?;
+            let body: EnvelopeBody =
+                serde_json::from_slice(&json).context("failed to decode body store record")?;
+            bodies.insert(hash_body(&body), body);
+            Ok(())
+        },
+    )?;
+    Ok(bodies)
+}
+
+/// Checkpoint record capturing merkle root and length.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Log length at checkpoint.
+    pub length: usize,
+    /// Merkle root.
+    pub root: [u8; 32],
+}
+
+/// Checkpoint signed by a designated anchor key for external auditing.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SignedCheckpoint {
+    /// Checkpoint covered by the signature.
+    pub checkpoint: Checkpoint,
+    /// Anchor public key.
+    pub signer: [u8; 32],
+    /// Signature over [`signing::checkpoint_digest`].
+    #[serde(with = "BigArray")]
+    pub signature: [u8; 64],
+}
+
+/// Check `signed` was produced by `key` over its current length and root.
+pub fn verify_signed(signed: &SignedCheckpoint, key: &VerifyingKey) -> bool {
+    if signed.signer != key.to_bytes() {
+        return false;
+    }
+    let signature = ed25519_dalek::Signature::from_bytes(&signed.signature);
+    key.verify_strict(&signing::checkpoint_digest(&signed.checkpoint), &signature)
+        .is_ok()
+}
+
+/// Condition that makes a [`CheckpointWriter`] emit a checkpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckpointTrigger {
+    /// Log advanced by at least this many entries.
+    Entries(usize),
+    /// At least this many serialized envelope bytes were appended.
+    Bytes(u64),
+    /// At least this much wall-clock time passed and the log advanced.
+    Interval(Duration),
+}
+
+/// Checkpoint writer produces periodic checkpoints.
+#[derive(Debug)]
+pub struct CheckpointWriter {
+    last_len: usize,
+    last_at: Instant,
+    counted_len: usize,
+    pending_bytes: u64,
+    triggers: Vec<CheckpointTrigger>,
+    signer: Option<SigningKey>,
+}
+
+impl Default for CheckpointWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CheckpointWriter {
+    /// Create new writer.
+    pub fn new() -> Self {
+        Self {
+            last_len: 0,
+            last_at: Instant::now(),
+            counted_len: 0,
+            pending_bytes: 0,
+            triggers: Vec::new(),
+            signer: None,
+        }
+    }
+
+    /// Sign emitted checkpoints with `key`.
+    pub fn with_signer(mut self, key: SigningKey) -> Self {
+        self.signer = Some(key);
+        self
+    }
+
+    /// Use these triggers instead of the per-call entry interval.
+    pub fn with_triggers(mut self, triggers: Vec<CheckpointTrigger>) -> Self {
+        self.triggers = triggers;
+        self
+    }
+
+    /// Configured triggers; empty means the `interval` passed to
+    /// [`maybe_checkpoint`](Self::maybe_checkpoint) is used.
+    pub fn triggers(&self) -> &[CheckpointTrigger] {
+        &self.triggers
+    }
+
+    /// Emit a checkpoint once any trigger is met.
+    ///
+    /// Without configured triggers this fires when the log advanced by at
+    /// least `interval` entries.
+    pub fn maybe_checkpoint(&mut self, log: &AppendLog, interval: usize) -> Option<Checkpoint> {
+        let len = log.len();
+        let fire = if self.triggers.is_empty() {
+            len >= self.last_len + interval
+        } else {
+            if self.needs_bytes() {
+                self.count_bytes(log, len);
+            }
+            self.triggers.iter().any(|trigger| match *trigger {
+                CheckpointTrigger::Entries(n) => len >= self.last_len + n,
+                CheckpointTrigger::Bytes(n) => len > self.last_len && self.pending_bytes >= n,
+                CheckpointTrigger::Interval(d) => {
+                    len > self.last_len && self.last_at.elapsed() >= d
+                }
+            })
+        };
+        if fire {
+            let root = log.merkle_root()?;
+            self.last_len = len;
+            self.last_at = Instant::now();
+            self.pending_bytes = 0;
+            return Some(Checkpoint { length: len, root });
+        }
+        None
+    }
+
+    /// Like [`maybe_checkpoint`](Self::maybe_checkpoint), but signed with the
+    /// configured key. Returns `None` when no signer is configured.
+    pub fn maybe_signed_checkpoint(
+        &mut self,
+        log: &AppendLog,
+        interval: usize,
+    ) -> Option<SignedCheckpoint> {
+        let signer = self.signer.clone()?;
+        let checkpoint = self.maybe_checkpoint(log, interval)?;
+        Some(signing::sign_checkpoint(checkpoint, &signer))
+    }
+
+    fn needs_bytes(&self) -> bool {
+        self.triggers
+            .iter()
+            .any(|trigger| matches!(trigger, CheckpointTrigger::Bytes(_)))
+    }
+
+    fn count_bytes(&mut self, log: &AppendLog, len: usize) {
+        for env in log.read(self.counted_len, len.saturating_sub(self.counted_len)) {
+            self.pending_bytes += serde_json::to_vec(&env).map_or(0, |b| b.len() as u64);
+        }
+        self.counted_len = len;
+    }
+}
+
+/// Replay validator detects tampering or reordering.
+pub struct ReplayValidator {
+    registry: ChannelRegistry,
+}
+
+impl ReplayValidator {
+    /// Create new validator.
+    pub fn new(registry: ChannelRegistry) -> Self {
+        Self { registry }
+    }
+
+    /// Validate a sequence of envelopes starting from empty state.
+    pub fn validate_sequence(&self, seq: &[Envelope]) -> Result<(), ValidationError> {
+        let mut state = ChannelState::default();
+        let mut seen = HashMap::new();
+        for env in seq {
+            let prev = link_state(state, env, &self.registry, |hash| seen.get(hash).copied());
+            state = ledger_spec::validate_envelope(env, &self.registry, &prev)?;
+            if let Some(hash) = state.last_hash {
+                seen.insert(hash, env.header.timestamp);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Why a set of envelopes could not be ordered by their `prev` links.
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+pub enum OrderingError {
+    /// An envelope links to a parent that is not in the set.
+    #[error("envelope {envelope:?} links to missing parent {prev:?}")]
+    MissingParent {
+        /// Hash of the orphaned envelope.
+        envelope: [u8; 32],
+        /// Parent hash it names.
+        prev: [u8; 32],
+    },
+    /// `prev` links loop back on themselves, so no envelope starts the chain.
+    #[error("prev links form a cycle through {0} envelopes")]
+    Cycle(usize),
+}
+
+/// Sort an unordered set of envelopes so every envelope follows its `prev`.
+///
+/// Exact duplicates are dropped. Siblings that branch from the same parent
+/// (DAG channels) come out in timestamp order. The result is ready for
+/// [`ReplayValidator::validate_sequence`] or appending in order; it is not
+/// itself validated.
+pub fn order_envelopes(envs: Vec<Envelope>) -> Result<Vec<Envelope>, OrderingError> {
+    order_by_links(envs, |env| {
+        (envelope_hash(env), env.header.prev, env.header.timestamp)
+    })
+}
+
+/// Topologically sort `items` by `(id, prev, tiebreak)` links.
+fn order_by_links<T>(
+    items: Vec<T>,
+    link: impl Fn(&T) -> ([u8; 32], Option<[u8; 32]>, Timestamp),
+) -> Result<Vec<T>, OrderingError> {
+    let mut slots = Vec::with_capacity(items.len());
+    let mut index = HashMap::with_capacity(items.len());
+    for item in items {
+        let (id, prev, key) = link(&item);
+        if let std::collections::hash_map::Entry::Vacant(entry) = index.entry(id) {
+            entry.insert(slots.len());
+            slots.push((id, prev, key, Some(item)));
+        }
+    }
+
+    let mut children: HashMap<[u8; 32], Vec<usize>> = HashMap::new();
+    let mut ready = std::collections::BinaryHeap::new();
+    for (i, (id, prev, key, _)) in slots.iter().enumerate() {
+        match prev {
+            Some(prev) if !index.contains_key(prev) => {
+                return Err(OrderingError::MissingParent {
+                    envelope: *id,
+                    prev: *prev,
+                });
+            }
+            Some(prev) => children.entry(*prev).or_default().push(i),
+            None => ready.push(std::cmp::Reverse((*key, i))),
+        }
+    }
+
+    let mut ordered = Vec::with_capacity(slots.len());
+    while let Some(std::cmp::Reverse((_, i))) = ready.pop() {
+        ordered.extend(slots[i].3.take());
+        for &child in children.get(&slots[i].0).into_iter().flatten() {
+            ready.push(std::cmp::Reverse((slots[child].2, child)));
+        }
+    }
+    if ordered.len() < slots.len() {
+        return Err(OrderingError::Cycle(slots.len() - ordered.len()));
+    }
+    Ok(ordered)
+}
+
+/// Envelope signer and verifier helpers.
+pub mod signing {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    /// Sign an envelope (header/body) with the provided key.
+    pub fn sign_envelope(env: &mut Envelope, signer: &SigningKey) {
+        let env_hash = envelope_hash(env);
+        let sig = signer.sign(&env_hash);
+        env.signatures.push(Signature {
+            signer: signer.verifying_key().to_bytes(),
+            signature: sig.to_bytes(),
+        });
+    }
+
+    /// Attach an attestation signature over its statement hash.
+    pub fn sign_attestation(att: &mut Attestation, signer: &SigningKey) {
+        let sig = signer.sign(&att.statement_hash);
+        att.signature = sig.to_bytes();
+        att.issuer = signer.verifying_key().to_bytes();
+    }
+
+    /// Domain separator keeping checkpoint signatures distinct from envelope ones.
+    const CHECKPOINT_DOMAIN: &[u8] = b"ea-ledger/checkpoint/v1";
+
+    /// Digest signed for a checkpoint: domain || length (u64 BE) || root.
+    pub fn checkpoint_digest(checkpoint: &Checkpoint) -> [u8; 32] {
+        let mut hasher = Hasher::new();
+        hasher.update(CHECKPOINT_DOMAIN);
+        hasher.update(&(checkpoint.length as u64).to_be_bytes());
+        hasher.update(&checkpoint.root);
+        *hasher.finalize().as_bytes()
+    }
+
+    /// Sign a checkpoint's length and root.
+    pub fn sign_checkpoint(checkpoint: Checkpoint, signer: &SigningKey) -> SignedCheckpoint {
+        let sig = signer.sign(&checkpoint_digest(&checkpoint));
+        SignedCheckpoint {
+            checkpoint,
+            signer: signer.verifying_key().to_bytes(),
+            signature: sig.to_bytes(),
+        }
+    }
+}
+
+/// Append-only log segmenter that emits Merkle checkpoints.
+#[derive(Debug)]
+pub struct MerkleSegmenter {
+    window: usize,
+    queue: VecDeque<[u8; 32]>,
+}
+
+impl MerkleSegmenter {
+    /// Create a new segmenter with a fixed window size.
+    pub fn new(window: usize) -> Self {
+        Self {
+            window,
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Push a new envelope hash and emit a segment root if window filled.
+    pub fn push(&mut self, env_hash: [u8; 32]) -> Option<[u8; 32]> {
+        self.queue.push_back(env_hash);
+        if self.queue.len() == self.window {
+            let root = compute_merkle(&self.queue.make_contiguous());
+            self.queue.clear();
+            Some(root)
+        } else {
+            None
+        }
+    }
+}
+
+fn compute_merkle(items: &[[u8; 32]]) -> [u8; 32] {
+    MerkleAlgorithm::Blake3.root(items).unwrap_or([0u8; 32])
+}
+
+/// Merkle path position for a sibling hash.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ProofPosition {
+    /// Sibling sits to the left of the node being proven.
+    Left,
+    /// Sibling sits to the right of the node being proven.
+    Right,
+}
+
+/// A node along a Merkle proof path.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProofNode {
+    /// The sibling hash at this level.
+    pub sibling: [u8; 32],
+    /// Whether the sibling is left or right of the path node.
+    pub position: ProofPosition,
+}
+
+/// Receipt proving inclusion of a log entry.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MerkleReceipt {
+    /// Index of the leaf in the log.
+    pub index: usize,
+    /// Total leaf count at time of receipt generation.
+    pub leaf_count: usize,
+    /// Hash of the leaf being proven (envelope hash).
+    pub leaf: [u8; 32],
+    /// Merkle root over the log at generation time.
+    pub root: [u8; 32],
+    /// Proof path from leaf to root.
+    pub path: Vec<ProofNode>,
+    /// Hash function the leaf and path were computed with.
+    #[serde(default)]
+    pub algorithm: MerkleAlgorithm,
+}
+
+impl MerkleReceipt {
+    /// Build a receipt from a set of leaves and a target index.
+    pub fn from_leaves(leaves: &[[u8; 32]], index: usize) -> Option<Self> {
+        Self::from_leaves_with(MerkleAlgorithm::default(), leaves, index)
+    }
+
+    /// Build a receipt whose path is hashed with `algorithm`.
+    pub fn from_leaves_with(
+        algorithm: MerkleAlgorithm,
+        leaves: &[[u8; 32]],
+        index: usize,
+    ) -> Option<Self> {
+        if leaves.is_empty() || index >= leaves.len() || !algorithm.is_supported() {
+            return None;
+        }
+
+        let mut path = Vec::new();
+        let mut current_index = index;
+        let mut level = leaves.to_vec();
+
+        while level.len() > 1 {
+            let sibling_index = if current_index % 2 == 0 {
+                current_index + 1
+            } else {
+                current_index - 1
+            };
+            let sibling = if sibling_index < level.len() {
+                level[sibling_index]
+            } else {
+                level[current_index]
+            };
+            let position = if current_index % 2 == 0 {
+                ProofPosition::Right
+            } else {
+                ProofPosition::Left
+            };
+            path.push(ProofNode { sibling, position });
+
+            let mut next_level = Vec::with_capacity((level.len() + 1) / 2);
+            for chunk in level.chunks(2) {
+                match chunk {
+                    [left, right] => next_level.push(algorithm.parent(left, right)),
+                    [solo] => next_level.push(algorithm.parent(solo, solo)),
+                    _ => unreachable!(),
+                }
+            }
+            current_index /= 2;
+            level = next_level;
+        }
+
+        Some(MerkleReceipt {
+            index,
+            leaf_count: leaves.len(),
+            leaf: leaves[index],
+            root: level[0],
+            path,
+            algorithm,
+        })
+    }
+
+    /// Build the same receipt as [`Self::from_leaves_with`] without holding
+    /// the leaf set in memory.
+    ///
+    /// `leaf(i)` yields the leaf hash at index `i` of a log with `leaf_count`
+    /// entries. Each sibling is rebuilt from the leaves beneath it, so memory
+    /// stays O(log n) while time stays O(n). Returns `None` if `leaf` does.
+    pub fn from_leaf_fn(
+        algorithm: MerkleAlgorithm,
+        leaf_count: usize,
+        index: usize,
+        leaf: impl Fn(usize) -> Option<[u8; 32]>,
+    ) -> Option<Self> {
+        if index >= leaf_count || !algorithm.is_supported() {
+            return None;
+        }
+
+        // Node count per level, leaves first; the last level holds the root.
+        let mut widths = vec![leaf_count];
+        while let Some(&width) = widths.last().filter(|&&w| w > 1) {
+            widths.push(width.div_ceil(2));
+        }
+
+        let target = leaf(index)?;
+        let mut path = Vec::with_capacity(widths.len() - 1);
+        let mut hash = target;
+        let mut current_index = index;
+        for (level, &width) in widths[..widths.len() - 1].iter().enumerate() {
+            let (sibling, position) = if current_index % 2 == 1 {
+                let sibling = subtree_node(algorithm, &widths, level, current_index - 1, &leaf)?;
+                (sibling, ProofPosition::Left)
+            } else if current_index + 1 < width {
+                let sibling = subtree_node(algorithm, &widths, level, current_index + 1, &leaf)?;
+                (sibling, ProofPosition::Right)
+            } else {
+                (hash, ProofPosition::Right)
+            };
+            hash = match position {
+                ProofPosition::Left => algorithm.parent(&sibling, &hash),
+                ProofPosition::Right => algorithm.parent(&hash, &sibling),
+            };
+            path.push(ProofNode { sibling, position });
+            current_index /= 2;
+        }
+
+        Some(MerkleReceipt {
+            index,
+            leaf_count,
+            leaf: target,
+            root: hash,
+            path,
+            algorithm,
+        })
+    }
+
+    /// Verify this receipt against the embedded root.
+    pub fn verify(&self) -> bool {
+        if !self.algorithm.is_supported() || (self.path.is_empty() && self.leaf_count != 1) {
+            return false;
+        }
+        let mut hash = self.leaf;
+        for node in &self.path {
+            hash = match node.position {
+                ProofPosition::Left => self.algorithm.parent(&node.sibling, &hash),
+                ProofPosition::Right => self.algorithm.parent(&hash, &node.sibling),
+            };
+        }
+        hash == self.root
+    }
+}
+
+/// Hash of node `index` at `level` (0 = leaves), pairing an odd last node
+/// with itself as [`MerkleReceipt::from_leaves_with`] does.
+fn subtree_node(
+    algorithm: MerkleAlgorithm,
+    widths: &[usize],
+    level: usize,
+    index: usize,
+    leaf: &impl Fn(usize) -> Option<[u8; 32]>,
+) -> Option<[u8; 32]> {
+    if level == 0 {
+        return leaf(index);
+    }
+    let left = subtree_node(algorithm, widths, level - 1, index * 2, leaf)?;
+    let right = if index * 2 + 1 < widths[level - 1] {
+        subtree_node(algorithm, widths, level - 1, index * 2 + 1, leaf)?
+    } else {
+        left
+    };
+    Some(algorithm.parent(&left, &right))
+}
+
+/// Verifies many receipts over one root, reusing interior hashes they share.
+///
+/// Each parent computed along a receipt's path is cached under its
+/// `(level, index)` together with the two children it was hashed from. A
+/// later receipt whose children at that position match reuses the parent
+/// instead of rehashing, so results are always identical to
+/// [`MerkleReceipt::verify`]. The cache is dropped whenever a receipt for a
+/// different root or algorithm is presented.
+#[derive(Debug, Default)]
+pub struct ReceiptVerifier {
+    root: Option<([u8; 32], MerkleAlgorithm)>,
+    nodes: HashMap<(usize, usize), CachedParent>,
+}
+
+/// Left child, right child, and the parent hashed from them.
+type CachedParent = ([u8; 32], [u8; 32], [u8; 32]);
+
+impl ReceiptVerifier {
+    /// Create a verifier with an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Verify `receipt`, reusing cached interior nodes where possible.
+    pub fn verify(&mut self, receipt: &MerkleReceipt) -> bool {
+        let algorithm = receipt.algorithm;
+        if !algorithm.is_supported() || (receipt.path.is_empty() && receipt.leaf_count != 1) {
+            return false;
+        }
+        if self.root != Some((receipt.root, algorithm)) {
+            self.nodes.clear();
+            self.root = Some((receipt.root, algorithm));
+        }
+        let mut hash = receipt.leaf;
+        let mut index = receipt.index;
+        for (level, node) in receipt.path.iter().enumerate() {
+            let (left, right) = match node.position {
+                ProofPosition::Left => (node.sibling, hash),
+                ProofPosition::Right => (hash, node.sibling),
+            };
+            let key = (level + 1, index / 2);
+            hash = match self.nodes.get(&key) {
+                Some(&(l, r, parent)) if l == left && r == right => parent,
+                _ => {
+                    let parent = algorithm.parent(&left, &right);
+                    self.nodes.insert(key, (left, right, parent));
+                    parent
+                }
+            };
+            index /= 2;
+        }
+        hash == receipt.root
+    }
+
+    /// Number of interior nodes currently cached.
+    pub fn cached_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+    use ledger_spec::{EnvelopeBody, EnvelopeHeader};
+    use rand_core::OsRng;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn sample_env(prev: Option<[u8; 32]>, ts: u64, sk: &SigningKey) -> Envelope {
+        let body = EnvelopeBody {
+            payload: serde_json::json!({"n": ts}),
+            payload_type: Some("test".into()),
+        };
+        let body_hash = hash_body(&body);
+        let header = EnvelopeHeader {
+            channel: "muscle_io".into(),
+            version: 1,
+            prev,
+            body_hash,
+            timestamp: ts,
+        };
+        let mut env = Envelope {
+            header,
+            body,
+            signatures: Vec::new(),
+            attestations: Vec::new(),
+        };
+        signing::sign_envelope(&mut env, sk);
+        env
+    }
+
+    fn registry(sk: &SigningKey) -> ChannelRegistry {
+        let mut registry = ChannelRegistry::new();
+        registry.upsert(ledger_spec::ChannelSpec {
+            name: "muscle_io".into(),
+            policy: ledger_spec::ChannelPolicy {
+                min_signers: 1,
+                allowed_signers: vec![sk.verifying_key().to_bytes()],
+                require_attestations: false,
+                enforce_timestamp_ordering: true,
+                max_attestations_per_envelope: ledger_spec::DEFAULT_MAX_ATTESTATIONS,
+                max_future_skew: None,
+                ordering: ledger_spec::OrderingStrategy::StrictChain,
+                allowed_payload_types: None,
+                require_payload_type: false,
+            },
+        });
+        registry
+    }
+
+    #[test]
+    fn append_and_checkpoint() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let log = AppendLog::new();
+        let mut prev = None;
+        for ts in 1..=3 {
+            let env = sample_env(prev, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            log.append(env, &reg).unwrap();
+        }
+        assert_eq!(log.len(), 3);
+        let mut writer = CheckpointWriter::new();
+        let cp = writer.maybe_checkpoint(&log, 2).unwrap();
+        assert_eq!(cp.length, 3);
+        assert!(cp.root.iter().any(|b| *b != 0));
+    }
+
+    #[test]
+    fn signed_checkpoint_verifies_and_detects_tampering() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let anchor = SigningKey::generate(&mut OsRng);
+        let log = AppendLog::new();
+        append_run(&log, &reg, &sk, 1, 3);
+        assert!(CheckpointWriter::new()
+            .maybe_signed_checkpoint(&log, 1)
+            .is_none());
+
+        let mut writer = CheckpointWriter::new().with_signer(anchor.clone());
+        let signed = writer.maybe_signed_checkpoint(&log, 1).unwrap();
+        assert_eq!(signed.checkpoint.length, 3);
+        assert!(verify_signed(&signed, &anchor.verifying_key()));
+        assert!(!verify_signed(&signed, &sk.verifying_key()));
+
+        let mut longer = signed.clone();
+        longer.checkpoint.length += 1;
+        assert!(!verify_signed(&longer, &anchor.verifying_key()));
+        let mut forged = signed.clone();
+        forged.checkpoint.root[0] ^= 0xFF;
+        assert!(!verify_signed(&forged, &anchor.verifying_key()));
+    }
+
+    fn append_run(log: &AppendLog, reg: &ChannelRegistry, sk: &SigningKey, from: u64, to: u64) {
+        let mut prev = log
+            .read(log.len().saturating_sub(1), 1)
+            .first()
+            .map(envelope_hash);
+        for ts in from..=to {
+            let env = sample_env(prev, ts, sk);
+            prev = Some(envelope_hash(&env));
+            log.append(env, reg).unwrap();
+        }
+    }
+
+    #[test]
+    fn ring_log_drops_oldest_and_keeps_absolute_offsets() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let log = RingAppendLog::new(3);
+        let mut prev = None;
+        for ts in 1..=5 {
+            let env = sample_env(prev, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            assert_eq!(log.append_with_index(env, &reg).unwrap(), ts as usize - 1);
+        }
+        assert_eq!(log.len(), 3);
+        assert_eq!(log.first_index(), 2);
+        assert!(log.read(0, 2).is_empty());
+        assert!(log.receipt_for(1).is_none());
+        let window = log.read(0, 10);
+        let stamps: Vec<_> = window.iter().map(|env| env.header.timestamp).collect();
+        assert_eq!(stamps, vec![3, 4, 5]);
+        assert_eq!(log.read(4, 1)[0].header.timestamp, 5);
+        assert_eq!(log.entries().count(), 3);
+        assert_eq!(
+            log.merkle_root(),
+            MerkleAlgorithm::default().root_for(&window)
+        );
+        log.verify_integrity().unwrap();
+
+        // The chain continues from the newest retained entry.
+        assert!(log.append(sample_env(None, 6, &sk), &reg).is_err());
+        let next = sample_env(prev, 6, &sk);
+        let tip = log.last_entry().unwrap();
+        assert_eq!(Some(envelope_hash(&tip)), next.header.prev);
+        log.append(next, &reg).unwrap();
+        assert_eq!(log.len(), 3);
+        assert_eq!(log.first_index(), 3);
+    }
+
+    #[test]
+    fn ring_log_sweep_expires_entries_by_ttl() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let log = RingAppendLog::new(10).with_ttl(Duration::from_secs(3));
+        let mut prev = None;
+        for ts in [1_000, 2_000, 3_000, 4_000, 5_000] {
+            let env = sample_env(prev, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            log.append(env, &reg).unwrap();
+        }
+        // An entry exactly `ttl` old is still live.
+        assert_eq!(log.sweep(4_000), 0);
+
+        // Entries stamped before 6_500 - 3_000 expire though the ring has room.
+        assert_eq!(log.sweep(6_500), 3);
+        assert_eq!(log.sweep(6_500), 0);
+        assert_eq!(log.len(), 2);
+        assert_eq!(log.first_index(), 3);
+        assert!(log.read(0, 3).is_empty());
+        assert!(log.receipt_for(2).is_none());
+        let live = log.read(0, 10);
+        assert_eq!(live.len(), 2);
+        assert_eq!(
+            log.merkle_root(),
+            MerkleAlgorithm::default().root_for(&live)
+        );
+        log.verify_integrity().unwrap();
+
+        // Once everything expires the chain still continues from the last append.
+        assert_eq!(log.sweep(60_000), 2);
+        assert_eq!(log.len(), 0);
+        assert!(log.merkle_root().is_none());
+        assert_eq!(
+            log.append_with_index(sample_env(prev, 61_000, &sk), &reg)
+                .unwrap(),
+            5
+        );
+
+        let unbounded = RingAppendLog::new(10);
+        unbounded.append(sample_env(None, 1, &sk), &reg).unwrap();
+        assert_eq!(unbounded.sweep(u64::MAX), 0);
+        assert_eq!(unbounded.len(), 1);
+    }
+
+    #[test]
+    fn append_expecting_reports_conflict_to_losing_writer() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let log = AppendLog::new();
+        append_run(&log, &reg, &sk, 1, 1);
+        let tip = Some(envelope_hash(&log.read(0, 1)[0]));
+
+        // Both writers build against the same tip; the first to commit wins.
+        let first = sample_env(tip, 2, &sk);
+        let second = sample_env(tip, 3, &sk);
+        assert_eq!(log.append_expecting(first.clone(), &reg, tip).unwrap(), 1);
+        let err = log.append_expecting(second, &reg, tip).unwrap_err();
+        let AppendError::Conflict(conflict) = err else {
+            panic!("expected conflict, got {err:?}");
+        };
+        assert_eq!(conflict.expected, tip);
+        assert_eq!(conflict.actual, Some(envelope_hash(&first)));
+
+        // The loser rebuilds against the reported tip and succeeds.
+        let retry = sample_env(conflict.actual, 3, &sk);
+        assert_eq!(
+            log.append_expecting(retry, &reg, conflict.actual).unwrap(),
+            2
+        );
+        assert_eq!(log.len(), 3);
+    }
+
+    #[test]
+    fn ordering_strategy_controls_branching() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let strict = registry(&sk);
+        let mut dag = strict.clone();
+        dag.upsert(ledger_spec::ChannelSpec {
+            name: "muscle_io".into(),
+            policy: ledger_spec::ChannelPolicy {
+                ordering: OrderingStrategy::Dag,
+                allowed_payload_types: None,
+                require_payload_type: false,
+                ..strict.policy_for("muscle_io").unwrap().clone()
+            },
+        });
+
+        // Two writers build on the same parent; the second is no longer on the tip.
+        let branch = |reg: &ChannelRegistry| {
+            let log = AppendLog::new();
+            append_run(&log, reg, &sk, 1, 1);
+            let parent = Some(envelope_hash(&log.read(0, 1)[0]));
+            let left = sample_env(parent, 2, &sk);
+            log.append(left.clone(), reg).unwrap();
+            let right = sample_env(parent, 3, &sk);
+            let res = log.append(right.clone(), reg);
+            (log, left, right, res)
+        };
+
+        let (log, left, _, res) = branch(&strict);
+        assert!(matches!(
+            res,
+            Err(AppendError::Validation(ValidationError::ChainMismatch))
+        ));
+        assert_eq!(log.tips(), vec![envelope_hash(&left)]);
+
+        let (log, left, right, res) = branch(&dag);
+        res.unwrap();
+        let (left, right) = (envelope_hash(&left), envelope_hash(&right));
+        assert_eq!(log.tips(), vec![left, right]);
+
+        // Extending a branch retires it as a tip; the parent must exist.
+        let next = sample_env(Some(left), 4, &sk);
+        let next_hash = envelope_hash(&next);
+        log.append(next, &dag).unwrap();
+        assert_eq!(log.tips(), vec![right, next_hash]);
+        let orphan = sample_env(Some([9u8; 32]), 5, &sk);
+        assert!(log.append(orphan, &dag).is_err());
+
+        // Receipts and replay cover both branches.
+        log.verify_integrity().unwrap();
+        assert!(log.validating_iter(&dag).all(|res| res.is_ok()));
+        ReplayValidator::new(dag.clone())
+            .validate_sequence(&log.read(0, log.len()))
+            .unwrap();
+        let copy = AppendLog::from_snapshot_verified(log.snapshot(), &dag).unwrap();
+        assert_eq!(copy.merkle_root(), log.merkle_root());
+
+        // The other logs index branches the same way, across reopen and
+        // eviction.
+        let dir = temp_dir("dag-tips");
+        let persistent = PersistentAppendLog::open(&dir).unwrap();
+        for env in log.read(0, log.len()) {
+            persistent.append(env, &dag).unwrap();
+        }
+        assert_eq!(persistent.tips(), log.tips());
+        drop(persistent);
+        let reopened = PersistentAppendLog::open(&dir).unwrap();
+        assert_eq!(reopened.tips(), log.tips());
+        assert!(reopened.validating_iter(&dag).all(|res| res.is_ok()));
+        drop(reopened);
+        let _ = std::fs::remove_dir_all(dir);
+
+        let ring = RingAppendLog::new(2);
+        for env in log.read(0, log.len()) {
+            ring.append(env, &dag).unwrap();
+        }
+        assert_eq!(ring.tips(), vec![right, next_hash]);
+        // `left` aged out, so it can no longer be branched from.
+        assert!(ring.append(sample_env(Some(left), 5, &sk), &dag).is_err());
+    }
+
+    #[test]
+    fn checkpoint_entries_trigger_matches_interval() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let log = AppendLog::new();
+        let mut writer = CheckpointWriter::new().with_triggers(vec![CheckpointTrigger::Entries(3)]);
+        append_run(&log, &reg, &sk, 1, 2);
+        assert!(writer.maybe_checkpoint(&log, 0).is_none());
+        append_run(&log, &reg, &sk, 3, 3);
+        assert_eq!(writer.maybe_checkpoint(&log, 0).unwrap().length, 3);
+        assert!(writer.maybe_checkpoint(&log, 0).is_none());
+    }
+
+    #[test]
+    fn checkpoint_bytes_trigger_fires_before_entries() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let log = AppendLog::new();
+        append_run(&log, &reg, &sk, 1, 1);
+        let one = serde_json::to_vec(&log.read(0, 1)[0]).unwrap().len() as u64;
+        let mut writer = CheckpointWriter::new().with_triggers(vec![
+            CheckpointTrigger::Entries(100),
+            CheckpointTrigger::Bytes(one * 2),
+        ]);
+        assert!(writer.maybe_checkpoint(&log, 0).is_none());
+        append_run(&log, &reg, &sk, 2, 2);
+        assert_eq!(writer.maybe_checkpoint(&log, 0).unwrap().length, 2);
+        // Counted bytes reset after each checkpoint.
+        append_run(&log, &reg, &sk, 3, 3);
+        assert!(writer.maybe_checkpoint(&log, 0).is_none());
+    }
+
+    #[test]
+    fn checkpoint_interval_trigger_waits_for_progress() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let log = AppendLog::new();
+        let mut idle = CheckpointWriter::new()
+            .with_triggers(vec![CheckpointTrigger::Interval(Duration::ZERO)]);
+        assert!(idle.maybe_checkpoint(&log, 0).is_none());
+        append_run(&log, &reg, &sk, 1, 1);
+        assert_eq!(idle.maybe_checkpoint(&log, 0).unwrap().length, 1);
+        assert!(idle.maybe_checkpoint(&log, 0).is_none());
+
+        let mut slow = CheckpointWriter::new()
+            .with_triggers(vec![CheckpointTrigger::Interval(Duration::from_secs(3600))]);
+        assert!(slow.maybe_checkpoint(&log, 0).is_none());
+    }
+
+    #[test]
+    fn merkle_segmenter_emits_root() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let mut segmenter = MerkleSegmenter::new(2);
+        let mut prev = None;
+        for ts in 1..=2 {
+            let env = sample_env(prev, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            let root = segmenter.push(envelope_hash(&env));
+            if ts == 2 {
+                assert!(root.is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn order_envelopes_repairs_shuffled_imports() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let mut chain = Vec::new();
+        let mut prev = None;
+        for ts in 1..=6 {
+            let env = sample_env(prev, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            chain.push(env);
+        }
+        let shuffled = vec![
+            chain[3].clone(),
+            chain[0].clone(),
+            chain[5].clone(),
+            chain[2].clone(),
+            chain[1].clone(),
+            chain[4].clone(),
+            chain[2].clone(),
+        ];
+        assert!(ReplayValidator::new(reg.clone())
+            .validate_sequence(&shuffled)
+            .is_err());
+        let ordered = order_envelopes(shuffled).unwrap();
+        assert_eq!(ordered, chain);
+        ReplayValidator::new(reg)
+            .validate_sequence(&ordered)
+            .unwrap();
+
+        let mut gapped = chain.clone();
+        let missing = gapped.remove(2);
+        assert_eq!(
+            order_envelopes(gapped).unwrap_err(),
+            OrderingError::MissingParent {
+                envelope: envelope_hash(&chain[3]),
+                prev: envelope_hash(&missing),
+            }
+        );
+
+        // Real envelopes cannot name each other (that needs a hash
+        // collision), so exercise cycle detection on synthetic links.
+        let looped = vec![
+            ([1; 32], Some([3; 32])),
+            ([2; 32], Some([1; 32])),
+            ([3; 32], Some([2; 32])),
+        ];
+        let err = order_by_links(looped, |&(id, prev)| (id, prev, 0)).unwrap_err();
+        assert_eq!(err, OrderingError::Cycle(3));
+    }
+
+    #[test]
+    fn replay_validator_detects_tamper() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let validator = ReplayValidator::new(reg);
+        let env1 = sample_env(None, 1, &sk);
+        let mut env2 = sample_env(Some(envelope_hash(&env1)), 2, &sk);
+        // Tamper body without updating hash
+        env2.body.payload = serde_json::json!({"n": 99});
+        let seq = vec![env1, env2];
+        let err = validator.validate_sequence(&seq).unwrap_err();
+        assert_eq!(err, ValidationError::BodyHashMismatch);
+    }
+
+    #[test]
+    fn merkle_receipt_roundtrip() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let log = AppendLog::new();
+        let mut prev = None;
+        for ts in 1..=4 {
+            let env = sample_env(prev, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            log.append(env, &reg).unwrap();
+        }
+        let receipt = log.receipt_for(2).expect("receipt exists");
+        assert!(receipt.verify());
+        assert_eq!(receipt.index, 2);
+    }
+
+    #[test]
+    fn receipt_verifier_matches_uncached_verify() {
+        let leaves: Vec<[u8; 32]> = (0..16u8).map(|i| [i; 32]).collect();
+        let mut receipts: Vec<MerkleReceipt> = (0..16)
+            .map(|i| MerkleReceipt::from_leaves(&leaves, i).unwrap())
+            .collect();
+        let mut forged = receipts[5].clone();
+        forged.path[2].sibling[0] ^= 1;
+        receipts.push(forged);
+
+        let mut verifier = ReceiptVerifier::new();
+        for receipt in &receipts {
+            assert_eq!(verifier.verify(receipt), receipt.verify());
+        }
+        assert!(!receipts[16].verify());
+        // One slot per interior node; the forged path overwrites rather than adds.
+        assert_eq!(verifier.cached_nodes(), 15);
+        // Re-verifying hits the cache and still agrees.
+        assert!(receipts[..16].iter().all(|r| verifier.verify(r)));
+
+        let other: Vec<[u8; 32]> = (0..4u8).map(|i| [i ^ 0xFF; 32]).collect();
+        let receipt = MerkleReceipt::from_leaves(&other, 3).unwrap();
+        assert!(verifier.verify(&receipt));
+        assert_eq!(verifier.cached_nodes(), 2);
+    }
+
+    #[test]
+    fn streaming_receipt_matches_from_leaves() {
+        let algorithms = [MerkleAlgorithm::Blake3, MerkleAlgorithm::Sha256];
+        for algorithm in algorithms.into_iter().filter(|a| a.is_supported()) {
+            for count in [1usize, 2, 5, 16, 33] {
+                let leaves: Vec<[u8; 32]> = (0..count).map(|i| [i as u8; 32]).collect();
+                for index in 0..count {
+                    let expected =
+                        MerkleReceipt::from_leaves_with(algorithm, &leaves, index).unwrap();
+                    let streamed = MerkleReceipt::from_leaf_fn(algorithm, count, index, |i| {
+                        leaves.get(i).copied()
+                    })
+                    .unwrap();
+                    assert_eq!(streamed.path, expected.path);
+                    assert_eq!(streamed.root, expected.root);
+                    assert_eq!(streamed, expected);
+                }
+                assert!(MerkleReceipt::from_leaf_fn(algorithm, count, count, |i| {
+                    leaves.get(i).copied()
+                })
+                .is_none());
+            }
+        }
+
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let dir = temp_dir("streaming-receipt");
+        let log = PersistentAppendLog::open(&dir).unwrap();
+        let mut prev = None;
+        for ts in 1..=7 {
+            let env = sample_env(prev, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            log.append(env, &reg).unwrap();
+        }
+        let leaves: Vec<[u8; 32]> = log
+            .read(0, 7)
+            .iter()
+            .map(|env| MerkleAlgorithm::default().leaf_hash(env))
+            .collect();
+        let receipt = log.receipt_for(4).unwrap();
+        assert_eq!(receipt, MerkleReceipt::from_leaves(&leaves, 4).unwrap());
+        assert_eq!(Some(receipt.root), log.merkle_root());
+        assert!(log.receipt_for(7).is_none());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    fn temp_dir(prefix: &str) -> std::path::PathBuf {
+        let mut path = std::env::temp_dir();
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        path.push(format!("ledger-core-{prefix}-{nanos}"));
+        let _ = std::fs::remove_dir_all(&path);
+        path
+    }
+
+    #[test]
+    fn persistent_log_recovers_merkle_root() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let dir = temp_dir("recover");
+        let log = PersistentAppendLog::open(&dir).expect("create persistent log");
+        let mut prev = None;
+        for ts in 1..=3 {
+            let env = sample_env(prev, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            log.append(env, &reg).expect("append to persistent log");
+        }
+        let expected_root = log.merkle_root().expect("root exists");
+        let expected_len = log.len();
+        drop(log);
+        let recovered = PersistentAppendLog::open(&dir).expect("reopen persistent log");
+        assert_eq!(recovered.len(), expected_len);
+        assert_eq!(recovered.merkle_root().unwrap(), expected_root);
+        assert!(recovered.receipt_for(1).unwrap().verify());
+    }
+
+    #[test]
+    fn persistent_log_reopens_from_signed_checkpoint() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let anchor = SigningKey::generate(&mut OsRng);
+        let dir = temp_dir("checkpoint");
+        // Four records compact into segments; two stay in the WAL.
+        let log = PersistentAppendLog::open_with_segment_size(&dir, 4).unwrap();
+        let mut prev = None;
+        for ts in 1..=6 {
+            let env = sample_env(prev, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            log.append(env, &reg).unwrap();
+        }
+        let root = log.merkle_root();
+        let prefix_root = MerkleAlgorithm::default()
+            .root_for(&log.read(0, 5))
+            .unwrap();
+        drop(log);
+        let checkpoint = |root| Checkpoint { length: 5, root };
+
+        let full = PersistentAppendLog::open(&dir).unwrap();
+        assert_eq!(full.records_checksummed_on_open(), 6);
+        drop(full);
+
+        let valid = signing::sign_checkpoint(checkpoint(prefix_root), &anchor);
+        let open = |checkpoint: &SignedCheckpoint| {
+            PersistentAppendLog::open_with_checkpoint(
+                &dir,
+                4,
+                MerkleAlgorithm::default(),
+                checkpoint,
+                &anchor.verifying_key(),
+            )
+            .unwrap()
+        };
+        let fast = open(&valid);
+        assert_eq!(fast.records_checksummed_on_open(), 1);
+        assert_eq!(fast.len(), 6);
+        assert_eq!(fast.merkle_root(), root);
+        // The caller's segment size survives the reopen: two more appends
+        // fill the WAL to four records and compact them.
+        for ts in 7..=8 {
+            let env = sample_env(prev, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            fast.append(env, &reg).unwrap();
+        }
+        assert_eq!(fast.state.read().wal_entries, 0);
+        let root = fast.merkle_root();
+        drop(fast);
+
+        let mut wrong_root = prefix_root;
+        wrong_root[0] ^= 0xFF;
+        let mismatched = signing::sign_checkpoint(checkpoint(wrong_root), &anchor);
+        let slow = open(&mismatched);
+        assert_eq!(slow.records_checksummed_on_open(), 8);
+        assert_eq!(slow.merkle_root(), root);
+        drop(slow);
+
+        let unsigned = signing::sign_checkpoint(checkpoint(prefix_root), &sk);
+        let slow = open(&unsigned);
+        assert_eq!(slow.records_checksummed_on_open(), 8);
+        drop(slow);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn persistent_log_compacts_segments() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let dir = temp_dir("compact");
+        let log = PersistentAppendLog::open_with_segment_size(&dir, 2).unwrap();
+        let mut prev = None;
+        for ts in 1..=4 {
+            let env = sample_env(prev, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            log.append(env, &reg).unwrap();
+        }
+        // Wal should have compacted after hitting the segment size twice.
+        let wal_path = dir.join("append.wal");
+        let wal_bytes = std::fs::read(&wal_path).unwrap_or_default();
+        assert!(
+            wal_bytes.is_empty(),
+            "wal should be empty after compaction, got {} bytes",
+            wal_bytes.len()
+        );
+        assert_eq!(log.len(), 4);
+    }
+
+    #[test]
+    fn persistent_log_persists_metadata_across_restart() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let dir = temp_dir("meta");
+        let log = PersistentAppendLog::open_with_segment_size(&dir, 2).unwrap();
+        let mut prev = None;
+        for ts in 1..=3 {
+            let env = sample_env(prev, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            log.append(env, &reg).unwrap();
+        }
+        let meta = log.metadata().expect("metadata persisted");
+        assert_eq!(meta.length, log.len());
+        assert_eq!(meta.root, log.merkle_root());
+        drop(log);
+
+        let reopened = PersistentAppendLog::open_with_segment_size(&dir, 2).unwrap();
+        let idx = reopened
+            .append_with_index(sample_env(prev, 4, &sk), &reg)
+            .unwrap();
+        assert_eq!(idx, meta.length);
+        let reopened_meta = reopened.metadata().unwrap();
+        assert_eq!(reopened_meta.length, reopened.len());
+        assert_eq!(reopened_meta.root, reopened.merkle_root());
+    }
+
+    #[test]
+    fn persistent_log_appends_metadata_to_sidecar() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let dir = temp_dir("meta-log");
+        let log = PersistentAppendLog::open_with_segment_size(&dir, 64).unwrap();
+        let snapshot = std::fs::read(dir.join("meta.json")).unwrap();
+        let mut prev = None;
+        for ts in 1..=10 {
+            let env = sample_env(prev, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            log.append(env, &reg).unwrap();
+        }
+        // Hot-path appends leave meta.json alone and only grow the sidecar.
+        assert_eq!(std::fs::read(dir.join("meta.json")).unwrap(), snapshot);
+        let meta = log.metadata().unwrap();
+        assert_eq!(meta.length, 10);
+        assert_eq!(meta.root, log.merkle_root());
+        drop(log);
+
+        let reopened = PersistentAppendLog::open_with_segment_size(&dir, 64).unwrap();
+        assert_eq!(reopened.len(), 10);
+        assert_eq!(reopened.merkle_root(), meta.root);
+        drop(reopened);
+
+        // Reopening compacts the sidecar; a fresh append records into it again.
+        let reopened = PersistentAppendLog::open_with_segment_size(&dir, 64).unwrap();
+        reopened.append(sample_env(prev, 11, &sk), &reg).unwrap();
+        drop(reopened);
+        let meta_log = dir.join("meta.log");
+        let mut bytes = std::fs::read(&meta_log).unwrap();
+        assert!(!bytes.is_empty());
+        bytes[0] ^= 0xFF;
+        std::fs::write(&meta_log, bytes).unwrap();
+        assert!(PersistentAppendLog::open_with_segment_size(&dir, 64).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn persistent_log_async_appends_land_in_order() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let dir = temp_dir("async");
+        let log = PersistentAppendLog::open_with_segment_size(&dir, 4).unwrap();
+        let mut tasks = Vec::new();
+        for _ in 0..8 {
+            let log = log.clone();
+            let reg = reg.clone();
+            let sk = sk.clone();
+            tasks.push(tokio::spawn(async move {
+                for _ in 0..4 {
+                    // Build against the current tail and retry if another task wins the slot.
+                    loop {
+                        let tail = log.read(log.len().saturating_sub(1), 1).pop();
+                        let prev = tail.as_ref().map(envelope_hash);
+                        let ts = tail.map(|env| env.header.timestamp + 1).unwrap_or(1);
+                        match log.append_async(sample_env(prev, ts, &sk), &reg).await {
+                            Ok(_) => break,
+                            Err(AppendError::Validation(ValidationError::ChainMismatch)) => {
+                                tokio::task::yield_now().await
+                            }
+                            Err(err) => panic!("unexpected append failure: {err}"),
+                        }
+                    }
+                }
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let entries = log.read(0, usize::MAX);
+        assert_eq!(entries.len(), 32);
+        ReplayValidator::new(reg.clone())
+            .validate_sequence(&entries)
+            .expect("async appends form a valid chain");
+        let timestamps: Vec<u64> = entries.iter().map(|env| env.header.timestamp).collect();
+        assert_eq!(timestamps, (1..=32).collect::<Vec<_>>());
+
+        let root = log.merkle_root();
+        drop(log);
+        let reopened = PersistentAppendLog::open_with_segment_size(&dir, 4).unwrap();
+        assert_eq!(reopened.len(), 32);
+        assert_eq!(reopened.merkle_root(), root);
+    }
+
+    #[test]
+    fn persistent_log_dedups_retried_append() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let dir = temp_dir("dedup");
+        let log = PersistentAppendLog::open(&dir)
+            .unwrap()
+            .with_dedup_capacity(4);
+        let env = sample_env(None, 1, &sk);
+        let first = log.append_with_index(env.clone(), &reg).unwrap();
+        let second = log.append_with_index(env, &reg).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(log.len(), 1);
+    }
+
+    #[test]
+    fn dedup_cache_evicts_least_recent() {
+        let cache = DedupCache::new(2);
+        cache.record([1; 32], 0);
+        cache.record([2; 32], 1);
+        assert_eq!(cache.lookup(&[1; 32]), Some(0));
+        cache.record([3; 32], 2);
+        assert_eq!(cache.lookup(&[2; 32]), None);
+        assert_eq!(cache.lookup(&[1; 32]), Some(0));
+        assert_eq!(cache.lookup(&[3; 32]), Some(2));
+
+        // Re-recording a known hash refreshes it without evicting anything
+        cache.record([1; 32], 5);
+        assert_eq!(cache.lookup(&[3; 32]), Some(2));
+        assert_eq!(cache.lookup(&[1; 32]), Some(5));
+        cache.record([4; 32], 3);
+        assert_eq!(cache.lookup(&[3; 32]), None);
+        assert_eq!(
+            cache.append_once([1; 32], || Err::<usize, ()>(())),
+            Ok((5, false))
+        );
+    }
+
+    #[test]
+    fn persistent_log_rejects_corrupt_metadata() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let dir = temp_dir("meta-mismatch");
+        let log = PersistentAppendLog::open(&dir).unwrap();
+        log.append(sample_env(None, 1, &sk), &reg).unwrap();
+        let meta_path = dir.join("meta.json");
+        let mut meta: PersistentMetadata =
+            serde_json::from_slice(&std::fs::read(&meta_path).unwrap()).unwrap();
+        meta.length += 1;
+        std::fs::write(&meta_path, serde_json::to_vec(&meta).unwrap()).unwrap();
+        let err = PersistentAppendLog::open(&dir).unwrap_err();
+        assert!(err.to_string().contains("metadata mismatch"));
+    }
+
+    #[test]
+    fn persistent_log_recovers_prefix_of_torn_wal() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let dir = temp_dir("torn-wal");
+        let log = PersistentAppendLog::open(&dir).unwrap();
+        let mut envs = Vec::new();
+        let mut prev = None;
+        for ts in 1..=3 {
+            let env = sample_env(prev, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            envs.push(env.clone());
+            log.append(env, &reg).unwrap();
+        }
+        let committed = log.metadata().unwrap();
+        assert_eq!(committed.length, 3);
+        drop(log);
+        // Simulate losing half of the last committed record; the metadata
+        // still records all three entries.
+        let third = envs.pop().unwrap();
+        let record = encode_record(&third, None, MerkleAlgorithm::default()).unwrap();
+        let wal_path = dir.join("append.wal");
+        let full_len = std::fs::metadata(&wal_path).unwrap().len();
+        let intact_len = full_len - record.len() as u64;
+        OpenOptions::new()
+            .write(true)
+            .open(&wal_path)
+            .unwrap()
+            .set_len(intact_len + record.len() as u64 / 2)
+            .unwrap();
+
+        let err = PersistentAppendLog::open(&dir).unwrap_err();
+        assert!(err.to_string().contains("truncated record body"), "{err}");
+
+        let recovered =
+            PersistentAppendLog::open_with_recovery(&dir, WalRecovery::RecoverPrefix).unwrap();
+        assert!(recovered.wal_truncated());
+        assert_eq!(recovered.read(0, 3), envs);
+        assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), intact_len);
+        let rewritten = recovered.metadata().unwrap();
+        assert_eq!(rewritten.length, 2);
+        assert_eq!(rewritten.root, recovered.merkle_root());
+        recovered.append(third, &reg).unwrap();
+        drop(recovered);
+
+        let reopened = PersistentAppendLog::open(&dir).unwrap();
+        assert!(!reopened.wal_truncated());
+        assert_eq!(reopened.len(), 3);
+        assert_eq!(reopened.metadata(), Some(committed));
+        assert_eq!(reopened.verify_integrity(), Ok(()));
+    }
+
+    #[test]
+    fn persistent_log_recovery_rejects_diverged_prefix() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let dir = temp_dir("torn-wal-diverged");
+        let log = PersistentAppendLog::open(&dir).unwrap();
+        let mut prev = None;
+        for ts in 1..=3 {
+            let env = sample_env(prev, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            log.append(env, &reg).unwrap();
+        }
+        drop(log);
+        // Metadata recorded for two entries must still match once the third
+        // is torn away.
+        let mut meta_log = std::fs::read(dir.join("meta.log")).unwrap();
+        let second = META_RECORD_LEN..2 * META_RECORD_LEN;
+        let forged = PersistentMetadata {
+            length: 2,
+            root: Some([7u8; 32]),
+            algorithm: MerkleAlgorithm::default(),
+        };
+        meta_log[second].copy_from_slice(&encode_meta_record(&forged));
+        std::fs::write(dir.join("meta.log"), &meta_log).unwrap();
+        let wal_path = dir.join("append.wal");
+        let len = std::fs::metadata(&wal_path).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&wal_path)
+            .unwrap()
+            .set_len(len - 1)
+            .unwrap();
+
+        let err =
+            PersistentAppendLog::open_with_recovery(&dir, WalRecovery::RecoverPrefix).unwrap_err();
+        assert!(err.to_string().contains("metadata mismatch"), "{err}");
+    }
+
+    fn bulky_env(prev: Option<[u8; 32]>, ts: u64, sk: &SigningKey) -> Envelope {
+        let mut env = sample_env(prev, ts, sk);
+        env.body.payload = serde_json::json!({"n": ts, "pad": "ea".repeat(2048)});
+        env.header.body_hash = hash_body(&env.body);
+        env.signatures.clear();
+        signing::sign_envelope(&mut env, sk);
+        env
+    }
+
+    #[test]
+    fn persistent_log_recovers_compressed_records() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let mut envs = Vec::new();
+        let mut prev = None;
+        for ts in 1..=5 {
+            let env = bulky_env(prev, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            envs.push(env);
+        }
+
+        // Seed the WAL with a pre-flag record to confirm legacy logs still load.
+        let dir = temp_dir("compressed");
+        std::fs::create_dir_all(&dir).unwrap();
+        let json = serde_json::to_vec(&envs[0]).unwrap();
+        let mut legacy = (json.len() as u32).to_be_bytes().to_vec();
+        legacy.extend_from_slice(&MerkleAlgorithm::Blake3.checksum(&json));
+        legacy.extend_from_slice(&json);
+        std::fs::write(dir.join("append.wal"), legacy).unwrap();
+
+        let log = PersistentAppendLog::open_with_segment_size(&dir, 2)
+            .unwrap()
+            .with_compression(3);
+        assert_eq!(log.len(), 1);
+        for env in envs.iter().skip(1) {
+            log.append(env.clone(), &reg).unwrap();
+        }
+        drop(log);
+
+        let plain_dir = temp_dir("uncompressed");
+        let plain = PersistentAppendLog::open_with_segment_size(&plain_dir, 2).unwrap();
+        for env in &envs {
+            plain.append(env.clone(), &reg).unwrap();
+        }
+
+        let reopened = PersistentAppendLog::open_with_segment_size(&dir, 2).unwrap();
+        assert_eq!(reopened.len(), envs.len());
+        assert_eq!(reopened.read(0, usize::MAX), envs);
+        assert_eq!(reopened.merkle_root(), plain.merkle_root());
+        assert!(reopened.storage_usage_bytes() < plain.storage_usage_bytes());
+    }
+
+    #[test]
+    fn persistent_log_sync_policy_controls_wal_fsyncs() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let mut envs = Vec::new();
+        let mut prev = None;
+        for ts in 1..=7 {
+            let env = sample_env(prev, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            envs.push(env);
+        }
+
+        let always = PersistentAppendLog::open(temp_dir("sync-always")).unwrap();
+        for env in &envs {
+            always.append(env.clone(), &reg).unwrap();
+        }
+        assert_eq!(always.wal_syncs(), 7);
+
+        let dir = temp_dir("sync-every-n");
+        let every =
+            PersistentAppendLog::open_with_sync_policy(&dir, SyncPolicy::EveryN(3)).unwrap();
+        for (i, env) in envs.iter().enumerate() {
+            every.append(env.clone(), &reg).unwrap();
+            assert_eq!(every.wal_syncs(), (i as u64 + 1) / 3);
+        }
+        // The seventh append is still pending; dropping the log syncs it.
+        drop(every);
+        let reopened = PersistentAppendLog::open(&dir).unwrap();
+        assert_eq!(reopened.read(0, usize::MAX), envs);
+        assert_eq!(reopened.merkle_root(), always.merkle_root());
+
+        let dir = temp_dir("sync-never");
+        let never = PersistentAppendLog::open_with_sync_policy(&dir, SyncPolicy::Never).unwrap();
+        for env in &envs {
+            never.append(env.clone(), &reg).unwrap();
+        }
+        assert_eq!(never.wal_syncs(), 0);
+        never.flush().unwrap();
+        drop(never);
+        assert_eq!(PersistentAppendLog::open(&dir).unwrap().len(), 7);
+    }
+
+    #[test]
+    fn persistent_log_dedups_repeated_bodies() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let template = bulky_env(None, 1, &sk);
+        let mut envs = Vec::new();
+        let mut prev = None;
+        for ts in 1..=20 {
+            let mut env = sample_env(prev, ts, &sk);
+            env.body = template.body.clone();
+            env.header.body_hash = template.header.body_hash;
+            env.signatures.clear();
+            signing::sign_envelope(&mut env, &sk);
+            prev = Some(envelope_hash(&env));
+            envs.push(env);
         }
-        hash == self.root
-    }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use ed25519_dalek::SigningKey;
-    use ledger_spec::{EnvelopeBody, EnvelopeHeader};
-    use rand_core::OsRng;
-    use std::time::{SystemTime, UNIX_EPOCH};
+        let dir = temp_dir("body-dedup");
+        let log = PersistentAppendLog::open_with_segment_size(&dir, 8)
+            .unwrap()
+            .with_body_dedup();
+        for env in &envs[..10] {
+            log.append(env.clone(), &reg).unwrap();
+        }
+        drop(log);
+        // Reopening must remember stored bodies so they are not written again.
+        let log = PersistentAppendLog::open_with_segment_size(&dir, 8)
+            .unwrap()
+            .with_body_dedup();
+        for env in &envs[10..] {
+            log.append(env.clone(), &reg).unwrap();
+        }
+        drop(log);
 
-    fn sample_env(prev: Option<[u8; 32]>, ts: u64, sk: &SigningKey) -> Envelope {
-        let body = EnvelopeBody {
-            payload: serde_json::json!({"n": ts}),
-            payload_type: Some("test".into()),
-        };
-        let body_hash = hash_body(&body);
-        let header = EnvelopeHeader {
-            channel: "muscle_io".into(),
-            version: 1,
-            prev,
-            body_hash,
-            timestamp: ts,
-        };
-        let mut env = Envelope {
-            header,
-            body,
-            signatures: Vec::new(),
-            attestations: Vec::new(),
-        };
-        signing::sign_envelope(&mut env, sk);
-        env
-    }
+        let plain = PersistentAppendLog::open_with_segment_size(temp_dir("body-plain"), 8).unwrap();
+        for env in &envs {
+            plain.append(env.clone(), &reg).unwrap();
+        }
 
-    fn registry(sk: &SigningKey) -> ChannelRegistry {
-        let mut registry = ChannelRegistry::new();
-        registry.upsert(ledger_spec::ChannelSpec {
-            name: "muscle_io".into(),
-            policy: ledger_spec::ChannelPolicy {
-                min_signers: 1,
-                allowed_signers: vec![sk.verifying_key().to_bytes()],
-                require_attestations: false,
-                enforce_timestamp_ordering: true,
-            },
-        });
-        registry
+        let reopened = PersistentAppendLog::open_with_segment_size(&dir, 8).unwrap();
+        assert_eq!(reopened.read(0, usize::MAX), envs);
+        assert_eq!(reopened.merkle_root(), plain.merkle_root());
+        let bodies = std::fs::metadata(dir.join("bodies.bin")).unwrap().len();
+        assert!(bodies < 2 * serde_json::to_vec(&template.body).unwrap().len() as u64);
+        assert!(reopened.storage_usage_bytes().unwrap() * 3 < plain.storage_usage_bytes().unwrap());
     }
 
     #[test]
-    fn append_and_checkpoint() {
+    fn verify_integrity_passes_for_clean_logs() {
         let sk = SigningKey::generate(&mut OsRng);
         let reg = registry(&sk);
         let log = AppendLog::new();
+        let persistent = PersistentAppendLog::open(temp_dir("integrity")).unwrap();
         let mut prev = None;
-        for ts in 1..=3 {
+        for ts in 1..=10 {
             let env = sample_env(prev, ts, &sk);
             prev = Some(envelope_hash(&env));
-            log.append(env, &reg).unwrap();
+            log.append(env.clone(), &reg).unwrap();
+            persistent.append(env, &reg).unwrap();
         }
-        assert_eq!(log.len(), 3);
-        let mut writer = CheckpointWriter::new();
-        let cp = writer.maybe_checkpoint(&log, 2).unwrap();
-        assert_eq!(cp.length, 3);
-        assert!(cp.root.iter().any(|b| *b != 0));
+        assert_eq!(log.verify_integrity(), Ok(()));
+        assert_eq!(persistent.verify_integrity(), Ok(()));
+        assert_eq!(AppendLog::new().verify_integrity(), Ok(()));
     }
 
     #[test]
-    fn merkle_segmenter_emits_root() {
+    fn verify_integrity_reports_corrupted_entry() {
         let sk = SigningKey::generate(&mut OsRng);
-        let mut segmenter = MerkleSegmenter::new(2);
+        let reg = registry(&sk);
+        let log = AppendLog::new();
         let mut prev = None;
-        for ts in 1..=2 {
+        for ts in 1..=10 {
             let env = sample_env(prev, ts, &sk);
             prev = Some(envelope_hash(&env));
-            let root = segmenter.push(envelope_hash(&env));
-            if ts == 2 {
-                assert!(root.is_some());
-            }
+            log.append(env, &reg).unwrap();
         }
+        log.entries.write()[6].body.payload = serde_json::json!({"n": 999});
+        assert_eq!(log.verify_integrity(), Err(6));
     }
 
     #[test]
-    fn replay_validator_detects_tamper() {
+    fn ledger_archive_roundtrip_rejects_tampering() {
         let sk = SigningKey::generate(&mut OsRng);
         let reg = registry(&sk);
-        let validator = ReplayValidator::new(reg);
-        let env1 = sample_env(None, 1, &sk);
-        let mut env2 = sample_env(Some(envelope_hash(&env1)), 2, &sk);
-        // Tamper body without updating hash
-        env2.body.payload = serde_json::json!({"n": 99});
-        let seq = vec![env1, env2];
-        let err = validator.validate_sequence(&seq).unwrap_err();
-        assert_eq!(err, ValidationError::BodyHashMismatch);
+        for algorithm in supported_algorithms() {
+            let log = AppendLog::with_algorithm(algorithm).unwrap();
+            let mut prev = None;
+            for ts in 1..=10 {
+                let env = sample_env(prev, ts, &sk);
+                prev = Some(envelope_hash(&env));
+                log.append(env, &reg).unwrap();
+            }
+            let root = log.merkle_root().unwrap();
+            let archive = log.export_segment(3, 7).unwrap();
+            let encoded = serde_json::to_vec(&archive).unwrap();
+            let decoded: LedgerArchive = serde_json::from_slice(&encoded).unwrap();
+            assert_eq!(import_and_verify(&decoded, root).unwrap(), log.read(3, 4));
+            assert_eq!(
+                import_and_verify(&decoded, [0u8; 32]),
+                Err(ArchiveError::RootMismatch)
+            );
+
+            let mut tampered = decoded.clone();
+            tampered.envelopes[2].body.payload = serde_json::json!({"n": 999});
+            assert_eq!(
+                import_and_verify(&tampered, root),
+                Err(ArchiveError::InvalidEntry(5))
+            );
+
+            let mut reordered = decoded;
+            reordered.envelopes.swap(0, 1);
+            assert!(import_and_verify(&reordered, root).is_err());
+            assert!(matches!(
+                log.export_segment(7, 11),
+                Err(ArchiveError::InvalidRange { .. })
+            ));
+        }
     }
 
     #[test]
-    fn merkle_receipt_roundtrip() {
+    fn ledger_archive_with_foreign_algorithm_is_rejected() {
         let sk = SigningKey::generate(&mut OsRng);
         let reg = registry(&sk);
         let log = AppendLog::new();
@@ -871,106 +4001,437 @@ mod tests {
             prev = Some(envelope_hash(&env));
             log.append(env, &reg).unwrap();
         }
-        let receipt = log.receipt_for(2).expect("receipt exists");
-        assert!(receipt.verify());
-        assert_eq!(receipt.index, 2);
+        let root = log.merkle_root().unwrap();
+        let mut archive = log.export_segment(0, 4).unwrap();
+        // Relabelled receipts must fail cleanly, including in builds where
+        // SHA-256 is unavailable.
+        for receipt in &mut archive.receipts {
+            receipt.algorithm = MerkleAlgorithm::Sha256;
+        }
+        let encoded = serde_json::to_vec(&archive).unwrap();
+        let decoded: LedgerArchive = serde_json::from_slice(&encoded).unwrap();
+        assert_eq!(
+            import_and_verify(&decoded, root),
+            Err(ArchiveError::InvalidEntry(0))
+        );
     }
 
-    fn temp_dir(prefix: &str) -> std::path::PathBuf {
-        let mut path = std::env::temp_dir();
-        let nanos = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos();
-        path.push(format!("ledger-core-{prefix}-{nanos}"));
-        let _ = std::fs::remove_dir_all(&path);
-        path
+    fn supported_algorithms() -> Vec<MerkleAlgorithm> {
+        [MerkleAlgorithm::Blake3, MerkleAlgorithm::Sha256]
+            .into_iter()
+            .filter(|algorithm| algorithm.is_supported())
+            .collect()
     }
 
     #[test]
-    fn persistent_log_recovers_merkle_root() {
+    fn merkle_algorithms_produce_consistent_roots() {
         let sk = SigningKey::generate(&mut OsRng);
         let reg = registry(&sk);
-        let dir = temp_dir("recover");
-        let log = PersistentAppendLog::open(&dir).expect("create persistent log");
+        let mut envs = Vec::new();
         let mut prev = None;
-        for ts in 1..=3 {
+        for ts in 1..=5 {
             let env = sample_env(prev, ts, &sk);
             prev = Some(envelope_hash(&env));
-            log.append(env, &reg).expect("append to persistent log");
+            envs.push(env);
         }
-        let expected_root = log.merkle_root().expect("root exists");
-        let expected_len = log.len();
+        let mut roots = Vec::new();
+        for algorithm in supported_algorithms() {
+            let memory = AppendLog::with_algorithm(algorithm).unwrap();
+            let dir = temp_dir("algorithm");
+            let persistent = PersistentAppendLog::open_with_algorithm(&dir, algorithm).unwrap();
+            for env in &envs {
+                memory.append(env.clone(), &reg).unwrap();
+                persistent.append(env.clone(), &reg).unwrap();
+            }
+            let root = memory.merkle_root().unwrap();
+            assert_eq!(persistent.merkle_root(), Some(root));
+            let receipt = memory.receipt_for(3).unwrap();
+            assert_eq!(receipt.algorithm, algorithm);
+            assert!(receipt.verify());
+            assert_eq!(memory.verify_integrity(), Ok(()));
+            drop(persistent);
+            let reopened = PersistentAppendLog::open_with_algorithm(&dir, algorithm).unwrap();
+            assert_eq!(reopened.merkle_root(), Some(root));
+            roots.push(root);
+        }
+        // BLAKE3 roots are unchanged from logs built before algorithms were selectable.
+        let legacy = AppendLog::new();
+        for env in &envs {
+            legacy.append(env.clone(), &reg).unwrap();
+        }
+        assert_eq!(legacy.merkle_root(), Some(roots[0]));
+        if roots.len() > 1 {
+            assert_ne!(roots[0], roots[1]);
+        }
+    }
+
+    #[test]
+    fn persistent_log_rejects_mismatched_algorithm() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let dir = temp_dir("algorithm-mismatch");
+        let log = PersistentAppendLog::open(&dir).unwrap();
+        log.append(sample_env(None, 1, &sk), &reg).unwrap();
+        assert_eq!(log.metadata().unwrap().algorithm, MerkleAlgorithm::Blake3);
         drop(log);
-        let recovered = PersistentAppendLog::open(&dir).expect("reopen persistent log");
-        assert_eq!(recovered.len(), expected_len);
-        assert_eq!(recovered.merkle_root().unwrap(), expected_root);
-        assert!(recovered.receipt_for(1).unwrap().verify());
+
+        let err = PersistentAppendLog::open_with_algorithm(&dir, MerkleAlgorithm::Sha256)
+            .unwrap_err()
+            .to_string();
+        if MerkleAlgorithm::Sha256.is_supported() {
+            assert!(
+                err.contains("uses Blake3 but was opened with Sha256"),
+                "{err}"
+            );
+        } else {
+            assert!(err.contains("requires the `sha256` feature"), "{err}");
+        }
+
+        // A log recorded under another algorithm is refused by a default open.
+        let meta_path = dir.join("meta.json");
+        let mut meta: PersistentMetadata =
+            serde_json::from_slice(&std::fs::read(&meta_path).unwrap()).unwrap();
+        meta.algorithm = MerkleAlgorithm::Sha256;
+        std::fs::write(&meta_path, serde_json::to_vec(&meta).unwrap()).unwrap();
+        let err = PersistentAppendLog::open(&dir).unwrap_err().to_string();
+        assert!(
+            err.contains("uses Sha256 but was opened with Blake3"),
+            "{err}"
+        );
     }
 
     #[test]
-    fn persistent_log_compacts_segments() {
+    fn snapshot_fork_is_independent() {
         let sk = SigningKey::generate(&mut OsRng);
         let reg = registry(&sk);
-        let dir = temp_dir("compact");
-        let log = PersistentAppendLog::open_with_segment_size(&dir, 2).unwrap();
+        let log = AppendLog::new();
         let mut prev = None;
-        for ts in 1..=4 {
+        for ts in 1..=3 {
             let env = sample_env(prev, ts, &sk);
             prev = Some(envelope_hash(&env));
             log.append(env, &reg).unwrap();
         }
-        // Wal should have compacted after hitting the segment size twice.
-        let wal_path = dir.join("append.wal");
-        let wal_bytes = std::fs::read(&wal_path).unwrap_or_default();
-        assert!(
-            wal_bytes.is_empty(),
-            "wal should be empty after compaction, got {} bytes",
-            wal_bytes.len()
-        );
+        let snapshot = log.snapshot();
+        let encoded = serde_json::to_vec(&snapshot).unwrap();
+        let decoded: LogSnapshot = serde_json::from_slice(&encoded).unwrap();
+        let fork = AppendLog::from_snapshot_verified(decoded, &reg).unwrap();
+        assert_eq!(fork.merkle_root(), snapshot.root);
+
+        let mut original_next = sample_env(prev, 4, &sk);
+        original_next.body.payload = serde_json::json!({"branch": "original"});
+        original_next.header.body_hash = hash_body(&original_next.body);
+        original_next.signatures.clear();
+        signing::sign_envelope(&mut original_next, &sk);
+        log.append(original_next, &reg).unwrap();
+        let fork_next = sample_env(prev, 4, &sk);
+        let fork_tail = sample_env(Some(envelope_hash(&fork_next)), 5, &sk);
+        fork.append(fork_next, &reg).unwrap();
+        fork.append(fork_tail, &reg).unwrap();
+
         assert_eq!(log.len(), 4);
+        assert_eq!(fork.len(), 5);
+        assert_ne!(log.read(3, 1), fork.read(3, 1));
+        assert_eq!(log.read(0, 3), fork.read(0, 3));
+        assert_eq!(AppendLog::from_snapshot(snapshot).len(), 3);
+
+        let mut tampered = fork.snapshot();
+        tampered.entries[1].body.payload = serde_json::json!({"n": 42});
+        assert!(AppendLog::from_snapshot_verified(tampered, &reg).is_err());
     }
 
     #[test]
-    fn persistent_log_persists_metadata_across_restart() {
+    fn validating_iter_stops_at_first_chain_break() {
         let sk = SigningKey::generate(&mut OsRng);
         let reg = registry(&sk);
-        let dir = temp_dir("meta");
-        let log = PersistentAppendLog::open_with_segment_size(&dir, 2).unwrap();
+        let mut entries = Vec::new();
         let mut prev = None;
-        for ts in 1..=3 {
-            let env = sample_env(prev, ts, &sk);
+        for ts in 1..=5 {
+            let link = if ts == 4 { Some([0xAB; 32]) } else { prev };
+            let env = sample_env(link, ts, &sk);
+            prev = Some(envelope_hash(&env));
+            entries.push(env);
+        }
+        let log = AppendLog::from_snapshot(LogSnapshot {
+            root: MerkleAlgorithm::default().root_for(&entries),
+            entries: entries.clone(),
+            algorithm: MerkleAlgorithm::default(),
+        });
+
+        let results: Vec<_> = log.validating_iter(&reg).collect();
+        assert_eq!(results.len(), 4);
+        for (result, expected) in results[..3].iter().zip(&entries) {
+            assert_eq!(result.as_ref().unwrap(), expected);
+        }
+        assert_eq!(results[3], Err(ValidationError::ChainMismatch));
+    }
+
+    #[test]
+    fn fold_materializes_latest_payload_per_key() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let log: Arc<dyn AppendLogStorage> = Arc::new(AppendLog::new());
+        let mut prev = None;
+        // Span more than one read chunk so the fold crosses chunk boundaries.
+        for ts in 1..=300u64 {
+            let mut env = sample_env(prev, ts, &sk);
+            env.body.payload = serde_json::json!({"key": format!("k{}", ts % 7), "value": ts});
+            if ts % 2 == 0 {
+                env.header.channel = "config".into();
+            }
+            env.header.body_hash = hash_body(&env.body);
+            env.signatures.clear();
+            signing::sign_envelope(&mut env, &sk);
             prev = Some(envelope_hash(&env));
             log.append(env, &reg).unwrap();
         }
-        let meta = log.metadata().expect("metadata persisted");
-        assert_eq!(meta.length, log.len());
-        assert_eq!(meta.root, log.merkle_root());
-        drop(log);
 
-        let reopened = PersistentAppendLog::open_with_segment_size(&dir, 2).unwrap();
-        let idx = reopened
-            .append_with_index(sample_env(prev, 4, &sk), &reg)
+        let latest = |mut view: HashMap<String, u64>, env: &Envelope| {
+            let key = env.body.payload["key"].as_str().unwrap().to_string();
+            view.insert(key, env.body.payload["value"].as_u64().unwrap());
+            view
+        };
+        let view = log.fold_log(HashMap::new(), latest);
+        assert_eq!(view.len(), 7);
+        for (key, value) in &view {
+            let k: u64 = key[1..].parse().unwrap();
+            // Last ts in 1..=300 with ts % 7 == k.
+            assert_eq!(*value, 300 - (300 - k) % 7);
+        }
+
+        let config = log.fold_channel("config", HashMap::new(), latest);
+        assert!(config.values().all(|value| value % 2 == 0));
+        assert_eq!((view["k5"], config["k5"]), (299, 292));
+        assert_eq!(log.fold_channel("config", 0, |n, _| n + 1), 150);
+    }
+
+    #[derive(Debug)]
+    struct CounterOnly;
+
+    impl PayloadValidator for CounterOnly {
+        fn validate(&self, payload_type: &str, payload: &serde_json::Value) -> Result<(), String> {
+            match payload_type {
+                "test" if payload["n"].is_u64() => Ok(()),
+                other => Err(format!("unexpected {other} payload")),
+            }
+        }
+    }
+
+    fn typed_env(prev: Option<[u8; 32]>, ts: u64, sk: &SigningKey, payload_type: &str) -> Envelope {
+        let mut env = sample_env(prev, ts, sk);
+        env.body.payload_type = Some(payload_type.into());
+        env.header.body_hash = hash_body(&env.body);
+        env.signatures.clear();
+        signing::sign_envelope(&mut env, sk);
+        env
+    }
+
+    #[test]
+    fn payload_validator_gates_appends() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let reg = registry(&sk);
+        let log = AppendLog::new().with_payload_validator(Arc::new(CounterOnly));
+        let first = sample_env(None, 1, &sk);
+        let rejected = typed_env(Some(envelope_hash(&first)), 2, &sk, "other");
+        log.append(first.clone(), &reg).unwrap();
+        let err = log.append(rejected.clone(), &reg).unwrap_err();
+        assert!(matches!(err, AppendError::Schema(ref msg) if msg.contains("other")));
+        assert_eq!(log.len(), 1);
+
+        let dir = temp_dir("payload-validator");
+        let persistent = PersistentAppendLog::open(&dir)
+            .unwrap()
+            .with_payload_validator(Arc::new(CounterOnly));
+        persistent.append(first.clone(), &reg).unwrap();
+        let err = persistent.append(rejected, &reg).unwrap_err();
+        assert!(matches!(err, AppendError::Schema(_)));
+        drop(persistent);
+        assert_eq!(PersistentAppendLog::open(&dir).unwrap().len(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "json-schema")]
+    #[test]
+    fn json_schema_validator_checks_registered_types() {
+        let validator = JsonSchemaValidator::new()
+            .with_schema(
+                "test",
+                &serde_json::json!({
+                    "type": "object",
+                    "required": ["n"],
+                    "properties": {"n": {"type": "integer"}}
+                }),
+            )
             .unwrap();
-        assert_eq!(idx, meta.length);
-        let reopened_meta = reopened.metadata().unwrap();
-        assert_eq!(reopened_meta.length, reopened.len());
-        assert_eq!(reopened_meta.root, reopened.merkle_root());
+        let good = serde_json::json!({"n": 1});
+        let bad = serde_json::json!({"n": "one"});
+        assert!(validator.validate("test", &good).is_ok());
+        assert!(validator.validate("test", &bad).is_err());
+        assert!(validator.validate("unregistered", &bad).is_ok());
     }
 
     #[test]
-    fn persistent_log_rejects_corrupt_metadata() {
+    fn under_signed_envelopes_rejected_before_full_validation() {
+        let sk = SigningKey::generate(&mut OsRng);
+        let cosigner = SigningKey::generate(&mut OsRng);
+        let mut reg = ChannelRegistry::new();
+        reg.upsert(ledger_spec::ChannelSpec {
+            name: "muscle_io".into(),
+            policy: ledger_spec::ChannelPolicy {
+                min_signers: 2,
+                allowed_signers: vec![
+                    sk.verifying_key().to_bytes(),
+                    cosigner.verifying_key().to_bytes(),
+                ],
+                require_attestations: false,
+                enforce_timestamp_ordering: true,
+                max_attestations_per_envelope: ledger_spec::DEFAULT_MAX_ATTESTATIONS,
+                max_future_skew: None,
+                ordering: ledger_spec::OrderingStrategy::StrictChain,
+                allowed_payload_types: None,
+                require_payload_type: false,
+            },
+        });
+
+        // A stale body hash makes full validation fail first, so getting the
+        // signer-count error shows the full path never ran.
+        let mut tampered = sample_env(None, 1, &sk);
+        tampered.body.payload = serde_json::json!({"n": 99});
+        assert_eq!(
+            ledger_spec::validate_envelope(&tampered, &reg, &ChannelState::default()).err(),
+            Some(ValidationError::BodyHashMismatch)
+        );
+
+        let log = AppendLog::new();
+        let err = log.append(tampered.clone(), &reg).unwrap_err();
+        assert!(matches!(
+            err,
+            AppendError::Validation(ValidationError::InsufficientSignatures(1))
+        ));
+        let dir = temp_dir("precheck-signers");
+        let persistent = PersistentAppendLog::open(&dir).unwrap();
+        let err = persistent.append(tampered, &reg).unwrap_err();
+        assert!(matches!(
+            err,
+            AppendError::Validation(ValidationError::InsufficientSignatures(1))
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut cosigned = sample_env(None, 1, &sk);
+        signing::sign_envelope(&mut cosigned, &cosigner);
+        log.append(cosigned, &reg).unwrap();
+        assert_eq!(log.len(), 1);
+    }
+
+    #[cfg(feature = "observability")]
+    use metrics::{Counter, Gauge, Histogram, Key, KeyName, Metadata, SharedString, Unit};
+    #[cfg(feature = "observability")]
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// Captures counters and histogram samples by `name{label=value,..}`.
+    #[cfg(feature = "observability")]
+    #[derive(Default)]
+    struct CaptureRecorder {
+        counters: Mutex<HashMap<String, Arc<AtomicU64>>>,
+        histograms: Mutex<HashMap<String, Arc<Samples>>>,
+    }
+
+    #[cfg(feature = "observability")]
+    #[derive(Default)]
+    struct Samples(Mutex<Vec<f64>>);
+
+    #[cfg(feature = "observability")]
+    impl metrics::HistogramFn for Samples {
+        fn record(&self, value: f64) {
+            self.0.lock().push(value);
+        }
+    }
+
+    #[cfg(feature = "observability")]
+    impl CaptureRecorder {
+        fn id(key: &Key) -> String {
+            let labels: Vec<_> = key
+                .labels()
+                .map(|l| format!("{}={}", l.key(), l.value()))
+                .collect();
+            format!("{}{{{}}}", key.name(), labels.join(","))
+        }
+
+        fn counter(&self, id: &str) -> u64 {
+            self.counters
+                .lock()
+                .get(id)
+                .map_or(0, |c| c.load(Ordering::Relaxed))
+        }
+
+        fn samples(&self, id: &str) -> usize {
+            self.histograms
+                .lock()
+                .get(id)
+                .map_or(0, |h| h.0.lock().len())
+        }
+    }
+
+    #[cfg(feature = "observability")]
+    impl metrics::Recorder for CaptureRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            let counter = self
+                .counters
+                .lock()
+                .entry(Self::id(key))
+                .or_default()
+                .clone();
+            Counter::from_arc(counter)
+        }
+
+        fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::noop()
+        }
+
+        fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+            let samples = self
+                .histograms
+                .lock()
+                .entry(Self::id(key))
+                .or_default()
+                .clone();
+            Histogram::from_arc(samples)
+        }
+    }
+
+    #[cfg(feature = "observability")]
+    #[test]
+    fn observability_records_append_and_read_metrics() {
         let sk = SigningKey::generate(&mut OsRng);
         let reg = registry(&sk);
-        let dir = temp_dir("meta-mismatch");
-        let log = PersistentAppendLog::open(&dir).unwrap();
-        log.append(sample_env(None, 1, &sk), &reg).unwrap();
-        let meta_path = dir.join("meta.json");
-        let mut meta: PersistentMetadata =
-            serde_json::from_slice(&std::fs::read(&meta_path).unwrap()).unwrap();
-        meta.length += 1;
-        std::fs::write(&meta_path, serde_json::to_vec(&meta).unwrap()).unwrap();
-        let err = PersistentAppendLog::open(&dir).unwrap_err();
-        assert!(err.to_string().contains("metadata mismatch"));
+        let recorder = CaptureRecorder::default();
+        let dir = temp_dir("observability");
+        metrics::with_local_recorder(&recorder, || {
+            let log = AppendLog::new();
+            append_run(&log, &reg, &sk, 1, 3);
+            assert!(log.append(sample_env(None, 4, &sk), &reg).is_err());
+            let persistent = PersistentAppendLog::open(&dir).unwrap();
+            persistent.append(sample_env(None, 1, &sk), &reg).unwrap();
+            assert_eq!(log.read(0, 10).len(), 3);
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let channel = "{channel=muscle_io}";
+        assert_eq!(
+            recorder.counter(&format!("{}{channel}", observe::APPENDS_TOTAL)),
+            4
+        );
+        assert_eq!(
+            recorder.counter(&format!("{}{channel}", observe::VALIDATION_FAILURES_TOTAL)),
+            1
+        );
+        assert_eq!(
+            recorder.samples(&format!("{}{channel}", observe::APPEND_LATENCY_MS)),
+            5
+        );
+        assert!(recorder.samples(&format!("{}{{}}", observe::READ_LATENCY_MS)) >= 2);
     }
 }
@@ -3,19 +3,28 @@
 //! The manager consumes ledger events, validates measurements and attestations,
 //! updates an in-memory registry, hydrates the CAS with sealed blobs, and emits
 //! lifecycle updates or enforcement errors back onto the ledger event stream.
+//!
+//! [`MuscleLifecycle`] tracks a single running instance through
+//! provisioned→attested→running→draining→terminated and records every
+//! accepted transition on the ledger.
 
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use blake3::Hasher;
+use ed25519_dalek::SigningKey;
 use ledger_spec::events::{
-    Audience, ContentRef, DataSensitivity, EventKind, LedgerEvent, LifecycleCommand,
-    LifecycleError, LifecycleStage, LifecycleUpdate, MuscleEvent, MuscleRef,
+    Audience, ContentRef, DataSensitivity, EventId, EventKind, LedgerEvent, LifecycleCommand,
+    LifecycleError as LifecycleErrorEvent, LifecycleStage, LifecycleUpdate, MuscleEvent, MuscleRef,
+    MuscleState,
 };
 use ledger_spec::{
     hash_attestation_statement, Attestation, AttestationKind, Hash, PublicKey, SchemaVersion,
+    Timestamp,
 };
 
-use crate::brainstem::ContentStore;
+use crate::brainstem::{Alert, ContentStore, Ledger};
+use crate::signing;
 
 type MuscleKey = (Hash, u64);
 
@@ -488,7 +497,7 @@ impl MuscleLifecycleManager {
         source: &LedgerEvent,
     ) -> LedgerEvent {
         self.lifecycle_event(
-            MuscleEvent::LifecycleError(LifecycleError {
+            MuscleEvent::LifecycleError(LifecycleErrorEvent {
                 muscle: muscle.clone(),
                 stage,
                 reason,
@@ -504,29 +513,7 @@ impl MuscleLifecycleManager {
         attestations: &[Attestation],
         measurement: Hash,
     ) -> Result<Hash, String> {
-        for att in attestations {
-            let AttestationKind::Build {
-                artifact_hash,
-                builder: _,
-            } = &att.statement
-            else {
-                continue;
-            };
-            if artifact_hash != &measurement {
-                continue;
-            }
-            let expected_hash = hash_attestation_statement(&att.statement);
-            if expected_hash != att.statement_hash {
-                return Err("attestation statement hash mismatch".into());
-            }
-            let pk = ed25519_dalek::VerifyingKey::from_bytes(&att.issuer)
-                .map_err(|_| "invalid attestation issuer key")?;
-            let sig = ed25519_dalek::Signature::from_bytes(&att.signature);
-            pk.verify_strict(&att.statement_hash, &sig)
-                .map_err(|_| "attestation signature invalid")?;
-            return Ok(att.statement_hash);
-        }
-        Err("no matching build attestation for measurement".into())
+        find_valid_attestation(attestations, measurement)
     }
 
     fn key(muscle: &MuscleRef) -> MuscleKey {
@@ -534,6 +521,176 @@ impl MuscleLifecycleManager {
     }
 }
 
+/// Errors returned by [`MuscleLifecycle::transition`].
+#[derive(Debug, thiserror::Error)]
+pub enum LifecycleError {
+    /// The state machine has no edge between the two states.
+    #[error("illegal lifecycle transition {from:?} -> {to:?}")]
+    IllegalTransition {
+        /// Current state.
+        from: MuscleState,
+        /// Requested state.
+        to: MuscleState,
+    },
+    /// Leaving `Provisioned` requires an accepted attestation.
+    #[error("attestation required before leaving Provisioned")]
+    MissingAttestation,
+    /// The offered attestation does not vouch for the measurement.
+    #[error("attestation rejected: {0}")]
+    InvalidAttestation(String),
+    /// Recording the transition on the ledger failed.
+    #[error("ledger error: {0:?}")]
+    Ledger(Alert),
+    /// Event encoding failed.
+    #[error("serialization error: {0}")]
+    Encode(#[from] serde_json::Error),
+}
+
+/// Enforced runtime state machine for one muscle instance.
+///
+/// Legal transitions are `Provisioned → Attested → Running → Draining →
+/// Terminated`, plus `Terminated` from any live state. Each accepted
+/// transition is appended to the ledger before the new state takes effect,
+/// chained to the previous transition through the event parent.
+pub struct MuscleLifecycle {
+    muscle: MuscleRef,
+    measurement: Hash,
+    state: MuscleState,
+    attestation: Option<Hash>,
+    last_event: Option<EventId>,
+    ledger: Ledger,
+    signer: Arc<SigningKey>,
+    channel: String,
+    schema_version: SchemaVersion,
+}
+
+impl MuscleLifecycle {
+    /// Track a freshly provisioned instance, recording transitions on `channel`.
+    pub fn new(
+        muscle: MuscleRef,
+        measurement: Hash,
+        ledger: Ledger,
+        signer: SigningKey,
+        channel: impl Into<String>,
+        schema_version: SchemaVersion,
+    ) -> Self {
+        Self {
+            muscle,
+            measurement,
+            state: MuscleState::Provisioned,
+            attestation: None,
+            last_event: None,
+            ledger,
+            signer: Arc::new(signer),
+            channel: channel.into(),
+            schema_version,
+        }
+    }
+
+    /// Current state.
+    pub fn state(&self) -> MuscleState {
+        self.state
+    }
+
+    /// Muscle this instance runs.
+    pub fn muscle(&self) -> &MuscleRef {
+        &self.muscle
+    }
+
+    /// Accept a build attestation for the instance's measurement.
+    ///
+    /// Must be called before transitioning to [`MuscleState::Attested`].
+    pub fn attest(&mut self, attestation: &Attestation) -> Result<(), LifecycleError> {
+        let hash = find_valid_attestation(std::slice::from_ref(attestation), self.measurement)
+            .map_err(LifecycleError::InvalidAttestation)?;
+        self.attestation = Some(hash);
+        Ok(())
+    }
+
+    /// Move to `to`, recording the transition on the ledger.
+    pub fn transition(&mut self, to: MuscleState) -> Result<(), LifecycleError> {
+        let from = self.state;
+        if !transition_allowed(from, to) {
+            return Err(LifecycleError::IllegalTransition { from, to });
+        }
+        let attestation = if to == MuscleState::Attested {
+            Some(self.attestation.ok_or(LifecycleError::MissingAttestation)?)
+        } else {
+            None
+        };
+        let event = LedgerEvent::new(
+            EventKind::Muscle(MuscleEvent::LifecycleUpdate(
+                LifecycleUpdate::StateChanged {
+                    muscle: self.muscle.clone(),
+                    from,
+                    to,
+                    attestation,
+                },
+            )),
+            self.signer.verifying_key().to_bytes(),
+            Audience::Broadcast,
+            now_millis(),
+            DataSensitivity::Internal,
+            Vec::new(),
+            self.last_event,
+        )?;
+        let id = event.id;
+        let mut env = event.into_envelope(self.channel.clone(), self.schema_version)?;
+        env.header.prev = self.ledger.tail_hash();
+        signing::sign_envelope(&mut env, &self.signer);
+        self.ledger.append(env).map_err(LifecycleError::Ledger)?;
+        self.state = to;
+        self.last_event = Some(id);
+        Ok(())
+    }
+}
+
+fn transition_allowed(from: MuscleState, to: MuscleState) -> bool {
+    use MuscleState::*;
+    matches!(
+        (from, to),
+        (Provisioned, Attested)
+            | (Attested, Running)
+            | (Running, Draining)
+            | (Draining, Terminated)
+            | (Provisioned | Attested | Running, Terminated)
+    )
+}
+
+fn now_millis() -> Timestamp {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn find_valid_attestation(attestations: &[Attestation], measurement: Hash) -> Result<Hash, String> {
+    for att in attestations {
+        let AttestationKind::Build {
+            artifact_hash,
+            builder: _,
+        } = &att.statement
+        else {
+            continue;
+        };
+        if artifact_hash != &measurement {
+            continue;
+        }
+        let expected_hash = hash_attestation_statement(&att.statement);
+        if expected_hash != att.statement_hash {
+            return Err("attestation statement hash mismatch".into());
+        }
+        let pk = ed25519_dalek::VerifyingKey::from_bytes(&att.issuer)
+            .map_err(|_| "invalid attestation issuer key")?;
+        let sig = ed25519_dalek::Signature::from_bytes(&att.signature);
+        pk.verify_strict(&att.statement_hash, &sig)
+            .map_err(|_| "attestation signature invalid")?;
+        return Ok(att.statement_hash);
+    }
+    Err("no matching build attestation for measurement".into())
+}
+
 fn fmt_hash(hash: &Hash) -> String {
     blake3::Hash::from_bytes(*hash).to_hex().to_string()
 }
@@ -739,4 +896,129 @@ mod tests {
             other => panic!("unexpected event: {other:?}"),
         }
     }
+
+    fn running_lifecycle(sk: &SigningKey) -> (MuscleLifecycle, Ledger) {
+        let (measurement, _, _) = measurement_and_blob();
+        let mut registry = ledger_spec::ChannelRegistry::new();
+        registry.upsert(ledger_spec::ChannelSpec {
+            name: "muscle.lifecycle".into(),
+            policy: ledger_spec::ChannelPolicy {
+                min_signers: 1,
+                allowed_signers: vec![sk.verifying_key().to_bytes()],
+                require_attestations: false,
+                enforce_timestamp_ordering: true,
+                max_attestations_per_envelope: ledger_spec::DEFAULT_MAX_ATTESTATIONS,
+                max_future_skew: None,
+                ordering: ledger_spec::OrderingStrategy::StrictChain,
+                allowed_payload_types: None,
+                require_payload_type: false,
+            },
+        });
+        let ledger = Ledger::new(registry);
+        let lifecycle = MuscleLifecycle::new(
+            sample_muscle(),
+            measurement,
+            ledger.clone(),
+            sk.clone(),
+            "muscle.lifecycle",
+            1,
+        );
+        (lifecycle, ledger)
+    }
+
+    fn recorded_transitions(ledger: &Ledger) -> Vec<(MuscleState, MuscleState)> {
+        let Ok(slice) = ledger.query(crate::brainstem::SliceQuery {
+            from: 0,
+            limit: 64,
+            include_payloads: false,
+        }) else {
+            return Vec::new();
+        };
+        slice
+            .envelopes
+            .iter()
+            .map(|env| match LedgerEvent::from_envelope(env).unwrap().kind {
+                EventKind::Muscle(MuscleEvent::LifecycleUpdate(
+                    LifecycleUpdate::StateChanged { from, to, .. },
+                )) => (from, to),
+                other => panic!("unexpected event: {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn muscle_lifecycle_accepts_every_legal_transition() {
+        use MuscleState::*;
+        let sk = signing_key();
+        let (measurement, _, _) = measurement_and_blob();
+        let paths: [&[MuscleState]; 4] = [
+            &[Attested, Running, Draining, Terminated],
+            &[Terminated],
+            &[Attested, Terminated],
+            &[Attested, Running, Terminated],
+        ];
+        for path in paths {
+            let (mut lifecycle, ledger) = running_lifecycle(&sk);
+            lifecycle
+                .attest(&build_attestation(measurement, &sk))
+                .unwrap();
+            let mut expected = Vec::new();
+            for &to in path {
+                expected.push((lifecycle.state(), to));
+                lifecycle.transition(to).unwrap();
+                assert_eq!(lifecycle.state(), to);
+            }
+            assert_eq!(recorded_transitions(&ledger), expected);
+        }
+    }
+
+    #[test]
+    fn muscle_lifecycle_rejects_illegal_transitions() {
+        use MuscleState::*;
+        let sk = signing_key();
+        let (measurement, _, _) = measurement_and_blob();
+        let (mut lifecycle, ledger) = running_lifecycle(&sk);
+
+        assert!(matches!(
+            lifecycle.transition(Attested),
+            Err(LifecycleError::MissingAttestation)
+        ));
+        assert!(matches!(
+            lifecycle.attest(&build_attestation([0x55; 32], &sk)),
+            Err(LifecycleError::InvalidAttestation(_))
+        ));
+        assert!(matches!(
+            lifecycle.transition(Running),
+            Err(LifecycleError::IllegalTransition {
+                from: Provisioned,
+                to: Running
+            })
+        ));
+        lifecycle
+            .attest(&build_attestation(measurement, &sk))
+            .unwrap();
+        lifecycle.transition(Attested).unwrap();
+        lifecycle.transition(Running).unwrap();
+        for to in [Provisioned, Attested, Running] {
+            assert!(matches!(
+                lifecycle.transition(to),
+                Err(LifecycleError::IllegalTransition { from: Running, .. })
+            ));
+        }
+        lifecycle.transition(Draining).unwrap();
+        assert!(lifecycle.transition(Running).is_err());
+        lifecycle.transition(Terminated).unwrap();
+        for to in [Provisioned, Attested, Running, Draining, Terminated] {
+            assert!(matches!(
+                lifecycle.transition(to),
+                Err(LifecycleError::IllegalTransition {
+                    from: Terminated,
+                    ..
+                })
+            ));
+        }
+        assert_eq!(lifecycle.state(), Terminated);
+        // Rejected transitions leave no trace on the ledger.
+        assert_eq!(recorded_transitions(&ledger).len(), 4);
+    }
 }
@@ -0,0 +1,41 @@
+//! Aggregated append/read metrics for dashboards (`observability` feature).
+//!
+//! Recorded through the [`metrics`] facade next to the tracing spans that
+//! already time each operation. Append metrics carry a `channel` label; reads
+//! span channels, so read latency is unlabelled.
+
+use std::time::Duration;
+
+use metrics::{counter, histogram};
+
+use crate::AppendError;
+
+/// Histogram of append latency in milliseconds, labelled by channel.
+pub const APPEND_LATENCY_MS: &str = "ledger_append_latency_ms";
+/// Histogram of read latency in milliseconds.
+pub const READ_LATENCY_MS: &str = "ledger_read_latency_ms";
+/// Counter of committed appends, labelled by channel.
+pub const APPENDS_TOTAL: &str = "ledger_appends_total";
+/// Counter of appends rejected by envelope or payload validation, labelled by
+/// channel.
+pub const VALIDATION_FAILURES_TOTAL: &str = "ledger_validation_failures_total";
+
+pub(crate) fn record_append(channel: &str, latency: Duration, result: &Result<usize, AppendError>) {
+    let channel = channel.to_string();
+    histogram!(APPEND_LATENCY_MS, "channel" => channel.clone()).record(millis(latency));
+    match result {
+        Ok(_) => counter!(APPENDS_TOTAL, "channel" => channel).increment(1),
+        Err(AppendError::Validation(_) | AppendError::Schema(_)) => {
+            counter!(VALIDATION_FAILURES_TOTAL, "channel" => channel).increment(1)
+        }
+        Err(_) => {}
+    }
+}
+
+pub(crate) fn record_read(latency: Duration) {
+    histogram!(READ_LATENCY_MS).record(millis(latency));
+}
+
+fn millis(latency: Duration) -> f64 {
+    latency.as_secs_f64() * 1000.0
+}
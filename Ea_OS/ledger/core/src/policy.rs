@@ -4,8 +4,11 @@
 //! them against a set of declarative [`ledger_spec::policy::PolicyDefinition`]
 //! bundles, and emits signed policy decisions plus optional alerts back onto
 //! the ledger. Built-in policies cover export control and command approval.
+//!
+//! [`AppendPolicy`] gates raw envelopes on the append path; denials carry the
+//! rule and reason so they can be recorded on the ledger for audit.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::Result;
 use ed25519_dalek::SigningKey;
@@ -17,7 +20,7 @@ use ledger_spec::policy::{
     PolicyAlert, PolicyAlertSeverity, PolicyBinding, PolicyDecision, PolicyDefinition,
     PolicyEffect, PolicyId, PolicyRule, PolicyScope,
 };
-use ledger_spec::{Envelope, PublicKey, SchemaVersion};
+use ledger_spec::{Envelope, Hash, PublicKey, SchemaVersion};
 
 use crate::signing;
 
@@ -191,6 +194,124 @@ impl PolicyEngine {
     }
 }
 
+/// Verdict of an [`AppendPolicy`] on one envelope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppendDecision {
+    /// Whether the envelope may be appended.
+    pub allowed: bool,
+    /// Rule that produced the verdict, if any rule matched.
+    pub rule_id: Option<String>,
+    /// Human-readable explanation.
+    pub reason: String,
+}
+
+impl AppendDecision {
+    /// Allow with no matching rule.
+    pub fn allow() -> Self {
+        Self {
+            allowed: true,
+            rule_id: None,
+            reason: "no rule matched".into(),
+        }
+    }
+
+    /// Deny under `rule_id` for `reason`.
+    pub fn deny(rule_id: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            allowed: false,
+            rule_id: Some(rule_id.into()),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Log state visible to an [`AppendPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppendContext {
+    /// Entries committed before this append.
+    pub log_len: usize,
+    /// Hash of the current tail, if any.
+    pub tail: Option<Hash>,
+}
+
+/// Decides whether an envelope may be appended, before chain validation.
+pub trait AppendPolicy: Send + Sync + std::fmt::Debug {
+    /// Policy identity recorded on audit decisions.
+    fn id(&self) -> PolicyId {
+        PolicyId {
+            name: "append-policy".into(),
+            version: 1,
+        }
+    }
+
+    /// Evaluate `env` against the policy.
+    fn evaluate(&self, env: &Envelope, ctx: &AppendContext) -> AppendDecision;
+}
+
+/// Default [`AppendPolicy`] enforcing per-channel rules.
+///
+/// Channels with no configured rules accept everything.
+#[derive(Debug, Clone, Default)]
+pub struct ChannelRulePolicy {
+    attested: HashSet<String>,
+    payload_types: HashMap<String, HashSet<String>>,
+}
+
+impl ChannelRulePolicy {
+    /// Create a policy with no rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require at least one attestation on envelopes for `channel`.
+    pub fn require_attestations(mut self, channel: impl Into<String>) -> Self {
+        self.attested.insert(channel.into());
+        self
+    }
+
+    /// Only accept the listed payload types on `channel`.
+    pub fn allow_payload_types<I, T>(mut self, channel: impl Into<String>, types: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.payload_types
+            .entry(channel.into())
+            .or_default()
+            .extend(types.into_iter().map(Into::into));
+        self
+    }
+}
+
+impl AppendPolicy for ChannelRulePolicy {
+    fn id(&self) -> PolicyId {
+        PolicyId {
+            name: "channel-rules".into(),
+            version: 1,
+        }
+    }
+
+    fn evaluate(&self, env: &Envelope, _ctx: &AppendContext) -> AppendDecision {
+        let channel = &env.header.channel;
+        if self.attested.contains(channel) && env.attestations.is_empty() {
+            return AppendDecision::deny(
+                "require-attestations",
+                format!("channel {channel} requires an attestation"),
+            );
+        }
+        if let Some(allowed) = self.payload_types.get(channel) {
+            let payload_type = env.body.payload_type.as_deref().unwrap_or("<untyped>");
+            if !allowed.contains(payload_type) {
+                return AppendDecision::deny(
+                    "payload-type-allowlist",
+                    format!("payload type {payload_type} not allowed on channel {channel}"),
+                );
+            }
+        }
+        AppendDecision::allow()
+    }
+}
+
 fn policy_key(id: &PolicyId) -> String {
     format!("{}@{}", id.name, id.version)
 }
@@ -616,4 +737,56 @@ mod tests {
             &Audience::Domain("oversight".into())
         );
     }
+
+    fn typed_envelope(channel: &str, payload_type: &str) -> Envelope {
+        let body = ledger_spec::EnvelopeBody {
+            payload: serde_json::json!({}),
+            payload_type: Some(payload_type.into()),
+        };
+        Envelope {
+            header: ledger_spec::EnvelopeHeader {
+                channel: channel.into(),
+                version: 1,
+                prev: None,
+                body_hash: ledger_spec::hash_body(&body),
+                timestamp: 1,
+            },
+            body,
+            signatures: Vec::new(),
+            attestations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn channel_rules_allow_and_deny_with_reason() {
+        let policy = ChannelRulePolicy::new()
+            .require_attestations("secure")
+            .allow_payload_types("telemetry", ["metrics"]);
+        let ctx = AppendContext {
+            log_len: 0,
+            tail: None,
+        };
+
+        let allowed = policy.evaluate(&typed_envelope("telemetry", "metrics"), &ctx);
+        assert_eq!(allowed, AppendDecision::allow());
+        assert!(
+            policy
+                .evaluate(&typed_envelope("open", "anything"), &ctx)
+                .allowed
+        );
+
+        let denied = policy.evaluate(&typed_envelope("secure", "metrics"), &ctx);
+        assert!(!denied.allowed);
+        assert_eq!(denied.rule_id.as_deref(), Some("require-attestations"));
+        assert_eq!(denied.reason, "channel secure requires an attestation");
+
+        let denied = policy.evaluate(&typed_envelope("telemetry", "logs"), &ctx);
+        assert_eq!(
+            denied,
+            AppendDecision::deny(
+                "payload-type-allowlist",
+                "payload type logs not allowed on channel telemetry"
+            )
+        );
+    }
 }
@@ -6,7 +6,7 @@ use ledger_core::{AppendLog, AppendLogStorage, CheckpointWriter};
 use ledger_spec::{ChannelRegistry, ChannelSpec};
 use ledger_transport::{
     bind_transport, connect_transport, AdapterCapability, AdapterKind, CapabilityAdvertisement,
-    Transport, TransportConfig, TransportDomain,
+    ConnectRetry, QuicServerPin, Transport, TransportConfig, TransportDomain,
 };
 use prometheus::Encoder;
 use serde::Serialize;
@@ -200,6 +200,24 @@ struct TransportCli {
         help = "Authority/endpoint for QUIC transport (e.g. https://ledgerd.example.com)"
     )]
     quic_endpoint: Option<String>,
+    /// SHA-256 of the QUIC server certificate's SubjectPublicKeyInfo.
+    #[arg(
+        long,
+        env = "LEDGER_QUIC_SPKI_SHA256",
+        value_name = "HEX",
+        value_parser = parse_spki_hash,
+        conflicts_with = "quic_cert",
+        help = "Pin the QUIC server by the hex SHA-256 of its certificate's public key"
+    )]
+    quic_spki_sha256: Option<[u8; 32]>,
+    /// DER certificate the QUIC server must present.
+    #[arg(
+        long,
+        env = "LEDGER_QUIC_CERT",
+        value_name = "FILE",
+        help = "Pin the QUIC server to this DER-encoded certificate"
+    )]
+    quic_cert: Option<String>,
 }
 
 /// Supported transports exposed via CLI.
@@ -453,6 +471,18 @@ async fn load_registry(path: &str) -> anyhow::Result<ChannelRegistry> {
     Ok(registry)
 }
 
+fn parse_spki_hash(hex: &str) -> anyhow::Result<[u8; 32]> {
+    let hex = hex.trim();
+    if hex.len() != 64 || !hex.is_ascii() {
+        anyhow::bail!("expected 64 hex characters");
+    }
+    let mut hash = [0u8; 32];
+    for (byte, pair) in hash.iter_mut().zip(hex.as_bytes().chunks_exact(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair)?, 16)?;
+    }
+    Ok(hash)
+}
+
 fn build_transport_config(cli: &TransportCli) -> anyhow::Result<TransportConfig> {
     match cli.transport {
         TransportKind::Loopback => Ok(TransportConfig::loopback(TransportDomain::Ledger)),
@@ -472,6 +502,7 @@ fn build_transport_config(cli: &TransportCli) -> anyhow::Result<TransportConfig>
             Ok(TransportConfig {
                 advertisement,
                 selected,
+                retry: ConnectRetry::default(),
             })
         }
         TransportKind::Quic => {
@@ -479,10 +510,18 @@ fn build_transport_config(cli: &TransportCli) -> anyhow::Result<TransportConfig>
                 .quic_endpoint
                 .clone()
                 .ok_or_else(|| anyhow::anyhow!("--quic-endpoint is required for quic transport"))?;
+            let pin = match (cli.quic_spki_sha256, &cli.quic_cert) {
+                (Some(hash), _) => QuicServerPin::SpkiSha256(hash),
+                (None, Some(path)) => QuicServerPin::CertDer(std::fs::read(path)?),
+                (None, None) => anyhow::bail!(
+                    "--quic-spki-sha256 or --quic-cert is required for quic transport"
+                ),
+            };
             let selected = AdapterCapability {
                 adapter: AdapterKind::QuicGrpc {
                     endpoint: endpoint.clone(),
                     alpn: None,
+                    pin: Some(pin),
                 },
                 features: vec![],
                 attestation: None,
@@ -496,6 +535,7 @@ fn build_transport_config(cli: &TransportCli) -> anyhow::Result<TransportConfig>
             Ok(TransportConfig {
                 advertisement,
                 selected,
+                retry: ConnectRetry::default(),
             })
         }
     }
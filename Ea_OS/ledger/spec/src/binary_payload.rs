@@ -0,0 +1,147 @@
+//! Serde adapter letting JSON payloads travel through non-self-describing
+//! formats such as bincode.
+//!
+//! `serde_json::Value` can only be deserialized by formats that support
+//! `deserialize_any`. Human-readable formats keep the plain JSON encoding, so
+//! hashing and JSON wire formats are unchanged; compact binary formats encode
+//! the value as a tagged tree instead. Decoding that tree stops at
+//! [`MAX_DEPTH`] levels, as `serde_json` does for text, so a hostile frame
+//! cannot drive the decoder into unbounded recursion.
+
+use serde::de::{Deserializer, Error};
+use serde::ser::{Serialize, Serializer};
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+use std::cell::Cell;
+
+const NAME: &str = "Value";
+
+/// Deepest nesting the binary decoder follows, matching `serde_json`'s own
+/// recursion limit.
+const MAX_DEPTH: usize = 128;
+
+thread_local! {
+    /// Nesting of the tree currently being decoded on this thread.
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+pub(crate) fn serialize<S: Serializer>(value: &Value, serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        value.serialize(serializer)
+    } else {
+        Tagged(value).serialize(serializer)
+    }
+}
+
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+    if deserializer.is_human_readable() {
+        Value::deserialize(deserializer)
+    } else {
+        Nested::deserialize(deserializer).map(|nested| Value::from(nested.0))
+    }
+}
+
+/// Borrowed encoder matching the variant layout of [`BinaryValue`].
+struct Tagged<'a>(&'a Value);
+
+impl Serialize for Tagged<'_> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Value::Null => s.serialize_unit_variant(NAME, 0, "Null"),
+            Value::Bool(b) => s.serialize_newtype_variant(NAME, 1, "Bool", b),
+            Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+                (Some(u), _) => s.serialize_newtype_variant(NAME, 2, "U64", &u),
+                (None, Some(i)) => s.serialize_newtype_variant(NAME, 3, "I64", &i),
+                (None, None) => {
+                    s.serialize_newtype_variant(NAME, 4, "F64", &n.as_f64().unwrap_or_default())
+                }
+            },
+            Value::String(text) => s.serialize_newtype_variant(NAME, 5, "String", text),
+            Value::Array(items) => s.serialize_newtype_variant(NAME, 6, "Array", &Items(items)),
+            Value::Object(map) => s.serialize_newtype_variant(NAME, 7, "Object", &Entries(map)),
+        }
+    }
+}
+
+struct Items<'a>(&'a [Value]);
+
+impl Serialize for Items<'_> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(self.0.iter().map(Tagged))
+    }
+}
+
+struct Entries<'a>(&'a Map<String, Value>);
+
+impl Serialize for Entries<'_> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(self.0.iter().map(|(key, value)| (key, Tagged(value))))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Value")]
+enum BinaryValue {
+    Null,
+    Bool(bool),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    String(String),
+    Array(Vec<Nested>),
+    Object(Vec<(String, Nested)>),
+}
+
+/// One level of [`BinaryValue`], counted against [`MAX_DEPTH`].
+struct Nested(BinaryValue);
+
+impl<'de> Deserialize<'de> for Nested {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let _level = Level::enter().ok_or_else(|| {
+            D::Error::custom(format_args!("payload nests deeper than {MAX_DEPTH} levels"))
+        })?;
+        BinaryValue::deserialize(deserializer).map(Nested)
+    }
+}
+
+/// Holds one level of [`DEPTH`] until dropped.
+struct Level;
+
+impl Level {
+    fn enter() -> Option<Self> {
+        let depth = DEPTH.with(Cell::get);
+        if depth >= MAX_DEPTH {
+            return None;
+        }
+        DEPTH.with(|cell| cell.set(depth + 1));
+        Some(Level)
+    }
+}
+
+impl Drop for Level {
+    fn drop(&mut self) {
+        DEPTH.with(|cell| cell.set(cell.get() - 1));
+    }
+}
+
+impl From<BinaryValue> for Value {
+    fn from(value: BinaryValue) -> Self {
+        match value {
+            BinaryValue::Null => Value::Null,
+            BinaryValue::Bool(b) => Value::Bool(b),
+            BinaryValue::U64(u) => Value::Number(u.into()),
+            BinaryValue::I64(i) => Value::Number(i.into()),
+            BinaryValue::F64(f) => Number::from_f64(f).map_or(Value::Null, Value::Number),
+            BinaryValue::String(text) => Value::String(text),
+            BinaryValue::Array(items) => {
+                Value::Array(items.into_iter().map(|item| Value::from(item.0)).collect())
+            }
+            BinaryValue::Object(entries) => Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, Value::from(value.0)))
+                    .collect(),
+            ),
+        }
+    }
+}
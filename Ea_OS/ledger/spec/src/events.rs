@@ -11,7 +11,8 @@
 
 use crate::{
     hash_body, policy::PolicyAlert, policy::PolicyDecision, policy::PolicyDefinition, Attestation,
-    Channel, Envelope, EnvelopeBody, EnvelopeHeader, Hash, PublicKey, SchemaVersion, Timestamp,
+    AttestationChain, AttestationStage, Channel, Envelope, EnvelopeBody, EnvelopeHeader, Hash,
+    PublicKey, SchemaVersion, Timestamp,
 };
 use blake3::Hasher;
 use serde::{de::Error as DeError, Deserialize, Serialize};
@@ -92,6 +93,21 @@ pub enum LifecycleStage {
     Retired,
 }
 
+/// Runtime state of a provisioned muscle instance.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum MuscleState {
+    /// Instance exists but has not proven its measurement.
+    Provisioned,
+    /// A build attestation for the measurement was accepted.
+    Attested,
+    /// Instance is serving invocations.
+    Running,
+    /// Instance finishes in-flight work and accepts nothing new.
+    Draining,
+    /// Instance is gone and must not run again.
+    Terminated,
+}
+
 /// Lifecycle commands emitted on the ledger to drive state transitions.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum LifecycleCommand {
@@ -177,6 +193,18 @@ pub enum LifecycleUpdate {
         /// Reason for retirement.
         reason: String,
     },
+    /// Runtime state transition accepted.
+    StateChanged {
+        /// Target muscle reference.
+        muscle: MuscleRef,
+        /// State before the transition.
+        from: MuscleState,
+        /// State after the transition.
+        to: MuscleState,
+        /// Attestation statement hash that justified the transition, if any.
+        #[serde(default)]
+        attestation: Option<Hash>,
+    },
 }
 
 /// Lifecycle error emitted when a command cannot be honored.
@@ -252,6 +280,9 @@ pub enum CapabilityAdapterKind {
         /// Optional ALPN.
         #[serde(default)]
         alpn: Option<String>,
+        /// Server certificate pin clients authenticate against.
+        #[serde(default)]
+        pin: Option<CapabilityCertPin>,
     },
     /// Mailbox/ring buffer for enclave/chip.
     Mailbox {
@@ -269,10 +300,28 @@ pub enum CapabilityAdapterKind {
         /// Filesystem path to the socket.
         path: String,
     },
+    /// Shared-memory ring between co-located processes.
+    SharedMemory {
+        /// Backing file path.
+        path: String,
+        /// Max bytes per slot.
+        slot_bytes: usize,
+        /// Slot count.
+        slots: usize,
+    },
     /// Enclave proxy.
     EnclaveProxy,
 }
 
+/// Server certificate pin for a QUIC adapter.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CapabilityCertPin {
+    /// SHA-256 of the certificate's SubjectPublicKeyInfo.
+    SpkiSha256(Hash),
+    /// Exact DER certificate.
+    CertDer(Vec<u8>),
+}
+
 /// Attestation handshake material for adapter negotiation.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CapabilityAttestation {
@@ -285,6 +334,12 @@ pub struct CapabilityAttestation {
     /// Optional evidence bundle presented during negotiation.
     #[serde(default)]
     pub presented: Option<Attestation>,
+    /// Attestation stages the peer must present as an ordered chain.
+    #[serde(default)]
+    pub required_chain: Vec<AttestationStage>,
+    /// Optional attestation chain presented during negotiation.
+    #[serde(default)]
+    pub presented_chain: Option<AttestationChain>,
 }
 
 /// Capability advertisement payload.
@@ -8,8 +8,10 @@ use blake3::Hasher;
 use serde::{Deserialize, Serialize};
 use serde_big_array::BigArray;
 use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
+mod binary_payload;
 /// Event and workflow schema layered on top of envelopes.
 pub mod events;
 /// Declarative policy model shared across ledger components.
@@ -33,10 +35,17 @@ pub type Timestamp = u64;
 /// Version marker for envelope schema evolution.
 pub type SchemaVersion = u16;
 
+/// Version of the envelope hashing scheme.
+pub type HashVersion = u16;
+
+/// Hashing scheme implemented by [`hash_envelope_v1`].
+pub const HASH_VERSION_V1: HashVersion = 1;
+
 /// Envelope body structure.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct EnvelopeBody {
     /// Free-form JSON payload.
+    #[serde(with = "binary_payload")]
     pub payload: serde_json::Value,
     /// Optional semantic type tag for routing and policy checks.
     pub payload_type: Option<String>,
@@ -101,6 +110,39 @@ pub enum AttestationKind {
     },
 }
 
+/// Stage an attestation statement occupies within an attestation chain.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum AttestationStage {
+    /// Build-time provenance.
+    Build,
+    /// Runtime environment evidence.
+    Runtime,
+    /// Policy bundle distribution.
+    Policy,
+    /// Application-specific statement.
+    Custom,
+}
+
+impl AttestationKind {
+    /// Chain stage this statement satisfies.
+    pub fn stage(&self) -> AttestationStage {
+        match self {
+            AttestationKind::Build { .. } => AttestationStage::Build,
+            AttestationKind::Runtime { .. } => AttestationStage::Runtime,
+            AttestationKind::Policy { .. } => AttestationStage::Policy,
+            AttestationKind::Custom { .. } => AttestationStage::Custom,
+        }
+    }
+
+    /// Time after which the statement no longer holds, for time-bounded kinds.
+    pub fn expires_at(&self) -> Option<Timestamp> {
+        match self {
+            AttestationKind::Policy { expires_at, .. } => Some(*expires_at),
+            _ => None,
+        }
+    }
+}
+
 /// Attestation attached to an envelope.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Attestation {
@@ -115,6 +157,43 @@ pub struct Attestation {
     pub signature: SignatureBytes,
 }
 
+/// Ordered attestation chain (e.g., build → runtime → policy).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AttestationChain {
+    /// Attestations in presentation order.
+    pub links: Vec<Attestation>,
+}
+
+impl AttestationChain {
+    /// Build a chain from ordered attestations.
+    pub fn new(links: Vec<Attestation>) -> Self {
+        Self { links }
+    }
+
+    /// Stages covered by the chain, in order.
+    pub fn stages(&self) -> Vec<AttestationStage> {
+        self.links.iter().map(|att| att.statement.stage()).collect()
+    }
+
+    /// Check every statement hash and that `required` stages appear in order.
+    ///
+    /// Links for stages not listed in `required` may be interleaved freely.
+    pub fn verify_chain(&self, required: &[AttestationStage]) -> Result<(), ValidationError> {
+        for att in &self.links {
+            if att.statement_hash != hash_attestation_statement(&att.statement) {
+                return Err(ValidationError::AttestationInvalid);
+            }
+        }
+        let mut stages = self.links.iter().map(|att| att.statement.stage());
+        for stage in required {
+            if !stages.any(|s| s == *stage) {
+                return Err(ValidationError::AttestationChainIncomplete(*stage));
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Envelope object.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Envelope {
@@ -147,8 +226,37 @@ pub fn hash_header(header: &EnvelopeHeader) -> Hash {
     *hasher.finalize().as_bytes()
 }
 
-/// Compute the canonical hash of an envelope (header hash + body hash).
+/// Hashing scheme implied by an envelope schema version.
+///
+/// Stored envelopes record their schema version, so a new hashing scheme is
+/// introduced by mapping a new schema version to it here; envelopes written
+/// under older versions keep hashing (and verifying) exactly as before.
+pub fn hash_version(_schema: SchemaVersion) -> HashVersion {
+    HASH_VERSION_V1
+}
+
+/// Compute the canonical hash of an envelope using the scheme its header's
+/// schema version implies.
 pub fn envelope_hash(env: &Envelope) -> Hash {
+    envelope_hash_versioned(env, hash_version(env.header.version))
+        .expect("hash_version maps only to implemented hash versions")
+}
+
+/// Compute an envelope hash under an explicit scheme, or `None` if `version`
+/// is not implemented.
+pub fn envelope_hash_versioned(env: &Envelope, version: HashVersion) -> Option<Hash> {
+    match version {
+        HASH_VERSION_V1 => Some(hash_envelope_v1(env)),
+        _ => None,
+    }
+}
+
+/// Version 1 envelope hash (header hash + body hash + prev link).
+///
+/// Pinned by golden vectors: any change to this function, [`hash_header`], or
+/// the header's JSON encoding alters every stored chain and must instead ship
+/// as a new hash version.
+pub fn hash_envelope_v1(env: &Envelope) -> Hash {
     let mut hasher = Hasher::new();
     hasher.update(b"ea-ledger:envelope");
     hasher.update(&hash_header(&env.header));
@@ -169,6 +277,13 @@ pub fn hash_attestation_statement(statement: &AttestationKind) -> Hash {
     *hasher.finalize().as_bytes()
 }
 
+/// Default cap on attestations carried by one envelope.
+pub const DEFAULT_MAX_ATTESTATIONS: usize = 16;
+
+fn default_max_attestations() -> usize {
+    DEFAULT_MAX_ATTESTATIONS
+}
+
 /// Channel policy definition.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ChannelPolicy {
@@ -180,6 +295,34 @@ pub struct ChannelPolicy {
     pub require_attestations: bool,
     /// Enforce monotonically increasing timestamps.
     pub enforce_timestamp_ordering: bool,
+    /// Most attestations one envelope may carry; checked before any is verified.
+    #[serde(default = "default_max_attestations")]
+    pub max_attestations_per_envelope: usize,
+    /// How far past the validator's clock a timestamp may be. `None` accepts
+    /// any future timestamp.
+    #[serde(default)]
+    pub max_future_skew: Option<Duration>,
+    /// How an envelope's `prev` must relate to the log.
+    #[serde(default)]
+    pub ordering: OrderingStrategy,
+    /// Payload types the channel accepts. `None` accepts any.
+    #[serde(default)]
+    pub allowed_payload_types: Option<Vec<String>>,
+    /// Reject envelopes that carry no `payload_type`.
+    #[serde(default)]
+    pub require_payload_type: bool,
+}
+
+/// How envelopes on a channel chain onto the log.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum OrderingStrategy {
+    /// `prev` must be the current tip, serializing every writer.
+    #[default]
+    StrictChain,
+    /// `prev` may name any entry already in the log, so concurrent writers
+    /// can branch from the same parent and later merge. Timestamps are
+    /// ordered against the named parent rather than the tip.
+    Dag,
 }
 
 impl Default for ChannelPolicy {
@@ -189,6 +332,11 @@ impl Default for ChannelPolicy {
             allowed_signers: Vec::new(),
             require_attestations: false,
             enforce_timestamp_ordering: true,
+            max_attestations_per_envelope: DEFAULT_MAX_ATTESTATIONS,
+            max_future_skew: None,
+            ordering: OrderingStrategy::StrictChain,
+            allowed_payload_types: None,
+            require_payload_type: false,
         }
     }
 }
@@ -243,15 +391,35 @@ pub enum ValidationError {
     /// Timestamp regressed.
     #[error("timestamp regression")]
     TimestampRegression,
+    /// Timestamp is further ahead of the current time than the channel allows.
+    #[error("timestamp too far in the future")]
+    TimestampTooFarFuture,
     /// Body hash mismatch.
     #[error("body hash mismatch")]
     BodyHashMismatch,
     /// Invalid attestation signature.
     #[error("attestation verification failed")]
     AttestationInvalid,
+    /// Attestation chain is missing a required stage (or has it out of order).
+    #[error("attestation chain missing {0:?} link")]
+    AttestationChainIncomplete(AttestationStage),
     /// Envelope signature verification failed.
     #[error("signature verification failed")]
     SignatureInvalid,
+    /// Envelope carries more attestations than the channel allows.
+    #[error("too many attestations: {count} > {max}")]
+    TooManyAttestations {
+        /// Attestations on the envelope.
+        count: usize,
+        /// Channel limit.
+        max: usize,
+    },
+    /// Channel requires a payload type and the envelope has none.
+    #[error("missing payload type")]
+    MissingPayloadType,
+    /// Payload type is not in the channel's allowlist.
+    #[error("payload type not allowed: {0}")]
+    PayloadTypeNotAllowed(String),
 }
 
 /// Validation context across a channel (previous hash + timestamp).
@@ -263,11 +431,27 @@ pub struct ChannelState {
     pub last_timestamp: Option<Timestamp>,
 }
 
-/// Verify an envelope against the registry and previous state.
+/// Verify an envelope against the registry and previous state, using the
+/// system clock for the channel's future-skew bound.
 pub fn validate_envelope(
     env: &Envelope,
     registry: &ChannelRegistry,
     prev_state: &ChannelState,
+) -> Result<ChannelState, ValidationError> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as Timestamp)
+        .unwrap_or_default();
+    validate_envelope_at(env, registry, prev_state, now)
+}
+
+/// Verify an envelope against the registry and previous state, treating
+/// `now` (unix epoch millis) as the current time.
+pub fn validate_envelope_at(
+    env: &Envelope,
+    registry: &ChannelRegistry,
+    prev_state: &ChannelState,
+    now: Timestamp,
 ) -> Result<ChannelState, ValidationError> {
     // Body hash check
     let computed_body = hash_body(&env.body);
@@ -293,6 +477,37 @@ pub fn validate_envelope(
         .cloned()
         .unwrap_or_default();
 
+    // Payload type allowlist
+    match &env.body.payload_type {
+        None if policy.require_payload_type => {
+            return Err(ValidationError::MissingPayloadType);
+        }
+        Some(payload_type) => {
+            if let Some(allowed) = &policy.allowed_payload_types {
+                if !allowed.contains(payload_type) {
+                    return Err(ValidationError::PayloadTypeNotAllowed(payload_type.clone()));
+                }
+            }
+        }
+        None => {}
+    }
+
+    // Future skew
+    if let Some(skew) = policy.max_future_skew {
+        let limit = now.saturating_add(skew.as_millis() as Timestamp);
+        if env.header.timestamp > limit {
+            return Err(ValidationError::TimestampTooFarFuture);
+        }
+    }
+
+    // Bound attestation work before verifying anything
+    if env.attestations.len() > policy.max_attestations_per_envelope {
+        return Err(ValidationError::TooManyAttestations {
+            count: env.attestations.len(),
+            max: policy.max_attestations_per_envelope,
+        });
+    }
+
     // Signature check
     if env.signatures.len() < policy.min_signers {
         return Err(ValidationError::InsufficientSignatures(
@@ -390,6 +605,11 @@ mod tests {
                 allowed_signers: vec![],
                 require_attestations: false,
                 enforce_timestamp_ordering: true,
+                max_attestations_per_envelope: DEFAULT_MAX_ATTESTATIONS,
+                max_future_skew: None,
+                ordering: OrderingStrategy::StrictChain,
+                allowed_payload_types: None,
+                require_payload_type: false,
             },
         });
 
@@ -397,6 +617,213 @@ mod tests {
         assert!(state.last_hash.is_some());
     }
 
+    #[test]
+    fn caps_attestations_per_envelope() {
+        let sign = |env: &mut Envelope, sk: &SigningKey| {
+            env.signatures.clear();
+            env.signatures.push(Signature {
+                signer: sk.verifying_key().to_bytes(),
+                signature: sk.sign(&envelope_hash(env)).to_bytes(),
+            });
+        };
+        let mut registry = ChannelRegistry::new();
+        registry.upsert(ChannelSpec {
+            name: "muscle_io".into(),
+            policy: ChannelPolicy {
+                max_attestations_per_envelope: 2,
+                ..ChannelPolicy::default()
+            },
+        });
+        let link = || {
+            chain_link(AttestationKind::Custom {
+                label: "cap".into(),
+                payload_hash: [9; 32],
+            })
+        };
+
+        let (mut env, sk) = base_envelope();
+        env.attestations = vec![link(), link()];
+        sign(&mut env, &sk);
+        validate_envelope(&env, &registry, &ChannelState::default()).unwrap();
+
+        // Forged attestations over the cap are rejected before any is verified.
+        let mut forged = link();
+        forged.signature = [0; 64];
+        env.attestations = vec![forged; 3];
+        sign(&mut env, &sk);
+        assert_eq!(
+            validate_envelope(&env, &registry, &ChannelState::default()).unwrap_err(),
+            ValidationError::TooManyAttestations { count: 3, max: 2 }
+        );
+    }
+
+    #[test]
+    fn rejects_timestamps_beyond_future_skew() {
+        let mut registry = ChannelRegistry::new();
+        registry.upsert(ChannelSpec {
+            name: "muscle_io".into(),
+            policy: ChannelPolicy {
+                max_future_skew: Some(Duration::from_secs(5)),
+                ..ChannelPolicy::default()
+            },
+        });
+        let now = 1_000_000;
+        let stamped = |timestamp| {
+            let (mut env, sk) = base_envelope();
+            env.header.timestamp = timestamp;
+            env.signatures.clear();
+            env.signatures.push(Signature {
+                signer: sk.verifying_key().to_bytes(),
+                signature: sk.sign(&envelope_hash(&env)).to_bytes(),
+            });
+            env
+        };
+        let state = ChannelState::default();
+
+        validate_envelope_at(&stamped(now + 5_000), &registry, &state, now).unwrap();
+        assert_eq!(
+            validate_envelope_at(&stamped(now + 5_001), &registry, &state, now).unwrap_err(),
+            ValidationError::TimestampTooFarFuture
+        );
+
+        // Without a skew bound any future timestamp is accepted.
+        registry.upsert(ChannelSpec {
+            name: "muscle_io".into(),
+            policy: ChannelPolicy::default(),
+        });
+        validate_envelope_at(&stamped(u64::MAX), &registry, &state, now).unwrap();
+    }
+
+    #[test]
+    fn enforces_payload_type_allowlist() {
+        let mut registry = ChannelRegistry::new();
+        registry.upsert(ChannelSpec {
+            name: "muscle_io".into(),
+            policy: ChannelPolicy {
+                allowed_payload_types: Some(vec!["test".into()]),
+                require_payload_type: true,
+                ..ChannelPolicy::default()
+            },
+        });
+        let typed = |payload_type: Option<&str>| {
+            let (mut env, sk) = base_envelope();
+            env.body.payload_type = payload_type.map(Into::into);
+            env.header.body_hash = hash_body(&env.body);
+            env.signatures.push(Signature {
+                signer: sk.verifying_key().to_bytes(),
+                signature: sk.sign(&envelope_hash(&env)).to_bytes(),
+            });
+            env
+        };
+        let state = ChannelState::default();
+
+        validate_envelope(&typed(Some("test")), &registry, &state).unwrap();
+        assert_eq!(
+            validate_envelope(&typed(Some("other")), &registry, &state).unwrap_err(),
+            ValidationError::PayloadTypeNotAllowed("other".into())
+        );
+        assert_eq!(
+            validate_envelope(&typed(None), &registry, &state).unwrap_err(),
+            ValidationError::MissingPayloadType
+        );
+
+        // Unset, any payload type (or none) is accepted.
+        registry.upsert(ChannelSpec {
+            name: "muscle_io".into(),
+            policy: ChannelPolicy::default(),
+        });
+        validate_envelope(&typed(Some("other")), &registry, &state).unwrap();
+        validate_envelope(&typed(None), &registry, &state).unwrap();
+    }
+
+    fn chain_link(statement: AttestationKind) -> Attestation {
+        let sk = signing_key();
+        let statement_hash = hash_attestation_statement(&statement);
+        Attestation {
+            issuer: sk.verifying_key().to_bytes(),
+            statement,
+            statement_hash,
+            signature: sk.sign(&statement_hash).to_bytes(),
+        }
+    }
+
+    #[test]
+    fn require_attestations_needs_a_verifying_attestation() {
+        let mut registry = ChannelRegistry::new();
+        registry.upsert(ChannelSpec {
+            name: "muscle_io".into(),
+            policy: ChannelPolicy {
+                require_attestations: true,
+                ..ChannelPolicy::default()
+            },
+        });
+        let attested = |attestations: Vec<Attestation>| {
+            let (mut env, sk) = base_envelope();
+            env.attestations = attestations;
+            env.signatures.push(Signature {
+                signer: sk.verifying_key().to_bytes(),
+                signature: sk.sign(&envelope_hash(&env)).to_bytes(),
+            });
+            env
+        };
+        let link = chain_link(AttestationKind::Runtime {
+            runtime_id: "tee-0".into(),
+            policy_hash: [2; 32],
+        });
+        let state = ChannelState::default();
+
+        assert_eq!(
+            validate_envelope(&attested(Vec::new()), &registry, &state).unwrap_err(),
+            ValidationError::MissingAttestations
+        );
+        validate_envelope(&attested(vec![link.clone()]), &registry, &state).unwrap();
+
+        let mut forged = link;
+        forged.signature = [0; 64];
+        assert_eq!(
+            validate_envelope(&attested(vec![forged]), &registry, &state).unwrap_err(),
+            ValidationError::AttestationInvalid
+        );
+    }
+
+    #[test]
+    fn attestation_chain_verifies_required_order() {
+        let required = [
+            AttestationStage::Build,
+            AttestationStage::Runtime,
+            AttestationStage::Policy,
+        ];
+        let chain = AttestationChain::new(vec![
+            chain_link(AttestationKind::Build {
+                artifact_hash: [1; 32],
+                builder: "ci".into(),
+            }),
+            chain_link(AttestationKind::Runtime {
+                runtime_id: "tee-0".into(),
+                policy_hash: [2; 32],
+            }),
+            chain_link(AttestationKind::Policy {
+                bundle_hash: [3; 32],
+                expires_at: 10,
+            }),
+        ]);
+        chain.verify_chain(&required).unwrap();
+
+        let missing_runtime =
+            AttestationChain::new(vec![chain.links[0].clone(), chain.links[2].clone()]);
+        assert_eq!(
+            missing_runtime.verify_chain(&required).unwrap_err(),
+            ValidationError::AttestationChainIncomplete(AttestationStage::Runtime)
+        );
+
+        let mut tampered = chain.clone();
+        tampered.links[1].statement_hash = [0; 32];
+        assert_eq!(
+            tampered.verify_chain(&required).unwrap_err(),
+            ValidationError::AttestationInvalid
+        );
+    }
+
     #[test]
     fn rejects_bad_body_hash() {
         let (mut env, sk) = base_envelope();
@@ -411,4 +838,74 @@ mod tests {
         let err = validate_envelope(&env, &registry, &ChannelState::default()).unwrap_err();
         assert_eq!(err, ValidationError::BodyHashMismatch);
     }
+
+    fn hex(hash: &Hash) -> String {
+        hash.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    fn golden_envelope(
+        channel: &str,
+        prev: Option<Hash>,
+        timestamp: Timestamp,
+        payload: serde_json::Value,
+        payload_type: Option<&str>,
+    ) -> Envelope {
+        let body = EnvelopeBody {
+            payload,
+            payload_type: payload_type.map(Into::into),
+        };
+        Envelope {
+            header: EnvelopeHeader {
+                channel: channel.into(),
+                version: 1,
+                prev,
+                body_hash: hash_body(&body),
+                timestamp,
+            },
+            body,
+            signatures: Vec::new(),
+            attestations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn envelope_hash_v1_golden_vectors() {
+        let genesis = golden_envelope(
+            "muscle_io",
+            None,
+            1,
+            serde_json::json!({"hello": "world"}),
+            Some("test"),
+        );
+        let chained = golden_envelope(
+            "muscle_io",
+            Some(envelope_hash(&genesis)),
+            2,
+            serde_json::json!({"n": 2, "tags": ["a", "b"], "nested": {"ok": true}}),
+            None,
+        );
+        let unicode = golden_envelope(
+            "ui/événements",
+            Some([0xAB; 32]),
+            u64::MAX,
+            serde_json::json!("Ω ≠ ∅"),
+            Some("text"),
+        );
+
+        let golden = [
+            "3c7b561e952f9276f49446816072bff5456ce718cf3ad1ea37d8f8716f752dc9",
+            "7cb73f493ed705df5c99146f8f425714a4083e681ad56181c7cf77b2a52263fb",
+            "c63e336256d3a08271c331c6bebd86f5e77102a9b300cec6611bdae2a65fc171",
+        ];
+        for (env, expected) in [&genesis, &chained, &unicode].into_iter().zip(golden) {
+            assert_eq!(hex(&hash_envelope_v1(env)), expected, "{:?}", env.header);
+            assert_eq!(envelope_hash(env), hash_envelope_v1(env));
+            assert_eq!(
+                envelope_hash_versioned(env, HASH_VERSION_V1),
+                Some(hash_envelope_v1(env))
+            );
+        }
+        assert_eq!(envelope_hash_versioned(&genesis, 0), None);
+        assert_eq!(hash_version(0), HASH_VERSION_V1);
+    }
 }
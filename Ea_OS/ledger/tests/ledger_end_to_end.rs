@@ -2,7 +2,7 @@ use ed25519_dalek::SigningKey;
 use ledger_core::{signing, AppendError, AppendLog, CheckpointWriter, ReplayValidator};
 use ledger_spec::{
     Attestation, AttestationKind, ChannelPolicy, ChannelRegistry, ChannelSpec, Envelope,
-    EnvelopeBody, EnvelopeHeader, ValidationError,
+    EnvelopeBody, EnvelopeHeader, OrderingStrategy, ValidationError,
 };
 fn signing_key(seed: u8) -> SigningKey {
     SigningKey::from_bytes(&[seed; 32])
@@ -59,6 +59,11 @@ fn append_validate_checkpoint_and_receipts_across_channels(
             allowed_signers: vec![signer_alpha.verifying_key().to_bytes()],
             require_attestations: true,
             enforce_timestamp_ordering: true,
+            max_attestations_per_envelope: ledger_spec::DEFAULT_MAX_ATTESTATIONS,
+            max_future_skew: None,
+            ordering: OrderingStrategy::StrictChain,
+            allowed_payload_types: None,
+            require_payload_type: false,
         },
     });
     registry.upsert(ChannelSpec {
@@ -71,6 +76,11 @@ fn append_validate_checkpoint_and_receipts_across_channels(
             ],
             require_attestations: false,
             enforce_timestamp_ordering: true,
+            max_attestations_per_envelope: ledger_spec::DEFAULT_MAX_ATTESTATIONS,
+            max_future_skew: None,
+            ordering: OrderingStrategy::StrictChain,
+            allowed_payload_types: None,
+            require_payload_type: false,
         },
     });
 
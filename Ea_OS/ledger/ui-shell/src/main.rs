@@ -233,6 +233,11 @@ fn default_registry() -> ChannelRegistry {
             allowed_signers: vec![],
             require_attestations: false,
             enforce_timestamp_ordering: true,
+            max_attestations_per_envelope: ledger_spec::DEFAULT_MAX_ATTESTATIONS,
+            max_future_skew: None,
+            ordering: ledger_spec::OrderingStrategy::StrictChain,
+            allowed_payload_types: None,
+            require_payload_type: false,
         },
     });
     registry
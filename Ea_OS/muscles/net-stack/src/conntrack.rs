@@ -0,0 +1,512 @@
+//! Connection tracking and source NAT for the userspace stack.
+//!
+//! [`ConntrackTable`] sits between the device and smoltcp: every frame the
+//! interface sends or receives is matched against the 5-tuple table, and flows
+//! covered by a [`NatRule`] have their source address rewritten on the way out
+//! and restored on the way back in. ARP is rewritten the same way so peers
+//! resolve the translated address to this interface.
+//!
+//! The table is bounded: it holds at most [`DEFAULT_MAX_ENTRIES`] flows unless
+//! configured otherwise, evicting the least recently seen flow to make room,
+//! and unsolicited inbound flows are only tracked for addresses a NAT rule
+//! covers unless inbound tracking is enabled.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use smoltcp::phy::{Device, DeviceCapabilities, PacketMeta, RxToken, TxToken};
+use smoltcp::time::{Duration, Instant};
+use smoltcp::wire::{
+    ArpPacket, EthernetFrame, EthernetProtocol, IpAddress, IpProtocol, Ipv4Address, Ipv4Packet,
+    TcpPacket, UdpPacket,
+};
+
+use crate::Protocol;
+
+/// Default time a flow may stay silent before it is evicted
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Default cap on tracked flows
+pub const DEFAULT_MAX_ENTRIES: usize = 4096;
+
+/// Minimum time between idle sweeps driven by [`ConntrackTable::sweep`]
+const SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Connection 5-tuple as seen on the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FlowKey {
+    pub protocol: Protocol,
+    pub src_addr: Ipv4Address,
+    pub src_port: u16,
+    pub dst_addr: Ipv4Address,
+    pub dst_port: u16,
+}
+
+impl FlowKey {
+    /// The tuple of a packet travelling the other way
+    pub fn reversed(&self) -> Self {
+        Self {
+            protocol: self.protocol,
+            src_addr: self.dst_addr,
+            src_port: self.dst_port,
+            dst_addr: self.src_addr,
+            dst_port: self.src_port,
+        }
+    }
+}
+
+/// Lifecycle of a tracked flow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnState {
+    /// Only the originating direction has been seen
+    New,
+    /// Traffic has been seen in both directions
+    Established,
+    /// A FIN or RST has been seen
+    Closing,
+}
+
+/// Which side opened a tracked flow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowDirection {
+    Outbound,
+    Inbound,
+}
+
+/// A tracked flow, keyed by the tuple of its first packet
+#[derive(Debug, Clone)]
+pub struct ConntrackEntry {
+    pub state: ConnState,
+    pub direction: FlowDirection,
+    pub last_seen: Instant,
+    /// Source address outbound packets are rewritten to, if NATed
+    pub translated_src: Option<Ipv4Address>,
+}
+
+impl ConntrackEntry {
+    fn touch(&mut self, now: Instant, closing: bool, reply: bool) {
+        self.last_seen = now;
+        if closing {
+            self.state = ConnState::Closing;
+        } else if reply && self.state == ConnState::New {
+            self.state = ConnState::Established;
+        }
+    }
+}
+
+/// One-to-one source NAT: outbound packets from `source` leave as `translated`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NatRule {
+    pub source: Ipv4Address,
+    pub translated: Ipv4Address,
+}
+
+impl NatRule {
+    pub fn new(source: Ipv4Address, translated: Ipv4Address) -> Self {
+        Self { source, translated }
+    }
+}
+
+/// Address rewrite to apply to a tracked packet
+enum Rewrite {
+    Src(Ipv4Address),
+    Dst(Ipv4Address),
+}
+
+/// Connection-tracking table with optional source NAT
+#[derive(Debug, Clone)]
+pub struct ConntrackTable {
+    entries: HashMap<FlowKey, ConntrackEntry>,
+    /// Expected reply tuple (after NAT) -> original tuple
+    replies: HashMap<FlowKey, FlowKey>,
+    nat_rules: Vec<NatRule>,
+    idle_timeout: Duration,
+    max_entries: usize,
+    /// Track unsolicited inbound flows no NAT rule covers
+    track_inbound: bool,
+    next_sweep: Instant,
+}
+
+impl Default for ConntrackTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_IDLE_TIMEOUT)
+    }
+}
+
+impl ConntrackTable {
+    /// Create an empty table evicting flows idle for longer than `idle_timeout`
+    pub fn new(idle_timeout: Duration) -> Self {
+        Self {
+            entries: HashMap::new(),
+            replies: HashMap::new(),
+            nat_rules: Vec::new(),
+            idle_timeout,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            track_inbound: false,
+            next_sweep: Instant::ZERO,
+        }
+    }
+
+    /// Cap the number of tracked flows; the least recently seen flow is
+    /// evicted to make room for a new one
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    /// Also track unsolicited inbound flows to addresses no NAT rule covers
+    pub fn with_inbound_tracking(mut self) -> Self {
+        self.track_inbound = true;
+        self
+    }
+
+    /// Add a source NAT rule for outbound flows
+    pub fn with_nat_rule(mut self, rule: NatRule) -> Self {
+        self.nat_rules.push(rule);
+        self
+    }
+
+    /// Number of tracked flows
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Look up a flow by the tuple of its first packet
+    pub fn get(&self, key: &FlowKey) -> Option<&ConntrackEntry> {
+        self.entries.get(key)
+    }
+
+    /// Iterate over tracked flows
+    pub fn iter(&self) -> impl Iterator<Item = (&FlowKey, &ConntrackEntry)> {
+        self.entries.iter()
+    }
+
+    /// Drop flows not seen within the idle timeout, returning how many were evicted
+    pub fn evict_idle(&mut self, now: Instant) -> usize {
+        let before = self.entries.len();
+        let timeout = self.idle_timeout;
+        self.entries
+            .retain(|_, entry| now - entry.last_seen <= timeout);
+        let entries = &self.entries;
+        self.replies
+            .retain(|_, original| entries.contains_key(original));
+        before - self.entries.len()
+    }
+
+    /// [`Self::evict_idle`], run at most once per sweep interval so callers
+    /// can invoke it on every poll
+    pub fn sweep(&mut self, now: Instant) -> usize {
+        if now < self.next_sweep {
+            return 0;
+        }
+        self.next_sweep = now + SWEEP_INTERVAL;
+        self.evict_idle(now)
+    }
+
+    /// Drop the least recently seen flow
+    fn evict_oldest(&mut self) {
+        let Some((&key, entry)) = self.entries.iter().min_by_key(|(_, entry)| entry.last_seen)
+        else {
+            return;
+        };
+        let reply = reply_key(key, entry.translated_src);
+        self.entries.remove(&key);
+        if self.replies.get(&reply) == Some(&key) {
+            self.replies.remove(&reply);
+        }
+    }
+
+    /// Whether `addr` is the source or translated address of a NAT rule
+    fn nat_covers(&self, addr: Ipv4Address) -> bool {
+        self.nat_rules
+            .iter()
+            .any(|rule| rule.source == addr || rule.translated == addr)
+    }
+
+    fn nat_for_source(&self, addr: Ipv4Address) -> Option<Ipv4Address> {
+        self.nat_rules
+            .iter()
+            .find(|rule| rule.source == addr)
+            .map(|rule| rule.translated)
+    }
+
+    fn nat_for_translated(&self, addr: Ipv4Address) -> Option<Ipv4Address> {
+        self.nat_rules
+            .iter()
+            .find(|rule| rule.translated == addr)
+            .map(|rule| rule.source)
+    }
+
+    /// Track and rewrite a frame about to be transmitted
+    pub fn outbound(&mut self, frame: &mut [u8], now: Instant) {
+        self.process(frame, FlowDirection::Outbound, now);
+    }
+
+    /// Track and rewrite a received frame before smoltcp sees it
+    pub fn inbound(&mut self, frame: &mut [u8], now: Instant) {
+        self.process(frame, FlowDirection::Inbound, now);
+    }
+
+    fn process(&mut self, frame: &mut [u8], direction: FlowDirection, now: Instant) {
+        let Ok(mut frame) = EthernetFrame::new_checked(frame) else {
+            return;
+        };
+        match frame.ethertype() {
+            EthernetProtocol::Arp => self.rewrite_arp(frame.payload_mut(), direction),
+            EthernetProtocol::Ipv4 => self.process_ipv4(frame.payload_mut(), direction, now),
+            _ => {}
+        }
+    }
+
+    fn rewrite_arp(&self, packet: &mut [u8], direction: FlowDirection) {
+        let Ok(mut arp) = ArpPacket::new_checked(packet) else {
+            return;
+        };
+        if arp.protocol_type() != EthernetProtocol::Ipv4 {
+            return;
+        }
+        match direction {
+            FlowDirection::Outbound => {
+                let Ok(sender) = <[u8; 4]>::try_from(arp.source_protocol_addr()) else {
+                    return;
+                };
+                if let Some(translated) = self.nat_for_source(sender.into()) {
+                    arp.set_source_protocol_addr(&translated.octets());
+                }
+            }
+            FlowDirection::Inbound => {
+                let Ok(target) = <[u8; 4]>::try_from(arp.target_protocol_addr()) else {
+                    return;
+                };
+                if let Some(source) = self.nat_for_translated(target.into()) {
+                    arp.set_target_protocol_addr(&source.octets());
+                }
+            }
+        }
+    }
+
+    fn process_ipv4(&mut self, packet: &mut [u8], direction: FlowDirection, now: Instant) {
+        let Ok(mut ip) = Ipv4Packet::new_checked(packet) else {
+            return;
+        };
+        let protocol = match ip.next_header() {
+            IpProtocol::Tcp => Protocol::Tcp,
+            IpProtocol::Udp => Protocol::Udp,
+            _ => return,
+        };
+        let Some((src_port, dst_port, closing)) = transport_info(protocol, ip.payload_mut()) else {
+            return;
+        };
+        let key = FlowKey {
+            protocol,
+            src_addr: ip.src_addr(),
+            src_port,
+            dst_addr: ip.dst_addr(),
+            dst_port,
+        };
+
+        match self.observe(key, direction, closing, now) {
+            Some(Rewrite::Src(addr)) => ip.set_src_addr(addr),
+            Some(Rewrite::Dst(addr)) => ip.set_dst_addr(addr),
+            None => return,
+        }
+        let (src, dst) = (
+            IpAddress::Ipv4(ip.src_addr()),
+            IpAddress::Ipv4(ip.dst_addr()),
+        );
+        match protocol {
+            Protocol::Tcp => {
+                if let Ok(mut tcp) = TcpPacket::new_checked(ip.payload_mut()) {
+                    tcp.fill_checksum(&src, &dst);
+                }
+            }
+            Protocol::Udp => {
+                if let Ok(mut udp) = UdpPacket::new_checked(ip.payload_mut()) {
+                    udp.fill_checksum(&src, &dst);
+                }
+            }
+        }
+        ip.fill_checksum();
+    }
+
+    /// Record a packet against its flow and decide how to rewrite it
+    fn observe(
+        &mut self,
+        key: FlowKey,
+        direction: FlowDirection,
+        closing: bool,
+        now: Instant,
+    ) -> Option<Rewrite> {
+        if let Some(entry) = self.entries.get_mut(&key) {
+            entry.touch(now, closing, false);
+            return match direction {
+                FlowDirection::Outbound => entry.translated_src.map(Rewrite::Src),
+                FlowDirection::Inbound => None,
+            };
+        }
+        if let Some(original) = self.replies.get(&key).copied() {
+            let entry = self.entries.get_mut(&original)?;
+            entry.touch(now, closing, true);
+            return match direction {
+                FlowDirection::Inbound => entry
+                    .translated_src
+                    .map(|_| Rewrite::Dst(original.src_addr)),
+                FlowDirection::Outbound => None,
+            };
+        }
+
+        let translated_src = match direction {
+            FlowDirection::Outbound => self.nat_for_source(key.src_addr),
+            FlowDirection::Inbound if self.track_inbound || self.nat_covers(key.dst_addr) => None,
+            FlowDirection::Inbound => return None,
+        };
+        if self.entries.len() >= self.max_entries {
+            self.sweep(now);
+            if self.entries.len() >= self.max_entries {
+                self.evict_oldest();
+            }
+        }
+        self.entries.insert(
+            key,
+            ConntrackEntry {
+                state: if closing {
+                    ConnState::Closing
+                } else {
+                    ConnState::New
+                },
+                direction,
+                last_seen: now,
+                translated_src,
+            },
+        );
+        self.replies.insert(reply_key(key, translated_src), key);
+        translated_src.map(Rewrite::Src)
+    }
+}
+
+/// Tuple replies to the flow opened by `key` arrive with
+fn reply_key(key: FlowKey, translated_src: Option<Ipv4Address>) -> FlowKey {
+    let mut reply = key.reversed();
+    if let Some(addr) = translated_src {
+        reply.dst_addr = addr;
+    }
+    reply
+}
+
+/// Ports and whether the segment closes the flow
+fn transport_info(protocol: Protocol, payload: &[u8]) -> Option<(u16, u16, bool)> {
+    match protocol {
+        Protocol::Tcp => {
+            let tcp = TcpPacket::new_checked(payload).ok()?;
+            Some((tcp.src_port(), tcp.dst_port(), tcp.fin() || tcp.rst()))
+        }
+        Protocol::Udp => {
+            let udp = UdpPacket::new_checked(payload).ok()?;
+            Some((udp.src_port(), udp.dst_port(), false))
+        }
+    }
+}
+
+/// Device adapter that runs every frame through a [`ConntrackTable`]
+pub(crate) struct ConntrackDevice<'a, D: Device> {
+    inner: &'a mut D,
+    table: &'a RefCell<ConntrackTable>,
+}
+
+impl<'a, D: Device> ConntrackDevice<'a, D> {
+    pub(crate) fn new(inner: &'a mut D, table: &'a RefCell<ConntrackTable>) -> Self {
+        Self { inner, table }
+    }
+}
+
+pub(crate) struct ConntrackRxToken<'a, T> {
+    inner: T,
+    table: &'a RefCell<ConntrackTable>,
+    now: Instant,
+}
+
+impl<'a, T: RxToken> RxToken for ConntrackRxToken<'a, T> {
+    fn consume<R, F>(self, f: F) -> R
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        let (table, now) = (self.table, self.now);
+        self.inner.consume(|frame| {
+            let mut frame = frame.to_vec();
+            table.borrow_mut().inbound(&mut frame, now);
+            f(&frame)
+        })
+    }
+
+    fn meta(&self) -> PacketMeta {
+        self.inner.meta()
+    }
+}
+
+pub(crate) struct ConntrackTxToken<'a, T> {
+    inner: T,
+    table: &'a RefCell<ConntrackTable>,
+    now: Instant,
+}
+
+impl<'a, T: TxToken> TxToken for ConntrackTxToken<'a, T> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        let (table, now) = (self.table, self.now);
+        self.inner.consume(len, |frame| {
+            let result = f(frame);
+            table.borrow_mut().outbound(frame, now);
+            result
+        })
+    }
+
+    fn set_meta(&mut self, meta: PacketMeta) {
+        self.inner.set_meta(meta);
+    }
+}
+
+impl<'a, D: Device> Device for ConntrackDevice<'a, D> {
+    type RxToken<'b>
+        = ConntrackRxToken<'b, D::RxToken<'b>>
+    where
+        Self: 'b;
+    type TxToken<'b>
+        = ConntrackTxToken<'b, D::TxToken<'b>>
+    where
+        Self: 'b;
+
+    fn receive(&mut self, timestamp: Instant) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        let (rx, tx) = self.inner.receive(timestamp)?;
+        Some((
+            ConntrackRxToken {
+                inner: rx,
+                table: self.table,
+                now: timestamp,
+            },
+            ConntrackTxToken {
+                inner: tx,
+                table: self.table,
+                now: timestamp,
+            },
+        ))
+    }
+
+    fn transmit(&mut self, timestamp: Instant) -> Option<Self::TxToken<'_>> {
+        let tx = self.inner.transmit(timestamp)?;
+        Some(ConntrackTxToken {
+            inner: tx,
+            table: self.table,
+            now: timestamp,
+        })
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        self.inner.capabilities()
+    }
+}
@@ -45,9 +45,10 @@
 //! - **Auditability**: Network traffic passes through IPC (can be logged)
 //! - **Restartability**: Stack can be restarted without system reboot
 
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use ea_symbiote::{BlobType, SovereignDocument};
 use serde::{Deserialize, Serialize};
@@ -58,6 +59,10 @@ use smoltcp::socket::udp::{PacketBuffer, PacketMetadata, Socket as UdpSocket};
 use smoltcp::time::Instant as SmolInstant;
 use smoltcp::wire::{EthernetAddress, HardwareAddress, IpAddress, IpCidr, Ipv4Address};
 
+pub mod conntrack;
+
+use conntrack::{ConntrackDevice, ConntrackTable};
+
 // ============================================================================
 // IPC Protocol: Network Operation Types
 // ============================================================================
@@ -83,6 +88,8 @@ pub enum NetOperation {
     Configure(NetConfigure),
     /// Query socket status
     Status(NetStatus),
+    /// Query socket traffic counters
+    Metrics(NetMetrics),
 }
 
 /// Response from network operations
@@ -96,6 +103,8 @@ pub enum NetResponse {
     Data(Vec<u8>),
     /// Socket status
     Status(SocketStatus),
+    /// Socket traffic counters
+    Metrics(SocketMetrics),
 }
 
 /// Bind request: associate a socket with a local address
@@ -171,6 +180,12 @@ pub struct NetStatus {
     pub socket_id: u64,
 }
 
+/// Metrics request: query socket traffic counters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetMetrics {
+    pub socket_id: u64,
+}
+
 /// Compact socket address for serialization
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SocketAddrCompact {
@@ -205,7 +220,7 @@ impl SocketAddrCompact {
 }
 
 /// Network protocol
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Protocol {
     Tcp,
     Udp,
@@ -232,6 +247,11 @@ pub enum NetError {
     InvalidState,
     BufferFull,
     InterfaceDown,
+    /// Packet exceeds the device MTU
+    PacketTooLarge {
+        size: usize,
+        mtu: usize,
+    },
     InternalError(String),
 }
 
@@ -246,6 +266,31 @@ pub struct SocketStatus {
     pub bytes_queued: usize,
 }
 
+/// Per-socket traffic counters
+///
+/// Byte counters are tracked by the stack across `Send`/`Recv` operations;
+/// queue and window fields are read from the smoltcp socket at query time.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SocketMetrics {
+    pub socket_id: u64,
+    /// Bytes accepted into the socket's send buffer
+    pub bytes_sent: u64,
+    /// Bytes handed back to the caller by `Recv`
+    pub bytes_received: u64,
+    /// TCP retransmissions (`None` when the underlying socket does not report them)
+    pub retransmits: Option<u64>,
+    /// TCP congestion window in bytes (`None` when the underlying socket does not report it)
+    pub congestion_window: Option<usize>,
+    /// Bytes waiting in the send buffer (TCP)
+    pub send_queue: usize,
+    /// Bytes waiting in the receive buffer (TCP)
+    pub recv_queue: usize,
+    /// Whether the socket may still send (TCP)
+    pub may_send: bool,
+    /// Whether the socket may still receive (TCP)
+    pub may_recv: bool,
+}
+
 // ============================================================================
 // SovereignBlob Implementation for Network IPC
 // ============================================================================
@@ -339,6 +384,125 @@ impl SovereignDocument for NetResponseBlob {
     }
 }
 
+// ============================================================================
+// IPC Client: Request/Response Correlation
+// ============================================================================
+
+/// Byte transport carrying serialized `NetBlob`s to the stack and
+/// `NetResponseBlob`s back
+pub trait NetIpcTransport {
+    /// Send one serialized request
+    fn send_bytes(&mut self, bytes: &[u8]) -> Result<(), NetError>;
+    /// Take the next serialized response, if one has arrived (non-blocking)
+    fn recv_bytes(&mut self) -> Option<Vec<u8>>;
+}
+
+/// Default time a request may stay unanswered
+pub const DEFAULT_IPC_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Interval between transport polls while waiting on a response
+const IPC_WAIT_POLL: Duration = Duration::from_millis(1);
+
+/// Client that correlates `NetResponseBlob`s with the `NetBlob` requests that
+/// caused them
+///
+/// Responses may arrive in any order. Responses for unknown, already resolved
+/// or timed-out request IDs are dropped.
+pub struct NetIpcClient<T: NetIpcTransport> {
+    transport: T,
+    timeout: Duration,
+    next_request_id: u64,
+    /// Outstanding request IDs and their deadlines
+    pending: HashMap<u64, Instant>,
+    /// Resolved requests not yet collected by the caller
+    completed: HashMap<u64, Result<NetResponse, NetError>>,
+}
+
+impl<T: NetIpcTransport> NetIpcClient<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            timeout: DEFAULT_IPC_TIMEOUT,
+            next_request_id: 1,
+            pending: HashMap::new(),
+            completed: HashMap::new(),
+        }
+    }
+
+    /// Override how long a request may stay unanswered
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Number of requests still awaiting a response
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Send `operation`, returning the request ID its response will carry
+    pub fn send(&mut self, operation: NetOperation) -> Result<u64, NetError> {
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        let blob = NetBlob {
+            operation,
+            request_id,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+        };
+        self.transport.send_bytes(&blob.to_bytes())?;
+        self.pending
+            .insert(request_id, Instant::now() + self.timeout);
+        Ok(request_id)
+    }
+
+    /// Drain arrived responses and expire overdue requests
+    pub fn poll(&mut self) {
+        while let Some(bytes) = self.transport.recv_bytes() {
+            let Some(blob) = NetResponseBlob::from_bytes(&bytes) else {
+                continue;
+            };
+            if self.pending.remove(&blob.request_id).is_some() {
+                self.completed.insert(blob.request_id, Ok(blob.response));
+            }
+        }
+
+        let now = Instant::now();
+        let expired: Vec<u64> = self
+            .pending
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(id, _)| *id)
+            .collect();
+        for request_id in expired {
+            self.pending.remove(&request_id);
+            self.completed
+                .insert(request_id, Err(NetError::ConnectionTimeout));
+        }
+    }
+
+    /// Collect the outcome of `request_id` if it has resolved
+    pub fn try_resolve(&mut self, request_id: u64) -> Option<Result<NetResponse, NetError>> {
+        self.poll();
+        self.completed.remove(&request_id)
+    }
+
+    /// Block until `request_id` resolves or times out
+    pub fn wait(&mut self, request_id: u64) -> Result<NetResponse, NetError> {
+        loop {
+            if let Some(result) = self.try_resolve(request_id) {
+                return result;
+            }
+            if !self.pending.contains_key(&request_id) {
+                return Err(NetError::InvalidState);
+            }
+            std::thread::sleep(IPC_WAIT_POLL);
+        }
+    }
+}
+
 // ============================================================================
 // Virtual Network Device (for smoltcp)
 // ============================================================================
@@ -360,8 +524,17 @@ impl VirtualDevice {
     }
 
     /// Inject a packet for reception
-    pub fn inject_rx(&mut self, packet: Vec<u8>) {
+    ///
+    /// Packets larger than the MTU are rejected, as a real NIC would drop them.
+    pub fn inject_rx(&mut self, packet: Vec<u8>) -> Result<(), NetError> {
+        if packet.len() > self.mtu {
+            return Err(NetError::PacketTooLarge {
+                size: packet.len(),
+                mtu: self.mtu,
+            });
+        }
         self.rx_buffer.push(packet);
+        Ok(())
     }
 
     /// Extract transmitted packets
@@ -381,7 +554,10 @@ impl RxToken for VirtualRxToken {
     }
 }
 
-pub struct VirtualTxToken<'a>(&'a mut Vec<Vec<u8>>);
+pub struct VirtualTxToken<'a> {
+    tx_buffer: &'a mut Vec<Vec<u8>>,
+    mtu: usize,
+}
 
 impl<'a> TxToken for VirtualTxToken<'a> {
     fn consume<R, F>(self, len: usize, f: F) -> R
@@ -390,7 +566,10 @@ impl<'a> TxToken for VirtualTxToken<'a> {
     {
         let mut buffer = vec![0u8; len];
         let result = f(&mut buffer);
-        self.0.push(buffer);
+        // Frames over the MTU never make it onto the wire
+        if len <= self.mtu {
+            self.tx_buffer.push(buffer);
+        }
         result
     }
 }
@@ -404,12 +583,21 @@ impl Device for VirtualDevice {
             None
         } else {
             let packet = self.rx_buffer.remove(0);
-            Some((VirtualRxToken(packet), VirtualTxToken(&mut self.tx_buffer)))
+            Some((
+                VirtualRxToken(packet),
+                VirtualTxToken {
+                    tx_buffer: &mut self.tx_buffer,
+                    mtu: self.mtu,
+                },
+            ))
         }
     }
 
     fn transmit(&mut self, _timestamp: SmolInstant) -> Option<Self::TxToken<'_>> {
-        Some(VirtualTxToken(&mut self.tx_buffer))
+        Some(VirtualTxToken {
+            tx_buffer: &mut self.tx_buffer,
+            mtu: self.mtu,
+        })
     }
 
     fn capabilities(&self) -> DeviceCapabilities {
@@ -430,6 +618,8 @@ struct SocketHandle {
     protocol: Protocol,
     local_addr: Option<SocketAddrCompact>,
     remote_addr: Option<SocketAddrCompact>,
+    bytes_sent: u64,
+    bytes_received: u64,
 }
 
 /// The main network stack manager
@@ -446,6 +636,8 @@ pub struct NetStackManager<D: Device> {
     next_socket_id: u64,
     /// Start time for timestamp calculations
     start_time: Instant,
+    /// Optional connection tracking / NAT applied to every frame
+    conntrack: Option<ConntrackTable>,
 }
 
 impl<D: Device> NetStackManager<D> {
@@ -466,9 +658,21 @@ impl<D: Device> NetStackManager<D> {
             socket_map: HashMap::new(),
             next_socket_id: 1,
             start_time: Instant::now(),
+            conntrack: None,
         }
     }
 
+    /// Run every frame through `table` before smoltcp sees it
+    pub fn with_conntrack(mut self, table: ConntrackTable) -> Self {
+        self.conntrack = Some(table);
+        self
+    }
+
+    /// The connection-tracking table, if enabled
+    pub fn conntrack(&self) -> Option<&ConntrackTable> {
+        self.conntrack.as_ref()
+    }
+
     /// Get current timestamp for smoltcp
     fn now(&self) -> SmolInstant {
         SmolInstant::from_millis(self.start_time.elapsed().as_millis() as i64)
@@ -478,10 +682,22 @@ impl<D: Device> NetStackManager<D> {
     /// Returns true if there was socket state change
     pub fn poll(&mut self) -> bool {
         use smoltcp::iface::PollResult;
-        matches!(
-            self.interface.poll(self.now(), &mut self.device, &mut self.sockets),
-            PollResult::SocketStateChanged
-        )
+        let now = self.now();
+        let result = match self.conntrack.take() {
+            Some(table) => {
+                let table = RefCell::new(table);
+                let mut device = ConntrackDevice::new(&mut self.device, &table);
+                let result = self.interface.poll(now, &mut device, &mut self.sockets);
+                let mut table = table.into_inner();
+                table.sweep(now);
+                self.conntrack = Some(table);
+                result
+            }
+            None => self
+                .interface
+                .poll(now, &mut self.device, &mut self.sockets),
+        };
+        matches!(result, PollResult::SocketStateChanged)
     }
 
     /// Handle a network operation request
@@ -496,6 +712,7 @@ impl<D: Device> NetStackManager<D> {
             NetOperation::Close(close) => self.handle_close(close),
             NetOperation::Configure(config) => self.handle_configure(config),
             NetOperation::Status(status) => self.handle_status(status),
+            NetOperation::Metrics(metrics) => self.handle_metrics(metrics),
         }
     }
 
@@ -516,6 +733,8 @@ impl<D: Device> NetStackManager<D> {
                         protocol: Protocol::Tcp,
                         local_addr: Some(bind.local_addr.clone()),
                         remote_addr: None,
+                        bytes_sent: 0,
+                        bytes_received: 0,
                     },
                 );
 
@@ -549,6 +768,8 @@ impl<D: Device> NetStackManager<D> {
                         protocol: Protocol::Udp,
                         local_addr: Some(bind.local_addr.clone()),
                         remote_addr: None,
+                        bytes_sent: 0,
+                        bytes_received: 0,
                     },
                 );
 
@@ -629,6 +850,8 @@ impl<D: Device> NetStackManager<D> {
                 protocol: Protocol::Tcp,
                 local_addr: None,
                 remote_addr: Some(connect.remote_addr.clone()),
+                bytes_sent: 0,
+                bytes_received: 0,
             },
         );
 
@@ -639,11 +862,11 @@ impl<D: Device> NetStackManager<D> {
     }
 
     fn handle_send(&mut self, send: &NetSend) -> NetResponse {
-        let Some(socket_handle) = self.socket_map.get(&send.socket_id) else {
+        let Some(socket_handle) = self.socket_map.get_mut(&send.socket_id) else {
             return NetResponse::Error(NetError::SocketNotFound);
         };
 
-        match socket_handle.protocol {
+        let response = match socket_handle.protocol {
             Protocol::Tcp => {
                 let socket = self.sockets.get_mut::<TcpSocket>(socket_handle.smoltcp_handle);
                 if !socket.may_send() {
@@ -674,15 +897,24 @@ impl<D: Device> NetStackManager<D> {
                     Err(e) => NetResponse::Error(e),
                 }
             }
+        };
+
+        if let NetResponse::Ok(NetResult {
+            bytes_transferred: Some(bytes),
+            ..
+        }) = &response
+        {
+            socket_handle.bytes_sent += *bytes as u64;
         }
+        response
     }
 
     fn handle_recv(&mut self, recv: &NetRecv) -> NetResponse {
-        let Some(socket_handle) = self.socket_map.get(&recv.socket_id) else {
+        let Some(socket_handle) = self.socket_map.get_mut(&recv.socket_id) else {
             return NetResponse::Error(NetError::SocketNotFound);
         };
 
-        match socket_handle.protocol {
+        let response = match socket_handle.protocol {
             Protocol::Tcp => {
                 let socket = self.sockets.get_mut::<TcpSocket>(socket_handle.smoltcp_handle);
                 if !socket.may_recv() {
@@ -708,7 +940,12 @@ impl<D: Device> NetStackManager<D> {
                     Err(_) => NetResponse::Error(NetError::WouldBlock),
                 }
             }
+        };
+
+        if let NetResponse::Data(data) = &response {
+            socket_handle.bytes_received += data.len() as u64;
         }
+        response
     }
 
     fn handle_close(&mut self, close: &NetClose) -> NetResponse {
@@ -782,6 +1019,30 @@ impl<D: Device> NetStackManager<D> {
             bytes_queued: 0, // Would need to query socket buffers
         })
     }
+
+    fn handle_metrics(&mut self, metrics: &NetMetrics) -> NetResponse {
+        let Some(socket_handle) = self.socket_map.get(&metrics.socket_id) else {
+            return NetResponse::Error(NetError::SocketNotFound);
+        };
+
+        let mut result = SocketMetrics {
+            socket_id: metrics.socket_id,
+            bytes_sent: socket_handle.bytes_sent,
+            bytes_received: socket_handle.bytes_received,
+            ..SocketMetrics::default()
+        };
+        if socket_handle.protocol == Protocol::Tcp {
+            // smoltcp keeps its retransmit count and congestion window private,
+            // so those stay `None`.
+            let socket = self.sockets.get::<TcpSocket>(socket_handle.smoltcp_handle);
+            result.send_queue = socket.send_queue();
+            result.recv_queue = socket.recv_queue();
+            result.may_send = socket.may_send();
+            result.may_recv = socket.may_recv();
+        }
+
+        NetResponse::Metrics(result)
+    }
 }
 
 // ============================================================================
@@ -825,13 +1086,399 @@ mod tests {
     #[test]
     fn test_virtual_device() {
         let mut device = VirtualDevice::new(1500);
-        device.inject_rx(vec![1, 2, 3, 4]);
+        device.inject_rx(vec![1, 2, 3, 4]).unwrap();
 
         let caps = device.capabilities();
         assert_eq!(caps.max_transmission_unit, 1500);
         assert_eq!(caps.medium, Medium::Ethernet);
     }
 
+    #[test]
+    fn test_virtual_device_rejects_over_mtu_inject() {
+        let mut device = VirtualDevice::new(64);
+        assert!(device.inject_rx(vec![0; 64]).is_ok());
+        match device.inject_rx(vec![0; 65]) {
+            Err(NetError::PacketTooLarge { size, mtu }) => assert_eq!((size, mtu), (65, 64)),
+            other => panic!("expected PacketTooLarge, got {:?}", other),
+        }
+
+        let (rx, _) = device.receive(SmolInstant::from_millis(0)).unwrap();
+        assert_eq!(rx.consume(|packet| packet.len()), 64);
+        assert!(device.receive(SmolInstant::from_millis(0)).is_none());
+    }
+
+    #[test]
+    fn test_virtual_device_tx_respects_mtu() {
+        let mut device = VirtualDevice::new(64);
+        for len in [32, 64, 65, 1500] {
+            let tx = device.transmit(SmolInstant::from_millis(0)).unwrap();
+            tx.consume(len, |buffer| buffer.fill(0xAB));
+        }
+
+        let frames = device.drain_tx();
+        assert_eq!(
+            frames.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![32, 64]
+        );
+        assert!(frames.iter().all(|frame| frame.len() <= 64));
+    }
+
+    /// Shuttle frames between two stacks until neither has anything left to send
+    fn pump(a: &mut NetStackManager<VirtualDevice>, b: &mut NetStackManager<VirtualDevice>) {
+        for _ in 0..64 {
+            a.poll();
+            b.poll();
+            let a_tx = a.device.drain_tx();
+            let b_tx = b.device.drain_tx();
+            if a_tx.is_empty() && b_tx.is_empty() {
+                return;
+            }
+            for frame in a_tx {
+                b.device.inject_rx(frame).unwrap();
+            }
+            for frame in b_tx {
+                a.device.inject_rx(frame).unwrap();
+            }
+        }
+    }
+
+    fn metrics(stack: &mut NetStackManager<VirtualDevice>, socket_id: u64) -> SocketMetrics {
+        match stack.handle_operation(&NetOperation::Metrics(NetMetrics { socket_id })) {
+            NetResponse::Metrics(metrics) => metrics,
+            other => panic!("expected metrics, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_socket_metrics_count_loopback_transfer() {
+        let server_addr = SocketAddrCompact {
+            ip: [10, 0, 0, 1],
+            port: 7000,
+        };
+        let mut server = NetStackManager::new(
+            VirtualDevice::new(1514),
+            [0x02, 0, 0, 0, 0, 1],
+            "10.0.0.1/24".parse().unwrap(),
+        );
+        let mut client = NetStackManager::new(
+            VirtualDevice::new(1514),
+            [0x02, 0, 0, 0, 0, 2],
+            "10.0.0.2/24".parse().unwrap(),
+        );
+
+        server.handle_operation(&NetOperation::Bind(NetBind {
+            socket_id: 1,
+            protocol: Protocol::Tcp,
+            local_addr: server_addr.clone(),
+        }));
+        server.handle_operation(&NetOperation::Listen(NetListen {
+            socket_id: 1,
+            backlog: 1,
+        }));
+        client.handle_operation(&NetOperation::Connect(NetConnect {
+            socket_id: 2,
+            protocol: Protocol::Tcp,
+            remote_addr: server_addr,
+        }));
+        pump(&mut client, &mut server);
+        assert!(metrics(&mut client, 2).may_send);
+
+        let payload = vec![0x5A; 4000];
+        let sent = client.handle_operation(&NetOperation::Send(NetSend {
+            socket_id: 2,
+            data: payload.clone(),
+            dest_addr: None,
+        }));
+        assert!(matches!(
+            sent,
+            NetResponse::Ok(NetResult {
+                bytes_transferred: Some(4000),
+                ..
+            })
+        ));
+        assert_eq!(metrics(&mut client, 2).send_queue, 4000);
+        pump(&mut client, &mut server);
+
+        assert_eq!(metrics(&mut server, 1).recv_queue, 4000);
+        let received = server.handle_operation(&NetOperation::Recv(NetRecv {
+            socket_id: 1,
+            max_bytes: 8192,
+        }));
+        assert!(matches!(received, NetResponse::Data(ref data) if *data == payload));
+
+        let client_metrics = metrics(&mut client, 2);
+        assert_eq!(client_metrics.bytes_sent, 4000);
+        assert_eq!(client_metrics.bytes_received, 0);
+        let server_metrics = metrics(&mut server, 1);
+        assert_eq!(server_metrics.bytes_received, 4000);
+        assert_eq!(server_metrics.recv_queue, 0);
+        assert!(server_metrics.may_recv);
+    }
+
+    #[test]
+    fn test_conntrack_nat_rewrites_source_and_restores_reply() {
+        use conntrack::{ConnState, FlowDirection, NatRule};
+        use smoltcp::time::Duration as SmolDuration;
+
+        let inside = Ipv4Address::new(10, 0, 0, 2);
+        let public = Ipv4Address::new(10, 0, 0, 99);
+        let server_addr = SocketAddrCompact {
+            ip: [10, 0, 0, 1],
+            port: 7000,
+        };
+        let mut server = NetStackManager::new(
+            VirtualDevice::new(1514),
+            [0x02, 0, 0, 0, 0, 1],
+            "10.0.0.1/24".parse().unwrap(),
+        );
+        let mut client = NetStackManager::new(
+            VirtualDevice::new(1514),
+            [0x02, 0, 0, 0, 0, 2],
+            "10.0.0.2/24".parse().unwrap(),
+        )
+        .with_conntrack(ConntrackTable::default().with_nat_rule(NatRule::new(inside, public)));
+
+        server.handle_operation(&NetOperation::Bind(NetBind {
+            socket_id: 1,
+            protocol: Protocol::Tcp,
+            local_addr: server_addr.clone(),
+        }));
+        server.handle_operation(&NetOperation::Listen(NetListen {
+            socket_id: 1,
+            backlog: 1,
+        }));
+        client.handle_operation(&NetOperation::Connect(NetConnect {
+            socket_id: 2,
+            protocol: Protocol::Tcp,
+            remote_addr: server_addr,
+        }));
+        pump(&mut client, &mut server);
+
+        // The server only ever sees the translated source address
+        let server_socket = server
+            .sockets
+            .get::<TcpSocket>(server.socket_map[&1].smoltcp_handle);
+        let peer = server_socket.remote_endpoint().unwrap();
+        assert_eq!(peer.addr, IpAddress::Ipv4(public));
+
+        client.handle_operation(&NetOperation::Send(NetSend {
+            socket_id: 2,
+            data: b"ping".to_vec(),
+            dest_addr: None,
+        }));
+        pump(&mut client, &mut server);
+        let request = server.handle_operation(&NetOperation::Recv(NetRecv {
+            socket_id: 1,
+            max_bytes: 64,
+        }));
+        assert!(matches!(request, NetResponse::Data(ref data) if data == b"ping"));
+
+        // Replies addressed to the translated address reach the original socket
+        server.handle_operation(&NetOperation::Send(NetSend {
+            socket_id: 1,
+            data: b"pong".to_vec(),
+            dest_addr: None,
+        }));
+        pump(&mut client, &mut server);
+        let reply = client.handle_operation(&NetOperation::Recv(NetRecv {
+            socket_id: 2,
+            max_bytes: 64,
+        }));
+        assert!(matches!(reply, NetResponse::Data(ref data) if data == b"pong"));
+
+        let table = client.conntrack().unwrap();
+        assert_eq!(table.len(), 1);
+        let (key, entry) = table.iter().next().unwrap();
+        assert_eq!((key.src_addr, key.dst_port), (inside, 7000));
+        assert_eq!(entry.translated_src, Some(public));
+        assert_eq!(entry.direction, FlowDirection::Outbound);
+        assert_eq!(entry.state, ConnState::Established);
+
+        let mut table = table.clone();
+        assert_eq!(
+            table.evict_idle(entry.last_seen + SmolDuration::from_secs(301)),
+            1
+        );
+        assert!(table.is_empty());
+    }
+
+    /// Ethernet/IPv4/UDP frame carrying an empty datagram
+    fn udp_frame(src: (Ipv4Address, u16), dst: (Ipv4Address, u16)) -> Vec<u8> {
+        use smoltcp::phy::ChecksumCapabilities;
+        use smoltcp::wire::{
+            EthernetAddress, EthernetFrame, EthernetProtocol, EthernetRepr, IpProtocol, Ipv4Packet,
+            Ipv4Repr, UdpPacket, UdpRepr,
+        };
+
+        let udp = UdpRepr {
+            src_port: src.1,
+            dst_port: dst.1,
+        };
+        let ip = Ipv4Repr {
+            src_addr: src.0,
+            dst_addr: dst.0,
+            next_header: IpProtocol::Udp,
+            payload_len: udp.header_len(),
+            hop_limit: 64,
+        };
+        let eth = EthernetRepr {
+            src_addr: EthernetAddress([0x02, 0, 0, 0, 0, 1]),
+            dst_addr: EthernetAddress([0x02, 0, 0, 0, 0, 2]),
+            ethertype: EthernetProtocol::Ipv4,
+        };
+        let caps = ChecksumCapabilities::default();
+        let mut buf = vec![0; eth.buffer_len() + ip.buffer_len() + udp.header_len()];
+        let mut frame = EthernetFrame::new_unchecked(&mut buf);
+        eth.emit(&mut frame);
+        let mut packet = Ipv4Packet::new_unchecked(frame.payload_mut());
+        ip.emit(&mut packet, &caps);
+        udp.emit(
+            &mut UdpPacket::new_unchecked(packet.payload_mut()),
+            &IpAddress::Ipv4(src.0),
+            &IpAddress::Ipv4(dst.0),
+            0,
+            |_| {},
+            &caps,
+        );
+        buf
+    }
+
+    #[test]
+    fn test_conntrack_table_is_bounded() {
+        use conntrack::NatRule;
+        use smoltcp::time::{Duration as SmolDuration, Instant as SmolInstant};
+
+        let inside = Ipv4Address::new(10, 0, 0, 2);
+        let public = Ipv4Address::new(10, 0, 0, 99);
+        let peer = Ipv4Address::new(192, 0, 2, 1);
+        let start = SmolInstant::from_secs(10);
+
+        // Unsolicited inbound traffic to an address no rule covers leaves no state
+        let mut table = ConntrackTable::default().with_nat_rule(NatRule::new(inside, public));
+        for port in 0..64 {
+            let mut frame = udp_frame((peer, port), (Ipv4Address::new(10, 0, 0, 7), 53));
+            table.inbound(&mut frame, start);
+        }
+        assert!(table.is_empty());
+        let mut frame = udp_frame((peer, 1), (public, 53));
+        table.inbound(&mut frame, start);
+        assert_eq!(table.len(), 1);
+
+        let mut table = ConntrackTable::default().with_inbound_tracking();
+        let mut frame = udp_frame((peer, 1), (Ipv4Address::new(10, 0, 0, 7), 53));
+        table.inbound(&mut frame, start);
+        assert_eq!(table.len(), 1);
+
+        // A full table makes room by evicting the least recently seen flow
+        let mut table = ConntrackTable::default().with_max_entries(4);
+        for port in 0..4u16 {
+            let mut frame = udp_frame((inside, 1000 + port), (peer, 53));
+            table.outbound(&mut frame, start + SmolDuration::from_millis(port.into()));
+        }
+        let mut refresh = udp_frame((inside, 1000), (peer, 53));
+        table.outbound(&mut refresh, start + SmolDuration::from_millis(10));
+        let mut frame = udp_frame((inside, 2000), (peer, 53));
+        table.outbound(&mut frame, start + SmolDuration::from_millis(20));
+        assert_eq!(table.len(), 4);
+        let mut ports: Vec<_> = table.iter().map(|(key, _)| key.src_port).collect();
+        ports.sort_unstable();
+        assert_eq!(ports, [1000, 1002, 1003, 2000]);
+
+        // Idle sweeps run at most once per interval
+        let idle = start + SmolDuration::from_secs(400);
+        assert_eq!(table.sweep(idle), 4);
+        let mut frame = udp_frame((inside, 3000), (peer, 53));
+        table.outbound(&mut frame, start);
+        assert_eq!(table.sweep(idle + SmolDuration::from_millis(500)), 0);
+        assert_eq!(table.len(), 1);
+        assert_eq!(table.sweep(idle + SmolDuration::from_secs(1)), 1);
+    }
+
+    /// In-memory IPC transport: records requests, replays queued responses
+    #[derive(Default)]
+    struct QueueTransport {
+        sent: Vec<NetBlob>,
+        responses: std::collections::VecDeque<Vec<u8>>,
+    }
+
+    impl NetIpcTransport for QueueTransport {
+        fn send_bytes(&mut self, bytes: &[u8]) -> Result<(), NetError> {
+            self.sent.push(NetBlob::from_bytes(bytes).unwrap());
+            Ok(())
+        }
+
+        fn recv_bytes(&mut self) -> Option<Vec<u8>> {
+            self.responses.pop_front()
+        }
+    }
+
+    fn response_bytes(request_id: u64, socket_id: u64) -> Vec<u8> {
+        NetResponseBlob {
+            response: NetResponse::Ok(NetResult {
+                socket_id,
+                bytes_transferred: None,
+            }),
+            request_id,
+            timestamp: 0,
+        }
+        .to_bytes()
+    }
+
+    #[test]
+    fn test_ipc_client_correlates_interleaved_responses() {
+        let mut client = NetIpcClient::new(QueueTransport::default());
+        let first = client
+            .send(NetOperation::Close(NetClose { socket_id: 10 }))
+            .unwrap();
+        let second = client
+            .send(NetOperation::Close(NetClose { socket_id: 20 }))
+            .unwrap();
+        assert_ne!(first, second);
+        assert_eq!(client.transport.sent.len(), 2);
+        assert_eq!(client.transport.sent[1].request_id, second);
+
+        // Second answered first, plus a spurious ID and a duplicate
+        let responses = &mut client.transport.responses;
+        responses.push_back(response_bytes(second, 20));
+        responses.push_back(response_bytes(999, 0));
+        responses.push_back(b"garbage".to_vec());
+        responses.push_back(response_bytes(first, 10));
+        responses.push_back(response_bytes(second, 99));
+
+        let socket_of = |result: Result<NetResponse, NetError>| match result {
+            Ok(NetResponse::Ok(result)) => result.socket_id,
+            other => panic!("unexpected response {:?}", other),
+        };
+        assert_eq!(socket_of(client.wait(first)), 10);
+        assert_eq!(socket_of(client.wait(second)), 20);
+        assert_eq!(client.pending(), 0);
+        assert!(client.try_resolve(second).is_none());
+        assert!(client.try_resolve(999).is_none());
+    }
+
+    #[test]
+    fn test_ipc_client_times_out_unanswered_request() {
+        let mut client =
+            NetIpcClient::new(QueueTransport::default()).with_timeout(Duration::from_millis(20));
+        let request_id = client
+            .send(NetOperation::Status(NetStatus { socket_id: 1 }))
+            .unwrap();
+        assert!(client.try_resolve(request_id).is_none());
+
+        assert!(matches!(
+            client.wait(request_id),
+            Err(NetError::ConnectionTimeout)
+        ));
+        assert_eq!(client.pending(), 0);
+
+        // A late response is ignored rather than resurrecting the request
+        client
+            .transport
+            .responses
+            .push_back(response_bytes(request_id, 1));
+        assert!(client.try_resolve(request_id).is_none());
+    }
+
     #[test]
     fn test_protocol_serialization() {
         let tcp = Protocol::Tcp;
@@ -24,6 +24,15 @@ pub enum BraidGenerator {
     Right(u8),
 }
 
+/// Errors from assembling or parsing a braid word
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BraidParseError {
+    /// More than [`BraidWord::MAX_LENGTH`] generators
+    TooLong,
+    /// Token is not `sN` or `sN'` with `N` in `0..=255`
+    InvalidToken,
+}
+
 use crate::RouletteInt;
 #[derive(Debug, Clone, PartialEq, Eq, BraidInvariant)]
 pub struct BraidWord {
@@ -41,6 +50,72 @@ impl BraidWord {
         _homotopy: core::marker::PhantomData,
     };
 
+    /// Maximum number of generators a braid word can hold
+    pub const MAX_LENGTH: usize = 16;
+
+    /// Assemble a braid word from a sequence of generators
+    /// Fails with [`BraidParseError::TooLong`] past [`Self::MAX_LENGTH`] generators
+    pub fn from_generators(generators: &[BraidGenerator]) -> Result<Self, BraidParseError> {
+        if generators.len() > Self::MAX_LENGTH {
+            return Err(BraidParseError::TooLong);
+        }
+        let mut word = Self::IDENTITY;
+        word.generators[..generators.len()].copy_from_slice(generators);
+        word.length = generators.len();
+        Ok(word)
+    }
+
+    /// Parse the text form of a braid word
+    /// Whitespace-separated tokens: `sN` is `σ_N` (`Left(N)`), `sN'` is `σ_N⁻¹` (`Right(N)`)
+    pub fn parse(text: &str) -> Result<Self, BraidParseError> {
+        let mut generators = [BraidGenerator::Left(0); 16];
+        let mut length = 0;
+
+        for token in text.split_whitespace() {
+            if length == Self::MAX_LENGTH {
+                return Err(BraidParseError::TooLong);
+            }
+            let digits = token
+                .strip_prefix('s')
+                .ok_or(BraidParseError::InvalidToken)?;
+            let (digits, inverse) = match digits.strip_suffix('\'') {
+                Some(digits) => (digits, true),
+                None => (digits, false),
+            };
+            if !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(BraidParseError::InvalidToken);
+            }
+            let index: u8 = digits.parse().map_err(|_| BraidParseError::InvalidToken)?;
+            generators[length] = if inverse {
+                BraidGenerator::Right(index)
+            } else {
+                BraidGenerator::Left(index)
+            };
+            length += 1;
+        }
+
+        Self::from_generators(&generators[..length])
+    }
+
+    /// Disassemble into the text form accepted by [`Self::parse`]
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn disassemble(&self) -> alloc::string::String {
+        use core::fmt::Write;
+
+        let mut text = alloc::string::String::new();
+        for (i, generator) in self.generators.iter().take(self.length).enumerate() {
+            if i > 0 {
+                text.push(' ');
+            }
+            let _ = match generator {
+                BraidGenerator::Left(n) => write!(text, "s{n}"),
+                BraidGenerator::Right(n) => write!(text, "s{n}'"),
+            };
+        }
+        text
+    }
+
     /// Create braid word from `RouletteInt` compressed data
     #[must_use] 
     pub fn from_roulette_int(value: &RouletteInt) -> Self {
@@ -258,4 +333,59 @@ mod tests {
         assert_eq!(permutation[2], 1); // Strand 2 moved to position 1
         assert_eq!(permutation[3], 3); // Strand 3 unchanged
     }
+
+    #[test]
+    fn test_braid_word_parse() {
+        let word = BraidWord::parse("s1 s2 s1' s2'").unwrap();
+        assert_eq!(word.length, 4);
+        assert_eq!(
+            &word.generators[..4],
+            &[
+                BraidGenerator::Left(1),
+                BraidGenerator::Left(2),
+                BraidGenerator::Right(1),
+                BraidGenerator::Right(2),
+            ]
+        );
+        assert_eq!(BraidWord::parse("").unwrap(), BraidWord::IDENTITY);
+
+        assert_eq!(
+            BraidWord::parse("s1 t2"),
+            Err(BraidParseError::InvalidToken)
+        );
+        assert_eq!(BraidWord::parse("s"), Err(BraidParseError::InvalidToken));
+        assert_eq!(BraidWord::parse("s+1"), Err(BraidParseError::InvalidToken));
+        assert_eq!(BraidWord::parse("s256"), Err(BraidParseError::InvalidToken));
+    }
+
+    #[test]
+    fn test_braid_word_from_generators_rejects_overlong() {
+        let full = BraidWord::from_generators(&[BraidGenerator::Left(2); 16]).unwrap();
+        assert_eq!(full.length, BraidWord::MAX_LENGTH);
+        assert_eq!(
+            BraidWord::from_generators(&[BraidGenerator::Left(2); 17]),
+            Err(BraidParseError::TooLong)
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_braid_word_disassembly_round_trip() {
+        let word = BraidWord::from_generators(&[
+            BraidGenerator::Left(3),
+            BraidGenerator::Right(0),
+            BraidGenerator::Left(15),
+            BraidGenerator::Right(7),
+        ])
+        .unwrap();
+        let text = word.disassemble();
+        assert_eq!(text, "s3 s0' s15 s7'");
+        assert_eq!(BraidWord::parse(&text).unwrap(), word);
+        assert_eq!(BraidWord::IDENTITY.disassemble(), "");
+
+        let full = BraidWord::from_generators(&[BraidGenerator::Right(1); 16]).unwrap();
+        assert_eq!(BraidWord::parse(&full.disassemble()).unwrap(), full);
+        let too_long = alloc::format!("{} s1", full.disassemble());
+        assert_eq!(BraidWord::parse(&too_long), Err(BraidParseError::TooLong));
+    }
 }
\ No newline at end of file
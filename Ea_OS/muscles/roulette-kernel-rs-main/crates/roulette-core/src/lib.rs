@@ -8,6 +8,9 @@
 #![deny(clippy::all)]
 #![feature(generic_const_exprs)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 /// ROULETTE COMPRESSION SYSTEM
 ///
 /// The Roulette kernel implements a revolutionary compression algorithm inspired by
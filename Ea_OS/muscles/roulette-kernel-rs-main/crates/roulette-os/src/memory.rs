@@ -28,7 +28,7 @@ pub struct MemoryManager {
 impl MemoryManager {
     /// Create a new memory manager
     pub fn new(heap_start: VirtAddr, heap_size: usize) -> Self {
-        let mut allocator = EnhancedAllocator::new(heap_start, heap_size);
+        let mut allocator = EnhancedAllocator::new_default(heap_start, heap_size);
         allocator.initialize();
         Self { allocator }
     }
@@ -227,6 +227,7 @@ impl From<BraidExecutionError> for KernelError {
             BraidExecutionError::NoProgramLoaded => KernelError::KernelNotInitialized,
             BraidExecutionError::ProgramEnd => KernelError::ProgramExecutionFailed,
             BraidExecutionError::InvalidGenerator => KernelError::ProgramExecutionFailed,
+            BraidExecutionError::TimedOut => KernelError::ProgramExecutionFailed,
         }
     }
 }
@@ -17,6 +17,7 @@ extern crate std;
 /// Virtual Machine core functionality for the Roulette Kernel
 use roulette_core::{braid::{BraidWord, BraidGenerator, BraidGroup}, t9_syscalls::{T9SyscallInterpreter, SystemCallResult}};
 use core::alloc::Layout;
+use serde::{Deserialize, Serialize};
 
 pub mod overlap_execution;
 pub mod kernel;
@@ -32,16 +33,33 @@ pub type VirtAddr = usize;
 /// Physical address type
 pub type PhysAddr = usize;
 
+/// End of the region `[start, start + size)`, or `None` if it wraps the address space
+#[must_use]
+pub const fn region_end(start: VirtAddr, size: usize) -> Option<VirtAddr> {
+    start.checked_add(size)
+}
+
+/// Address of block `index` in an array of `block_size` blocks at `base`, or `None` on overflow
+#[must_use]
+pub const fn block_addr(base: VirtAddr, index: usize, block_size: usize) -> Option<VirtAddr> {
+    match index.checked_mul(block_size) {
+        Some(offset) => base.checked_add(offset),
+        None => None,
+    }
+}
+
 /// Memory region descriptor
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct MemoryRegion {
     pub start: VirtAddr,
     pub size: usize,
     pub permissions: MemoryPermissions,
+    #[serde(default)]
+    pub cow: bool, // Shared with a forked process until one of them writes
 }
 
 /// Memory permissions
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum MemoryPermissions {
     ReadOnly,
     ReadWrite,
@@ -49,8 +67,81 @@ pub enum MemoryPermissions {
     ReadWriteExecute,
 }
 
-/// Process state
+impl MemoryPermissions {
+    /// Whether these permissions allow `access`
+    #[must_use]
+    pub const fn allows(self, access: AccessKind) -> bool {
+        match access {
+            AccessKind::Read => true,
+            AccessKind::Write => matches!(self, Self::ReadWrite | Self::ReadWriteExecute),
+            AccessKind::Execute => matches!(self, Self::ReadExecute | Self::ReadWriteExecute),
+        }
+    }
+}
+
+/// Kind of memory access checked against a region's permissions
 #[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccessKind {
+    Read,
+    Write,
+    Execute,
+}
+
+/// Memory access faults
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemoryFault {
+    /// No live process with the given ID
+    NoSuchProcess,
+    /// No region of the process contains the start address
+    Unmapped,
+    /// Access starts in a region but runs past its end (or wraps)
+    OutOfRange,
+    /// Region permissions do not allow the access
+    PermissionDenied,
+    /// No free blocks for a copy-on-write copy
+    OutOfMemory,
+}
+
+/// Inter-process messaging errors
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VmError {
+    /// No process with the given ID
+    NoSuchProcess(Pid),
+    /// The process has terminated and can no longer send or receive
+    ProcessTerminated(Pid),
+    /// The recipient's mailbox has no free slot
+    MailboxFull(Pid),
+    /// Payload exceeds `MESSAGE_BYTES`
+    MessageTooLarge,
+}
+
+/// Messages a process mailbox can hold before sends are rejected
+pub const MAILBOX_SLOTS: usize = 8;
+
+/// Maximum payload bytes carried by one message
+pub const MESSAGE_BYTES: usize = 64;
+
+/// Fixed-size message delivered between processes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Message {
+    pub from: Pid,
+    len: usize,
+    data: [u8; MESSAGE_BYTES],
+}
+
+/// Per-process message queue, oldest message first
+type Mailbox = [Option<Message>; MAILBOX_SLOTS];
+
+impl Message {
+    /// Payload bytes
+    #[must_use]
+    pub fn data(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+}
+
+/// Process state
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ProcessState {
     Running,
     Ready,
@@ -58,23 +149,192 @@ pub enum ProcessState {
     Terminated,
 }
 
+/// Scheduling class used by the class-aware scheduler
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SchedulerClass {
+    /// Runs only when no real-time or interactive process is ready
+    Batch,
+    /// Round-robin whenever no real-time process is ready
+    #[default]
+    Interactive,
+    /// Always preferred; preempted only by a higher real-time priority
+    RealTime,
+}
+
+impl SchedulerClass {
+    /// Selection rank, higher runs first
+    const fn rank(self) -> u8 {
+        match self {
+            SchedulerClass::Batch => 0,
+            SchedulerClass::Interactive => 1,
+            SchedulerClass::RealTime => 2,
+        }
+    }
+}
+
 /// Basic process control block
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Process {
     pub id: Pid,
     pub state: ProcessState,
     pub memory_regions: [Option<MemoryRegion>; 16], // Fixed size for no_std
     pub pc: VirtAddr, // Program counter
     pub sp: VirtAddr, // Stack pointer
+    pub class: SchedulerClass,
+    pub rt_priority: u8, // Only meaningful for `SchedulerClass::RealTime`
+    #[serde(default)]
+    pub cpu_ticks: u64, // Times the scheduler has picked this process
+    #[serde(default)]
+    pub quota: Option<u64>, // Tick budget; exhausted processes are terminated
+    #[serde(default)]
+    pub waiting_on: Option<Pid>, // Process this one is blocked on, if any
+}
+
+impl Process {
+    /// Class-aware scheduling key, higher runs first
+    const fn schedule_key(&self) -> (u8, u8) {
+        match self.class {
+            SchedulerClass::RealTime => (self.class.rank(), self.rt_priority),
+            class => (class.rank(), 0),
+        }
+    }
+
+    /// Whether the process has used up its tick quota
+    fn quota_exhausted(&self) -> bool {
+        self.quota.is_some_and(|quota| self.cpu_ticks >= quota)
+    }
+}
+
+/// Cycle of processes each blocked on the next, as found by
+/// [`VirtualMachine::detect_deadlock`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaitCycle {
+    pub pids: [Pid; 64],
+    pub length: usize,
+}
+
+impl WaitCycle {
+    /// Processes in the cycle, each waiting on the one after it (the last
+    /// waits on the first)
+    #[must_use]
+    pub fn as_slice(&self) -> &[Pid] {
+        &self.pids[..self.length]
+    }
 }
 
 /// Virtual Machine instance
 pub struct VirtualMachine {
     processes: [Option<Process>; 64], // Fixed size process table
+    mailboxes: [Mailbox; 64],         // Indexed like `processes`; not part of snapshots
+    block_refs: [u8; BITMAP_BLOCKS], // Extra copy-on-write sharers per heap block, indexed like the bitmap
     current_pid: Pid,
     memory_allocator: EnhancedAllocator,
 }
 
+/// Serializable VM state for offline inspection or migration between hosts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VmSnapshot {
+    #[serde(with = "serde_array")]
+    pub processes: [Option<Process>; 64],
+    pub current_pid: Pid,
+    pub heap_start: VirtAddr,
+    pub heap_end: VirtAddr,
+    pub block_size: usize,
+    #[serde(with = "serde_array")]
+    pub bitmap: [u8; 4096],
+    #[serde(default = "empty_bitmap", with = "serde_array")]
+    pub heads: [u8; 4096],
+}
+
+/// Bitmap with every block clear, for snapshots that predate `heads`
+const fn empty_bitmap() -> [u8; 4096] {
+    [0; 4096]
+}
+
+/// Reasons a snapshot cannot be imported
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnapshotError {
+    /// Heap bounds or block size are unusable
+    InvalidHeap,
+    /// Two processes share an ID, or an ID was never issued
+    InvalidPid(Pid),
+    /// A live process region lies outside the heap
+    RegionOutOfBounds(Pid),
+    /// A live process region covers blocks the bitmap marks free
+    RegionNotAllocated(Pid),
+    /// Strand permutation is not a permutation of the registers
+    InvalidPermutation,
+}
+
+/// Serde support for fixed arrays longer than serde's built-in 32 elements
+mod serde_array {
+    use core::fmt;
+    use core::marker::PhantomData;
+    use serde::de::{Deserialize, Deserializer, Error, SeqAccess, Visitor};
+    use serde::ser::{Serialize, SerializeTuple, Serializer};
+
+    pub fn serialize<S, T, const N: usize>(array: &[T; N], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        let mut tuple = serializer.serialize_tuple(N)?;
+        for item in array {
+            tuple.serialize_element(item)?;
+        }
+        tuple.end()
+    }
+
+    pub fn deserialize<'de, D, T, const N: usize>(deserializer: D) -> Result<[T; N], D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de> + Copy + Default,
+    {
+        struct ArrayVisitor<T, const N: usize>(PhantomData<T>);
+
+        impl<'de, T, const N: usize> Visitor<'de> for ArrayVisitor<T, N>
+        where
+            T: Deserialize<'de> + Copy + Default,
+        {
+            type Value = [T; N];
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "an array of length {N}")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<[T; N], A::Error> {
+                let mut out = [T::default(); N];
+                for (i, slot) in out.iter_mut().enumerate() {
+                    *slot = seq
+                        .next_element()?
+                        .ok_or_else(|| A::Error::invalid_length(i, &self))?;
+                }
+                Ok(out)
+            }
+        }
+
+        deserializer.deserialize_tuple(N, ArrayVisitor::<T, N>(PhantomData))
+    }
+}
+
+/// Block size used by [`EnhancedAllocator::new_default`]
+pub const DEFAULT_BLOCK_SIZE: usize = 64;
+
+/// Smallest block size [`EnhancedAllocator::new`] accepts
+pub const MIN_BLOCK_SIZE: usize = 8;
+
+/// Blocks the allocator bitmap can track
+const BITMAP_BLOCKS: usize = 4096 * 8;
+
+/// Reasons an allocator cannot be configured
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AllocatorError {
+    /// Block size is not a power of two of at least `MIN_BLOCK_SIZE`
+    InvalidBlockSize(usize),
+    /// The heap holds more blocks than the bitmap can track
+    HeapTooLarge { blocks: usize, capacity: usize },
+}
+
 /// Enhanced memory allocator with deallocation support
 /// Uses a free list to track available memory blocks
 pub struct EnhancedAllocator {
@@ -82,6 +342,7 @@ pub struct EnhancedAllocator {
     heap_end: VirtAddr,
     block_size: usize,
     bitmap: [u8; 4096], // Each bit represents a block (up to 32K blocks)
+    heads: [u8; 4096],  // Set for the first block of each live allocation
 }
 
 /// Free memory block header (stored at the start of each free block)
@@ -133,6 +394,30 @@ impl BraidCPU {
         }
     }
 
+    /// Capture the strand permutation and program counter
+    #[must_use]
+    pub fn snapshot(&self) -> BraidCpuSnapshot {
+        BraidCpuSnapshot {
+            strand_permutation: self.strand_permutation,
+            pc: self.pc,
+        }
+    }
+
+    /// Restore register state captured by [`BraidCPU::snapshot`]
+    pub fn restore(&mut self, snapshot: BraidCpuSnapshot) -> Result<(), SnapshotError> {
+        let mut register_positions = [usize::MAX; 16];
+        for (position, &register) in snapshot.strand_permutation.iter().enumerate() {
+            match register_positions.get_mut(register) {
+                Some(slot) if *slot == usize::MAX => *slot = position,
+                _ => return Err(SnapshotError::InvalidPermutation),
+            }
+        }
+        self.strand_permutation = snapshot.strand_permutation;
+        self.register_positions = register_positions;
+        self.pc = snapshot.pc;
+        Ok(())
+    }
+
     /// Load a braid program for execution
     pub fn load_program(&mut self, program: BraidWord) {
         self.program = Some(program);
@@ -164,6 +449,28 @@ impl BraidCPU {
         }
     }
 
+    /// Step until the program ends, returning the number of steps taken
+    ///
+    /// Fails with `TimedOut` if the program has not ended after `max_steps`,
+    /// bounding execution time for untrusted programs.
+    pub fn run(&mut self, max_steps: usize) -> Result<usize, BraidExecutionError> {
+        let mut steps = 0;
+        loop {
+            let finished = match &self.program {
+                Some(program) => self.pc >= program.length,
+                None => return Err(BraidExecutionError::NoProgramLoaded),
+            };
+            if finished {
+                return Ok(steps);
+            }
+            if steps == max_steps {
+                return Err(BraidExecutionError::TimedOut);
+            }
+            self.step()?;
+            steps += 1;
+        }
+    }
+
     /// Apply a single braid generator to a permutation
     fn apply_generator_to_permutation(generator: BraidGenerator, mut permutation: [usize; 16]) -> [usize; 16] {
         match generator {
@@ -197,6 +504,11 @@ impl BraidCPU {
                 }
             }
         }
+        #[cfg(feature = "invariant_checks")]
+        debug_assert!(
+            self.validate_invariant(),
+            "register_positions out of sync after {generator:?}"
+        );
     }
 
     /// Get register value (strand position of register index) - O(1)
@@ -220,15 +532,50 @@ impl BraidCPU {
         // Update inverse mapping
         self.register_positions[old_register] = old_position;
         self.register_positions[register_index] = strand_position;
+
+        #[cfg(feature = "invariant_checks")]
+        debug_assert!(
+            self.validate_invariant(),
+            "register_positions out of sync after set_register"
+        );
+    }
+
+    /// Check that `register_positions` is the inverse of `strand_permutation`
+    #[must_use]
+    pub fn validate_invariant(&self) -> bool {
+        self.strand_permutation
+            .iter()
+            .enumerate()
+            .all(|(position, &register)| self.register_positions.get(register) == Some(&position))
+    }
+
+    /// Rebuild `register_positions` from `strand_permutation`
+    /// Repairs a stale inverse; if `strand_permutation` is not itself a permutation,
+    /// `validate_invariant` still reports the corruption afterwards
+    pub fn recompute_inverse(&mut self) {
+        for (position, &register) in self.strand_permutation.iter().enumerate() {
+            if let Some(slot) = self.register_positions.get_mut(register) {
+                *slot = position;
+            }
+        }
     }
 }
 
+/// Serializable braid CPU register state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BraidCpuSnapshot {
+    pub strand_permutation: [usize; 16],
+    pub pc: usize,
+}
+
 /// Braid execution errors
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BraidExecutionError {
     NoProgramLoaded,
     ProgramEnd,
     InvalidGenerator,
+    /// `BraidCPU::run` hit its step cap before the program ended
+    TimedOut,
 }
 
 /// Braid-based process that executes using strand permutations
@@ -247,27 +594,77 @@ impl Default for BraidCPU {
 }
 
 impl EnhancedAllocator {
+    /// Allocator with `DEFAULT_BLOCK_SIZE` blocks
+    ///
+    /// Heap beyond what the bitmap can track is left unused.
     #[must_use] 
-    pub const fn new(heap_start: VirtAddr, heap_size: usize) -> Self {
+    pub const fn new_default(heap_start: VirtAddr, heap_size: usize) -> Self {
+        Self::with_block_size(heap_start, heap_size, DEFAULT_BLOCK_SIZE)
+    }
+
+    /// Allocator handing out `block_size`-byte blocks
+    ///
+    /// Small blocks waste less on small allocations; large ones let the
+    /// fixed-size bitmap cover a bigger heap. Fails if `block_size` is not a
+    /// power of two of at least `MIN_BLOCK_SIZE`, or if the heap has more
+    /// blocks than the bitmap can track.
+    pub const fn new(
+        heap_start: VirtAddr,
+        heap_size: usize,
+        block_size: usize,
+    ) -> Result<Self, AllocatorError> {
+        if block_size < MIN_BLOCK_SIZE || !block_size.is_power_of_two() {
+            return Err(AllocatorError::InvalidBlockSize(block_size));
+        }
+        let allocator = Self::with_block_size(heap_start, heap_size, block_size);
+        let blocks = (allocator.heap_end - allocator.heap_start) / block_size;
+        if blocks > BITMAP_BLOCKS {
+            return Err(AllocatorError::HeapTooLarge {
+                blocks,
+                capacity: BITMAP_BLOCKS,
+            });
+        }
+        Ok(allocator)
+    }
+
+    const fn with_block_size(heap_start: VirtAddr, heap_size: usize, block_size: usize) -> Self {
+        // A heap that would wrap the address space is clamped to its top
+        let heap_end = match region_end(heap_start, heap_size) {
+            Some(end) => end,
+            None => VirtAddr::MAX,
+        };
         Self {
             heap_start,
-            heap_end: heap_start + heap_size,
-            block_size: 64, // 64 bytes per block (tunable)
+            heap_end,
+            block_size,
             bitmap: [0; 4096],
+            heads: [0; 4096],
         }
     }
 
+    /// Bytes per allocation block
+    #[must_use]
+    pub const fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Number of blocks tracked by the bitmap
+    fn total_blocks(&self) -> usize {
+        ((self.heap_end - self.heap_start) / self.block_size).min(self.bitmap.len() * 8)
+    }
+
     /// Initialize the free list with one large block
     pub fn initialize(&mut self) {
         // Mark all blocks as free
         for byte in &mut self.bitmap {
             *byte = 0;
         }
+        self.heads = [0; 4096];
     }
 
     pub fn allocate(&mut self, layout: Layout) -> Option<VirtAddr> {
         let blocks_needed = layout.size().div_ceil(self.block_size);
-        let total_blocks = (self.heap_end - self.heap_start) / self.block_size;
+        let total_blocks = self.total_blocks();
         if blocks_needed > total_blocks {
             return None;
         }
@@ -281,6 +678,7 @@ impl EnhancedAllocator {
                     continue 'outer;
                 }
             }
+            let addr = block_addr(self.heap_start, i, self.block_size)?;
             // Mark blocks as allocated
             for j in 0..blocks_needed {
                 let idx = i + j;
@@ -288,7 +686,10 @@ impl EnhancedAllocator {
                 let bit = idx % 8;
                 self.bitmap[byte] |= 1 << bit;
             }
-            return Some(self.heap_start + i * self.block_size);
+            if blocks_needed > 0 {
+                self.heads[i / 8] |= 1 << (i % 8);
+            }
+            return Some(addr);
         }
         None
     }
@@ -298,7 +699,7 @@ impl EnhancedAllocator {
     #[cfg(kani)]
     #[kani::proof]
     fn verify_allocation_safety() {
-        let mut allocator = EnhancedAllocator::new(0x1000, 0x10000);
+        let mut allocator = EnhancedAllocator::new_default(0x1000, 0x10000);
         let layout = Layout::from_size_align(64, 8).unwrap();
         let addr = allocator.allocate(layout);
         assert!(addr.is_some());
@@ -306,33 +707,72 @@ impl EnhancedAllocator {
         assert!(addr.unwrap() < allocator.heap_end);
     }
 
-    pub fn deallocate(&mut self, ptr: VirtAddr, layout: Layout) {
-        if ptr < self.heap_start || ptr >= self.heap_end {
-            return; // Invalid pointer
-        }
+    /// Free an allocation made with `layout`
+    ///
+    /// Returns `false` and leaves the bitmap untouched unless `ptr` starts a
+    /// live allocation of exactly `layout`'s size, so a double free or a
+    /// mismatched layout cannot free a neighbour's blocks or leak a tail.
+    pub fn deallocate(&mut self, ptr: VirtAddr, layout: Layout) -> bool {
         let blocks_needed = layout.size().div_ceil(self.block_size);
+        if blocks_needed == 0 {
+            return true; // Zero-sized allocations own no blocks
+        }
+        if ptr < self.heap_start
+            || ptr >= self.heap_end
+            || !(ptr - self.heap_start).is_multiple_of(self.block_size)
+        {
+            return false; // Invalid pointer
+        }
         let start_block = (ptr - self.heap_start) / self.block_size;
-        let total_blocks = (self.heap_end - self.heap_start) / self.block_size;
-        if start_block + blocks_needed > total_blocks {
-            return; // Out of bounds
+        let total_blocks = self.total_blocks();
+        let Some(end_block) = start_block
+            .checked_add(blocks_needed)
+            .filter(|&end| end <= total_blocks)
+        else {
+            return false; // Out of bounds
+        };
+        let exact = Self::bit(&self.heads, start_block)
+            && (start_block..end_block).all(|idx| Self::bit(&self.bitmap, idx))
+            && (start_block + 1..end_block).all(|idx| !Self::bit(&self.heads, idx))
+            // The allocation must not continue past the layout's last block
+            && (end_block == total_blocks
+                || !Self::bit(&self.bitmap, end_block)
+                || Self::bit(&self.heads, end_block));
+        if !exact {
+            return false; // Double-free or mismatched layout
         }
         // Mark blocks as free
-        for j in 0..blocks_needed {
-            let idx = start_block + j;
-            let byte = idx / 8;
-            let bit = idx % 8;
-            if (self.bitmap[byte] & (1 << bit)) == 0 {
-                return; // Double-free or invalid pointer
-            }
-            self.bitmap[byte] &= !(1 << bit);
+        for idx in start_block..end_block {
+            self.bitmap[idx / 8] &= !(1 << (idx % 8));
         }
+        self.heads[start_block / 8] &= !(1 << (start_block % 8));
+        true
+    }
+
+    /// Whether block `idx` is set in `map`
+    const fn bit(map: &[u8; 4096], idx: usize) -> bool {
+        map[idx / 8] & (1 << (idx % 8)) != 0
     }
 
     // No coalescing needed with bitmap allocator
 
+    /// Whether every block of `[addr, addr + size)` is inside the heap and allocated
+    fn is_allocated(&self, addr: VirtAddr, size: usize) -> bool {
+        if addr < self.heap_start || region_end(addr, size).is_none_or(|end| end > self.heap_end) {
+            return false;
+        }
+        let start_block = (addr - self.heap_start) / self.block_size;
+        let blocks = size.div_ceil(self.block_size);
+        let Some(end_block) = start_block.checked_add(blocks) else {
+            return false;
+        };
+        end_block <= self.total_blocks()
+            && (start_block..end_block).all(|idx| self.bitmap[idx / 8] & (1 << (idx % 8)) != 0)
+    }
+
     #[must_use] 
     pub fn free_memory(&self) -> usize {
-        let total_blocks = (self.heap_end - self.heap_start) / self.block_size;
+        let total_blocks = self.total_blocks();
         let mut free_blocks = 0;
         for i in 0..total_blocks {
             let byte = i / 8;
@@ -343,24 +783,38 @@ impl EnhancedAllocator {
         }
         free_blocks * self.block_size
     }
+
+    /// Bitmap indices of the blocks covering `[addr, addr + size)`
+    fn block_range(&self, addr: VirtAddr, size: usize) -> core::ops::Range<usize> {
+        let start = (addr - self.heap_start) / self.block_size;
+        let end = start
+            .saturating_add(size.div_ceil(self.block_size))
+            .min(self.total_blocks());
+        start.min(end)..end
+    }
 }
 
 impl VirtualMachine {
     /// Create a new virtual machine instance
     #[must_use] 
     pub fn new(heap_start: VirtAddr, heap_size: usize) -> Self {
-        let mut allocator = EnhancedAllocator::new(heap_start, heap_size);
+        let mut allocator = EnhancedAllocator::new_default(heap_start, heap_size);
         allocator.initialize();
         allocator.initialize(); // Initialize the free list
 
         Self {
             processes: [None; 64],
+            mailboxes: [[None; MAILBOX_SLOTS]; 64],
+            block_refs: [0; BITMAP_BLOCKS],
             current_pid: 0,
             memory_allocator: allocator,
         }
     }
 
     /// Create a new process
+    ///
+    /// Returns `None` when the process table is full or the heap has no room
+    /// for the stack.
     pub fn create_process(&mut self, entry_point: VirtAddr, stack_size: usize) -> Option<Pid> {
         // Always guarantee at least one process slot is available
         if self.processes.iter().all(core::option::Option::is_some) {
@@ -375,21 +829,28 @@ impl VirtualMachine {
             }
         }
         let slot = self.processes.iter().position(core::option::Option::is_none)?;
-        let pid = self.current_pid;
-        self.current_pid += 1;
         let stack_layout = Layout::from_size_align(stack_size, 16).ok()?;
-        let stack_addr = match self.memory_allocator.allocate(stack_layout) {
-            Some(addr) => addr,
-            None => self.memory_allocator.heap_start, // Fallback: always return a valid address
+        let stack_addr = self.memory_allocator.allocate(stack_layout)?;
+        let Some(sp) = region_end(stack_addr, stack_size) else {
+            self.memory_allocator.deallocate(stack_addr, stack_layout);
+            return None;
         };
+        let pid = self.current_pid;
+        self.current_pid += 1;
         let process = Process {
             id: pid,
             state: ProcessState::Ready,
             memory_regions: [None; 16],
             pc: entry_point,
-            sp: stack_addr + stack_size,
+            sp,
+            class: SchedulerClass::default(),
+            rt_priority: 0,
+            cpu_ticks: 0,
+            quota: None,
+            waiting_on: None,
         };
         self.processes[slot] = Some(process);
+        self.mailboxes[slot] = [None; MAILBOX_SLOTS];
         Some(pid)
     }
 
@@ -406,17 +867,8 @@ impl VirtualMachine {
 
     /// Schedule the next process (simple round-robin)
     pub fn schedule_next(&mut self) -> Option<Pid> {
-        // Find current running process index
-        let current_idx = self.processes.iter().position(|p| {
-            p.as_ref().is_some_and(|proc| proc.state == ProcessState::Running)
-        });
-
-        // Set current running process to ready
-        if let Some(idx) = current_idx {
-            if let Some(proc) = &mut self.processes[idx] {
-                proc.state = ProcessState::Ready;
-            }
-        }
+        // Return the running process to ready, or terminate it if over quota
+        let current_idx = self.preempt_current();
 
         // Start searching from the next process after the current one (or from beginning if none running)
         let start_idx = current_idx.map_or(0, |i| i + 1);
@@ -426,6 +878,7 @@ impl VirtualMachine {
             if let Some(proc) = &mut self.processes[i] {
                 if proc.state == ProcessState::Ready {
                     proc.state = ProcessState::Running;
+                    proc.cpu_ticks += 1;
                     return Some(proc.id);
                 }
             }
@@ -436,6 +889,7 @@ impl VirtualMachine {
             if let Some(proc) = &mut self.processes[i] {
                 if proc.state == ProcessState::Ready {
                     proc.state = ProcessState::Running;
+                    proc.cpu_ticks += 1;
                     return Some(proc.id);
                 }
             }
@@ -444,26 +898,235 @@ impl VirtualMachine {
         None
     }
 
-    /// Terminate a process and deallocate its resources
-    pub fn terminate_process(&mut self, pid: Pid) -> bool {
-        // First, collect region info without mutably borrowing self
-        let mut addrs = [0usize; 16];
-        let mut layouts = [Layout::new::<u8>(); 16];
-        let mut count = 0;
-        if let Some(process_ref) = self.get_process(pid) {
-            for mem_region in process_ref.memory_regions.iter().flatten() {
-                if count < 16 {
-                    addrs[count] = mem_region.start;
-                    layouts[count] = Layout::from_size_align(mem_region.size, 16).unwrap_or(Layout::new::<u8>());
-                    count += 1;
+    /// Move the running process back to ready, or terminate it if it has
+    /// exhausted its quota; returns its slot index
+    fn preempt_current(&mut self) -> Option<usize> {
+        let idx = self.processes.iter().position(|p| {
+            p.as_ref()
+                .is_some_and(|proc| proc.state == ProcessState::Running)
+        })?;
+        let proc = self.processes[idx].as_mut()?;
+        if proc.quota_exhausted() {
+            let pid = proc.id;
+            self.terminate_process(pid);
+        } else {
+            proc.state = ProcessState::Ready;
+        }
+        Some(idx)
+    }
+
+    /// Limit a process to `quota` scheduler ticks, or lift the limit with `None`
+    ///
+    /// A process at or over its quota is terminated the next time it is
+    /// descheduled.
+    pub fn set_quota(&mut self, pid: Pid, quota: Option<u64>) -> bool {
+        match self.get_process_mut(pid) {
+            Some(process) => {
+                process.quota = quota;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Scheduler ticks consumed by a process (0 for unknown PIDs)
+    #[must_use]
+    pub fn usage(&self, pid: Pid) -> u64 {
+        self.get_process(pid).map_or(0, |process| process.cpu_ticks)
+    }
+
+    /// Queue a copy of `data` in the mailbox of `to`
+    ///
+    /// Fails without blocking when the mailbox is full; the sender may
+    /// retry after the recipient drains it.
+    pub fn send_message(&mut self, from: Pid, to: Pid, data: &[u8]) -> Result<(), VmError> {
+        if data.len() > MESSAGE_BYTES {
+            return Err(VmError::MessageTooLarge);
+        }
+        self.live_slot(from)?;
+        let recipient = self.live_slot(to)?;
+        let slot = self.mailboxes[recipient]
+            .iter_mut()
+            .find(|slot| slot.is_none())
+            .ok_or(VmError::MailboxFull(to))?;
+        let mut message = Message {
+            from,
+            len: data.len(),
+            data: [0; MESSAGE_BYTES],
+        };
+        message.data[..data.len()].copy_from_slice(data);
+        *slot = Some(message);
+        Ok(())
+    }
+
+    /// Take the oldest message from the mailbox of `pid`
+    pub fn recv_message(&mut self, pid: Pid) -> Option<Message> {
+        let mailbox = &mut self.mailboxes[self.live_slot(pid).ok()?];
+        let message = mailbox[0].take()?;
+        mailbox.rotate_left(1);
+        Some(message)
+    }
+
+    /// Table slot of a process that has not terminated
+    fn live_slot(&self, pid: Pid) -> Result<usize, VmError> {
+        let slot = self
+            .processes
+            .iter()
+            .position(|p| p.as_ref().is_some_and(|proc| proc.id == pid))
+            .ok_or(VmError::NoSuchProcess(pid))?;
+        match self.processes[slot] {
+            Some(proc) if proc.state == ProcessState::Terminated => {
+                Err(VmError::ProcessTerminated(pid))
+            }
+            _ => Ok(slot),
+        }
+    }
+
+    /// Block a process until `target` wakes it, recording the wait for
+    /// deadlock detection
+    pub fn block_on(&mut self, pid: Pid, target: Pid) -> bool {
+        match self.get_process_mut(pid) {
+            Some(process) if process.state != ProcessState::Terminated => {
+                process.state = ProcessState::Blocked;
+                process.waiting_on = Some(target);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Return a blocked process to the ready queue
+    pub fn unblock(&mut self, pid: Pid) -> bool {
+        match self.get_process_mut(pid) {
+            Some(process) if process.state == ProcessState::Blocked => {
+                process.state = ProcessState::Ready;
+                process.waiting_on = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Find a cycle in the wait-for graph of blocked processes
+    ///
+    /// Each blocked process waits on at most one other, so following
+    /// `waiting_on` edges from every blocked process finds any cycle in at
+    /// most one pass over the table.
+    #[must_use]
+    pub fn detect_deadlock(&self) -> Option<WaitCycle> {
+        const UNVISITED: usize = usize::MAX;
+        let len = self.processes.len();
+        // Slot each blocked process waits on, if that process is also blocked
+        let mut next = [None; 64];
+        for (slot, edge) in next.iter_mut().enumerate() {
+            let Some(target) = self.processes[slot]
+                .filter(|proc| proc.state == ProcessState::Blocked)
+                .and_then(|proc| proc.waiting_on)
+            else {
+                continue;
+            };
+            *edge = self.processes.iter().position(|p| {
+                p.is_some_and(|proc| proc.id == target && proc.state == ProcessState::Blocked)
+            });
+        }
+
+        // Walk from each slot, tagging slots with the walk that reached them;
+        // meeting the current walk's tag again closes a cycle
+        let mut walk_of = [UNVISITED; 64];
+        for start in 0..len {
+            let mut slot = start;
+            while walk_of[slot] == UNVISITED {
+                walk_of[slot] = start;
+                match next[slot] {
+                    Some(target) => slot = target,
+                    None => break,
+                }
+            }
+            if walk_of[slot] != start || next[slot].is_none() {
+                continue;
+            }
+            let mut cycle = WaitCycle {
+                pids: [0; 64],
+                length: 0,
+            };
+            let first = slot;
+            loop {
+                cycle.pids[cycle.length] = self.processes[slot].map_or(0, |proc| proc.id);
+                cycle.length += 1;
+                slot = next[slot]?;
+                if slot == first {
+                    return Some(cycle);
+                }
+            }
+        }
+        None
+    }
+
+    /// Set the scheduling class of a process
+    pub fn set_class(&mut self, pid: Pid, class: SchedulerClass) -> bool {
+        match self.get_process_mut(pid) {
+            Some(process) => {
+                process.class = class;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Set the real-time priority of a process (higher preempts lower)
+    pub fn set_rt_priority(&mut self, pid: Pid, priority: u8) -> bool {
+        match self.get_process_mut(pid) {
+            Some(process) => {
+                process.rt_priority = priority;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Schedule the next process by class: real-time, then interactive, then batch
+    ///
+    /// Processes sharing the best key run round-robin. A running real-time
+    /// process keeps the CPU unless a ready one has a higher priority or the
+    /// same priority (which alternates with it).
+    pub fn schedule_next_by_class(&mut self) -> Option<Pid> {
+        let current_idx = self.preempt_current();
+
+        let best = self
+            .processes
+            .iter()
+            .flatten()
+            .filter(|proc| proc.state == ProcessState::Ready)
+            .map(Process::schedule_key)
+            .max()?;
+
+        // Round-robin within the best key, starting after the current process
+        let len = self.processes.len();
+        let start_idx = current_idx.map_or(0, |i| i + 1);
+        for offset in 0..len {
+            if let Some(proc) = &mut self.processes[(start_idx + offset) % len] {
+                if proc.state == ProcessState::Ready && proc.schedule_key() == best {
+                    proc.state = ProcessState::Running;
+                    proc.cpu_ticks += 1;
+                    return Some(proc.id);
                 }
             }
-        } else {
-            return false;
         }
-        // Deallocate memory regions first
-        for i in 0..count {
-            self.memory_allocator.deallocate(addrs[i], layouts[i]);
+
+        None
+    }
+
+    /// Terminate a process and deallocate its resources
+    pub fn terminate_process(&mut self, pid: Pid) -> bool {
+        let Some(process_ref) = self.get_process(pid) else {
+            return false;
+        };
+        // A terminated process already gave its regions back
+        if process_ref.state != ProcessState::Terminated {
+            let regions = process_ref.memory_regions;
+            for region in regions.into_iter().flatten() {
+                self.release_region(region);
+            }
         }
         // Now mutably borrow and update process state
         if let Some(process) = self.get_process_mut(pid) {
@@ -479,7 +1142,11 @@ impl VirtualMachine {
         let layout = Layout::from_size_align(size, 16).ok()?;
         let addr = self.memory_allocator.allocate(layout)?;
         // Integrity assertion: allocation must be within heap bounds
-        assert!(addr >= self.memory_allocator.heap_start && addr + size <= self.memory_allocator.heap_end, "Process memory allocation out of bounds");
+        assert!(
+            addr >= self.memory_allocator.heap_start
+                && region_end(addr, size).is_some_and(|end| end <= self.memory_allocator.heap_end),
+            "Process memory allocation out of bounds"
+        );
         if let Some(process) = self.get_process_mut(pid) {
             // Find free memory region slot
             for region in &mut process.memory_regions {
@@ -488,6 +1155,7 @@ impl VirtualMachine {
                         start: addr,
                         size,
                         permissions,
+                        cow: false,
                     });
                     return Some(addr);
                 }
@@ -501,15 +1169,20 @@ impl VirtualMachine {
         let region_info = if let Some(process) = self.get_process(pid) {
             process.memory_regions.iter().enumerate()
                 .find(|(_, region)| region.as_ref().is_some_and(|r| r.start == addr))
-                .map(|(idx, region)| (idx, region.as_ref().unwrap().size))
+                .map(|(idx, region)| (idx, region.unwrap()))
         } else {
             None
         };
-        if let Some((region_idx, size)) = region_info {
-            let layout = Layout::from_size_align(size, 16).unwrap_or(Layout::new::<u8>());
+        if let Some((region_idx, region)) = region_info {
+            let size = region.size;
             // Integrity assertion: deallocation must be within heap bounds
-            assert!(addr >= self.memory_allocator.heap_start && addr + size <= self.memory_allocator.heap_end, "Process memory deallocation out of bounds");
-            self.memory_allocator.deallocate(addr, layout);
+            assert!(
+                addr >= self.memory_allocator.heap_start
+                    && region_end(addr, size)
+                        .is_some_and(|end| end <= self.memory_allocator.heap_end),
+                "Process memory deallocation out of bounds"
+            );
+            self.release_region(region);
             // Now remove the region from the process
             if let Some(process) = self.get_process_mut(pid) {
                 process.memory_regions[region_idx] = None;
@@ -520,6 +1193,252 @@ impl VirtualMachine {
         }
     }
 
+    /// Return a region's blocks to the allocator, or only drop this
+    /// process's reference to blocks another copy-on-write sharer still maps
+    fn release_region(&mut self, region: MemoryRegion) {
+        if region.cow {
+            // Sharers map whole regions, so all of its blocks share one count
+            let blocks = self.memory_allocator.block_range(region.start, region.size);
+            if blocks.clone().any(|block| self.block_refs[block] > 0) {
+                for block in blocks {
+                    self.block_refs[block] = self.block_refs[block].saturating_sub(1);
+                }
+                return;
+            }
+        }
+        // Regions are allocated with this layout, so a size it rejects was
+        // never allocated and there is nothing to free
+        if let Ok(layout) = Layout::from_size_align(region.size, 16) {
+            self.memory_allocator.deallocate(region.start, layout);
+        }
+    }
+
+    /// Create a child of `parent` that shares its memory regions copy-on-write
+    ///
+    /// Parent and child map the same blocks until one of them calls
+    /// [`VirtualMachine::write_region`]. The child starts ready at the
+    /// parent's program counter and stack pointer, with no ticks used and an
+    /// empty mailbox.
+    pub fn fork(&mut self, parent: Pid) -> Option<Pid> {
+        let parent_slot = self.live_slot(parent).ok()?;
+        let slot = self
+            .processes
+            .iter()
+            .position(core::option::Option::is_none)?;
+        let mut regions = self.processes[parent_slot]?.memory_regions;
+        // Refuse rather than overflow a block's sharer count
+        let saturated = regions.iter().flatten().any(|region| {
+            self.memory_allocator
+                .block_range(region.start, region.size)
+                .any(|block| self.block_refs[block] == u8::MAX)
+        });
+        if saturated {
+            return None;
+        }
+        for region in regions.iter_mut().flatten() {
+            region.cow = true;
+            for block in self.memory_allocator.block_range(region.start, region.size) {
+                self.block_refs[block] += 1;
+            }
+        }
+
+        let pid = self.current_pid;
+        self.current_pid += 1;
+        let parent_process = self.processes[parent_slot].as_mut()?;
+        parent_process.memory_regions = regions;
+        let child = Process {
+            id: pid,
+            state: ProcessState::Ready,
+            cpu_ticks: 0,
+            waiting_on: None,
+            ..*parent_process
+        };
+        self.processes[slot] = Some(child);
+        self.mailboxes[slot] = [None; MAILBOX_SLOTS];
+        Some(pid)
+    }
+
+    /// Check that `pid` may write `[addr, addr + len)` and return the address
+    /// to write at
+    ///
+    /// A region still shared copy-on-write first moves to freshly allocated
+    /// blocks, leaving the other sharers on the originals. The VM tracks
+    /// regions rather than their contents, so the new blocks stand in for the
+    /// copy and `addr` is translated into them.
+    pub fn write_region(
+        &mut self,
+        pid: Pid,
+        addr: VirtAddr,
+        len: usize,
+    ) -> Result<VirtAddr, MemoryFault> {
+        self.check_access(pid, addr, len, AccessKind::Write)?;
+        let process = self.get_process(pid).ok_or(MemoryFault::NoSuchProcess)?;
+        let (index, region) = process
+            .memory_regions
+            .iter()
+            .enumerate()
+            .find_map(|(index, region)| {
+                region
+                    .filter(|region| {
+                        addr >= region.start
+                            && region_end(region.start, region.size).is_some_and(|end| addr < end)
+                    })
+                    .map(|region| (index, region))
+            })
+            .ok_or(MemoryFault::Unmapped)?;
+        if !region.cow {
+            return Ok(addr);
+        }
+
+        let mut owned = MemoryRegion {
+            cow: false,
+            ..region
+        };
+        let shared = self
+            .memory_allocator
+            .block_range(region.start, region.size)
+            .any(|block| self.block_refs[block] > 0);
+        if shared {
+            let layout =
+                Layout::from_size_align(region.size, 16).map_err(|_| MemoryFault::OutOfMemory)?;
+            owned.start = self
+                .memory_allocator
+                .allocate(layout)
+                .ok_or(MemoryFault::OutOfMemory)?;
+            self.release_region(region);
+        }
+        if let Some(process) = self.get_process_mut(pid) {
+            process.memory_regions[index] = Some(owned);
+        }
+        Ok(owned.start + (addr - region.start))
+    }
+
+    /// Export the process table and allocator state
+    #[must_use]
+    pub fn export_state(&self) -> VmSnapshot {
+        VmSnapshot {
+            processes: self.processes,
+            current_pid: self.current_pid,
+            heap_start: self.memory_allocator.heap_start,
+            heap_end: self.memory_allocator.heap_end,
+            block_size: self.memory_allocator.block_size,
+            bitmap: self.memory_allocator.bitmap,
+            heads: self.memory_allocator.heads,
+        }
+    }
+
+    /// Rebuild a VM from an exported snapshot
+    ///
+    /// Every region of a non-terminated process must lie in the heap and
+    /// cover only blocks the bitmap marks allocated.
+    pub fn import_state(snapshot: VmSnapshot) -> Result<Self, SnapshotError> {
+        let block_size = snapshot.block_size;
+        if block_size < MIN_BLOCK_SIZE
+            || !block_size.is_power_of_two()
+            || snapshot.heap_end < snapshot.heap_start
+        {
+            return Err(SnapshotError::InvalidHeap);
+        }
+        let allocator = EnhancedAllocator {
+            heap_start: snapshot.heap_start,
+            heap_end: snapshot.heap_end,
+            block_size: snapshot.block_size,
+            bitmap: snapshot.bitmap,
+            heads: snapshot.heads,
+        };
+
+        for (i, process) in snapshot.processes.iter().enumerate() {
+            let Some(process) = process else {
+                continue;
+            };
+            let duplicate = snapshot.processes[..i]
+                .iter()
+                .flatten()
+                .any(|other| other.id == process.id);
+            if duplicate || process.id >= snapshot.current_pid {
+                return Err(SnapshotError::InvalidPid(process.id));
+            }
+            if process.state == ProcessState::Terminated {
+                continue; // Regions were already returned to the allocator
+            }
+            for region in process.memory_regions.iter().flatten() {
+                if !allocator.is_allocated(region.start, region.size) {
+                    let in_heap = region.start >= allocator.heap_start
+                        && region_end(region.start, region.size)
+                            .is_some_and(|end| end <= allocator.heap_end);
+                    return Err(if in_heap {
+                        SnapshotError::RegionNotAllocated(process.id)
+                    } else {
+                        SnapshotError::RegionOutOfBounds(process.id)
+                    });
+                }
+            }
+        }
+
+        let mut vm = Self {
+            processes: snapshot.processes,
+            mailboxes: [[None; MAILBOX_SLOTS]; 64],
+            block_refs: [0; BITMAP_BLOCKS],
+            current_pid: snapshot.current_pid,
+            memory_allocator: allocator,
+        };
+        // Rebuild sharer counts from the copy-on-write regions still mapped,
+        // and mark region starts missing from snapshots that predate `heads`
+        for process in vm
+            .processes
+            .iter()
+            .flatten()
+            .filter(|p| p.state != ProcessState::Terminated)
+        {
+            for region in process.memory_regions.iter().flatten() {
+                let blocks = vm.memory_allocator.block_range(region.start, region.size);
+                if !blocks.is_empty() {
+                    vm.memory_allocator.heads[blocks.start / 8] |= 1 << (blocks.start % 8);
+                }
+                if region.cow {
+                    for block in blocks {
+                        vm.block_refs[block] = vm.block_refs[block].saturating_add(1);
+                    }
+                }
+            }
+        }
+        // The first mapping of each block is its owner, not an extra sharer
+        for refs in &mut vm.block_refs {
+            *refs = refs.saturating_sub(1);
+        }
+        Ok(vm)
+    }
+
+    /// Check that `pid` may perform `access` on `[addr, addr + len)`
+    pub fn check_access(
+        &self,
+        pid: Pid,
+        addr: VirtAddr,
+        len: usize,
+        access: AccessKind,
+    ) -> Result<(), MemoryFault> {
+        let process = self.get_process(pid).ok_or(MemoryFault::NoSuchProcess)?;
+        let region = process
+            .memory_regions
+            .iter()
+            .flatten()
+            .find(|region| {
+                addr >= region.start
+                    && region_end(region.start, region.size).is_some_and(|end| addr < end)
+            })
+            .ok_or(MemoryFault::Unmapped)?;
+        let within = region_end(addr, len)
+            .zip(region_end(region.start, region.size))
+            .is_some_and(|(end, limit)| end <= limit);
+        if !within {
+            return Err(MemoryFault::OutOfRange);
+        }
+        if !region.permissions.allows(access) {
+            return Err(MemoryFault::PermissionDenied);
+        }
+        Ok(())
+    }
+
     /// Get memory statistics
     #[must_use] 
     pub fn get_memory_stats(&self) -> (usize, usize) {
@@ -558,9 +1477,15 @@ impl VirtualMachine {
             memory_regions: [None; 16],
             pc: 0,
             sp: 0,
+            class: SchedulerClass::default(),
+            rt_priority: 0,
+            cpu_ticks: 0,
+            quota: None,
+            waiting_on: None,
         };
 
         self.processes[slot] = Some(regular_process);
+        self.mailboxes[slot] = [None; MAILBOX_SLOTS];
         Some(pid)
     }
 
@@ -608,6 +1533,371 @@ mod tests {
         assert!(vm.create_process(0x2000, 0x1000).is_some());
     }
 
+    #[test]
+    fn test_address_helpers_detect_overflow() {
+        assert_eq!(region_end(usize::MAX - 1, 1), Some(usize::MAX));
+        assert_eq!(region_end(usize::MAX - 1, 2), None);
+        assert_eq!(region_end(usize::MAX, usize::MAX), None);
+
+        assert_eq!(block_addr(0x1000, 3, 64), Some(0x1000 + 192));
+        assert_eq!(block_addr(usize::MAX - 127, 1, 64), Some(usize::MAX - 63));
+        assert_eq!(block_addr(usize::MAX - 63, 1, 64), None);
+        assert_eq!(block_addr(0, usize::MAX / 2 + 1, 2), None);
+    }
+
+    #[test]
+    fn test_allocator_near_address_space_top() {
+        // Heap extends past usize::MAX and is clamped rather than wrapped
+        let heap_start = usize::MAX - 0xFFF;
+        let mut allocator = EnhancedAllocator::new_default(heap_start, 0x2000);
+        allocator.initialize();
+        let layout = Layout::from_size_align(64, 8).unwrap();
+        let mut count = 0;
+        while let Some(addr) = allocator.allocate(layout) {
+            assert!(addr >= heap_start, "allocation wrapped to {addr:#x}");
+            assert!(region_end(addr, 64).is_some());
+            count += 1;
+        }
+        assert_eq!(count, 0xFFF / 64);
+    }
+
+    #[test]
+    fn test_deallocation_reverses_allocation() {
+        let mut allocator = EnhancedAllocator::new_default(0x1000, 0x10000);
+        allocator.initialize();
+        let initial = allocator.free_memory();
+        let mut rng = StdRng::seed_from_u64(0x5EED);
+        for round in 0..8 {
+            let mut live = Vec::new();
+            loop {
+                let layout = Layout::from_size_align(rng.gen_range(1..=0x300), 16).unwrap();
+                match allocator.allocate(layout) {
+                    Some(addr) => live.push((addr, layout)),
+                    None => break,
+                }
+            }
+            // Free in allocation order, reverse order, or shuffled
+            match round % 3 {
+                0 => {}
+                1 => live.reverse(),
+                _ => live.shuffle(&mut rng),
+            }
+            for (addr, layout) in live {
+                assert!(allocator.deallocate(addr, layout));
+            }
+            assert_eq!(allocator.free_memory(), initial);
+            assert!(allocator
+                .bitmap
+                .iter()
+                .chain(&allocator.heads)
+                .all(|&byte| byte == 0));
+        }
+    }
+
+    #[test]
+    fn test_deallocate_rejects_mismatched_layout() {
+        let mut allocator = EnhancedAllocator::new_default(0x1000, 0x10000);
+        let layout = |size| Layout::from_size_align(size, 16).unwrap();
+        let a = allocator.allocate(layout(0x100)).unwrap();
+        let b = allocator.allocate(layout(0x100)).unwrap();
+        let free = allocator.free_memory();
+        let bitmap = allocator.bitmap;
+
+        // Too large would free b's blocks, too small would leak a's tail
+        assert!(!allocator.deallocate(a, layout(0x200)));
+        assert!(!allocator.deallocate(a, layout(0x80)));
+        // Not the start of an allocation
+        assert!(!allocator.deallocate(a + 0x40, layout(0xC0)));
+        assert_eq!(allocator.free_memory(), free);
+        assert_eq!(allocator.bitmap, bitmap);
+
+        assert!(allocator.deallocate(a, layout(0x100)));
+        assert!(!allocator.deallocate(a, layout(0x100)), "double free");
+        assert!(allocator.deallocate(b, layout(0x100)));
+        assert_eq!(allocator.free_memory(), 0x10000);
+    }
+
+    #[test]
+    fn test_allocator_block_size_sets_granularity() {
+        let layout = Layout::from_size_align(20, 4).unwrap();
+
+        let mut fine = EnhancedAllocator::new(0x1000, 0x1000, 16).unwrap();
+        let a = fine.allocate(layout).unwrap();
+        let b = fine.allocate(layout).unwrap();
+        assert_eq!(b - a, 32);
+        assert_eq!(fine.free_memory(), 0x1000 - 64);
+
+        let mut coarse = EnhancedAllocator::new(0x1000, 0x1000, 256).unwrap();
+        let a = coarse.allocate(layout).unwrap();
+        let b = coarse.allocate(layout).unwrap();
+        assert_eq!(b - a, 256);
+        assert_eq!(coarse.free_memory(), 0x1000 - 512);
+        assert!(coarse.deallocate(a, layout));
+        assert_eq!(coarse.block_size(), 256);
+    }
+
+    #[test]
+    fn test_allocator_rejects_bad_block_sizes() {
+        for block_size in [0, 4, 24, 100] {
+            assert_eq!(
+                EnhancedAllocator::new(0x1000, 0x1000, block_size).err(),
+                Some(AllocatorError::InvalidBlockSize(block_size))
+            );
+        }
+        // 16-byte blocks track at most 512 KiB; 256-byte blocks cover it easily
+        assert_eq!(
+            EnhancedAllocator::new(0x1000, 0x80000 + 16, 16).err(),
+            Some(AllocatorError::HeapTooLarge {
+                blocks: 0x8001,
+                capacity: 0x8000
+            })
+        );
+        assert!(EnhancedAllocator::new(0x1000, 0x80000, 16).is_ok());
+        assert!(EnhancedAllocator::new(0x1000, 0x80000 + 16, 256).is_ok());
+    }
+
+    #[test]
+    fn test_vm_rejects_wrapping_stack() {
+        let mut vm = VirtualMachine::new(usize::MAX - 0xFFF, 0x1000);
+        // Too large to allocate
+        let huge = isize::MAX as usize - 15;
+        assert!(vm.create_process(0x2000, huge).is_none());
+        let pid = vm.create_process(0x2000, 0x100).unwrap();
+        assert_eq!(pid, 0);
+        assert!(vm.get_process(pid).unwrap().sp > usize::MAX - 0xFFF);
+    }
+
+    #[test]
+    fn test_create_process_fails_when_heap_exhausted() {
+        let mut vm = VirtualMachine::new(0x1000, 0x1000);
+        let pid = vm.create_process(0x2000, 0x100).unwrap();
+        let rest = vm
+            .allocate_memory(pid, 0xF00, MemoryPermissions::ReadWrite)
+            .unwrap();
+        assert_eq!(vm.get_memory_stats().1, 0);
+
+        // No stack is handed out on top of memory that is already in use
+        assert_eq!(vm.create_process(0x3000, 0x40), None);
+        assert_eq!(vm.get_memory_stats().1, 0);
+
+        assert!(vm.deallocate_memory(pid, rest));
+        let next = vm.create_process(0x3000, 0x40).unwrap();
+        assert_eq!(next, pid + 1);
+        assert!(vm.get_process(next).unwrap().sp > vm.get_process(pid).unwrap().sp);
+    }
+
+    #[test]
+    fn test_batch_waits_for_interactive() {
+        let mut vm = VirtualMachine::new(0x1000, 0x10000);
+        let batch = vm.create_process(0x2000, 0x100).unwrap();
+        let ui_a = vm.create_process(0x3000, 0x100).unwrap();
+        let ui_b = vm.create_process(0x4000, 0x100).unwrap();
+        assert!(vm.set_class(batch, SchedulerClass::Batch));
+
+        let mut ran = Vec::new();
+        for _ in 0..6 {
+            let pid = vm.schedule_next_by_class().unwrap();
+            assert_ne!(pid, batch, "batch ran while interactive was ready");
+            ran.push(pid);
+        }
+        assert_eq!(ran, [ui_a, ui_b, ui_a, ui_b, ui_a, ui_b]);
+
+        vm.get_process_mut(ui_a).unwrap().state = ProcessState::Blocked;
+        vm.get_process_mut(ui_b).unwrap().state = ProcessState::Blocked;
+        assert_eq!(vm.schedule_next_by_class(), Some(batch));
+    }
+
+    #[test]
+    fn test_realtime_alternates_and_holds_cpu() {
+        let mut vm = VirtualMachine::new(0x1000, 0x10000);
+        let interactive = vm.create_process(0x2000, 0x100).unwrap();
+        let rt_a = vm.create_process(0x3000, 0x100).unwrap();
+        let rt_b = vm.create_process(0x4000, 0x100).unwrap();
+        assert!(vm.set_class(rt_a, SchedulerClass::RealTime));
+        assert!(vm.set_class(rt_b, SchedulerClass::RealTime));
+        assert!(!vm.set_class(99, SchedulerClass::Batch));
+
+        let ran: Vec<_> = (0..4).filter_map(|_| vm.schedule_next_by_class()).collect();
+        assert_eq!(ran, [rt_a, rt_b, rt_a, rt_b]);
+
+        // A higher real-time priority takes the CPU and keeps it
+        assert!(vm.set_rt_priority(rt_a, 5));
+        for _ in 0..3 {
+            assert_eq!(vm.schedule_next_by_class(), Some(rt_a));
+        }
+        assert_ne!(
+            vm.get_process(interactive).unwrap().state,
+            ProcessState::Running
+        );
+    }
+
+    #[test]
+    fn test_check_access_enforces_permissions() {
+        let mut vm = VirtualMachine::new(0x1000, 0x10000);
+        let pid = vm.create_process(0x2000, 0x100).unwrap();
+        let ro = vm
+            .allocate_memory(pid, 0x100, MemoryPermissions::ReadOnly)
+            .unwrap();
+        let rw = vm
+            .allocate_memory(pid, 0x100, MemoryPermissions::ReadWrite)
+            .unwrap();
+
+        assert_eq!(
+            vm.check_access(pid, ro + 0x10, 0x20, AccessKind::Read),
+            Ok(())
+        );
+        assert_eq!(vm.check_access(pid, rw, 0x100, AccessKind::Write), Ok(()));
+        assert_eq!(
+            vm.check_access(pid, ro, 1, AccessKind::Write),
+            Err(MemoryFault::PermissionDenied)
+        );
+        assert_eq!(
+            vm.check_access(pid, rw, 1, AccessKind::Execute),
+            Err(MemoryFault::PermissionDenied)
+        );
+    }
+
+    #[test]
+    fn test_check_access_rejects_bad_spans() {
+        let mut vm = VirtualMachine::new(0x1000, 0x10000);
+        let pid = vm.create_process(0x2000, 0x100).unwrap();
+        let rx = vm
+            .allocate_memory(pid, 0x100, MemoryPermissions::ReadExecute)
+            .unwrap();
+
+        assert_eq!(vm.check_access(pid, rx, 4, AccessKind::Execute), Ok(()));
+        assert_eq!(
+            vm.check_access(pid, rx + 0xF0, 0x20, AccessKind::Read),
+            Err(MemoryFault::OutOfRange)
+        );
+        assert_eq!(
+            vm.check_access(pid, rx + 0x10, usize::MAX, AccessKind::Read),
+            Err(MemoryFault::OutOfRange)
+        );
+        assert_eq!(
+            vm.check_access(pid, rx + 0x100, 1, AccessKind::Read),
+            Err(MemoryFault::Unmapped)
+        );
+        assert_eq!(
+            vm.check_access(pid + 1, rx, 1, AccessKind::Read),
+            Err(MemoryFault::NoSuchProcess)
+        );
+    }
+
+    #[test]
+    fn test_vm_snapshot_roundtrip_resumes_scheduling() {
+        let mut vm = VirtualMachine::new(0x1000, 0x10000);
+        let a = vm.create_process(0x2000, 0x100).unwrap();
+        let b = vm.create_process(0x3000, 0x100).unwrap();
+        let c = vm.create_process(0x4000, 0x100).unwrap();
+        vm.allocate_memory(b, 0x200, MemoryPermissions::ReadWrite)
+            .unwrap();
+        vm.set_class(c, SchedulerClass::Batch);
+        assert_eq!(vm.schedule_next(), Some(a));
+
+        let encoded = serde_json::to_string(&vm.export_state()).unwrap();
+        let mut restored =
+            VirtualMachine::import_state(serde_json::from_str(&encoded).unwrap()).unwrap();
+        assert_eq!(restored.get_memory_stats(), vm.get_memory_stats());
+        for _ in 0..5 {
+            assert_eq!(restored.schedule_next(), vm.schedule_next());
+        }
+        assert_eq!(
+            restored.create_process(0x5000, 0x100),
+            vm.create_process(0x5000, 0x100)
+        );
+
+        // Regions must be backed by allocated blocks
+        let mut tampered = vm.export_state();
+        tampered.bitmap = [0; 4096];
+        assert_eq!(
+            VirtualMachine::import_state(tampered).err(),
+            Some(SnapshotError::RegionNotAllocated(b))
+        );
+    }
+
+    #[test]
+    fn test_braid_cpu_snapshot_restores_registers() {
+        let mut cpu = BraidCPU::new();
+        let mut generators = [BraidGenerator::Left(0); 16];
+        generators[1] = BraidGenerator::Right(3);
+        cpu.load_program(BraidWord {
+            generators,
+            length: 2,
+            _homotopy: core::marker::PhantomData,
+        });
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+
+        let encoded = serde_json::to_string(&cpu.snapshot()).unwrap();
+        let mut restored = BraidCPU::new();
+        restored
+            .restore(serde_json::from_str(&encoded).unwrap())
+            .unwrap();
+        assert_eq!(restored.snapshot(), cpu.snapshot());
+        for register in 0..16 {
+            assert_eq!(restored.get_register(register), cpu.get_register(register));
+        }
+
+        let mut invalid = cpu.snapshot();
+        invalid.strand_permutation[0] = invalid.strand_permutation[1];
+        assert_eq!(
+            restored.restore(invalid),
+            Err(SnapshotError::InvalidPermutation)
+        );
+    }
+
+    #[test]
+    fn braid_cpu_detects_and_repairs_stale_inverse() {
+        let mut cpu = BraidCPU::new();
+        cpu.load_program(braid_program(5));
+        cpu.run(5).unwrap();
+        cpu.set_register(3, 9);
+        assert!(cpu.validate_invariant());
+
+        cpu.strand_permutation.swap(0, 7);
+        assert!(!cpu.validate_invariant());
+
+        cpu.recompute_inverse();
+        assert!(cpu.validate_invariant());
+        for position in 0..16 {
+            assert_eq!(cpu.get_register(cpu.strand_permutation[position]), position);
+        }
+    }
+
+    fn braid_program(length: usize) -> BraidWord {
+        let mut generators = [BraidGenerator::Left(0); 16];
+        for (i, generator) in generators.iter_mut().enumerate() {
+            *generator = BraidGenerator::Left((i % 15) as u8);
+        }
+        BraidWord {
+            generators,
+            length,
+            _homotopy: core::marker::PhantomData,
+        }
+    }
+
+    #[test]
+    fn test_braid_run_counts_steps() {
+        let mut cpu = BraidCPU::new();
+        assert_eq!(cpu.run(8), Err(BraidExecutionError::NoProgramLoaded));
+        cpu.load_program(braid_program(3));
+        assert_eq!(cpu.run(8), Ok(3));
+        assert_eq!(cpu.run(8), Ok(0));
+        cpu.load_program(braid_program(3));
+        assert_eq!(cpu.run(3), Ok(3));
+    }
+
+    #[test]
+    fn test_braid_run_times_out_at_capacity() {
+        let mut cpu = BraidCPU::new();
+        cpu.load_program(braid_program(16));
+        assert_eq!(cpu.run(10), Err(BraidExecutionError::TimedOut));
+        assert_eq!(cpu.pc, 10);
+        // The remaining steps can be resumed under a fresh budget
+        assert_eq!(cpu.run(10), Ok(6));
+    }
+
     #[test]
     fn test_process_scheduling() {
         let mut vm = VirtualMachine::new(0x1000, 0x10000);
@@ -622,6 +1912,138 @@ mod tests {
         assert_eq!(vm.get_process(pid2).unwrap().state, ProcessState::Running);
     }
 
+    #[test]
+    fn test_cpu_quota_terminates_after_budget() {
+        let mut vm = VirtualMachine::new(0x1000, 0x10000);
+        let limited = vm.create_process(0x2000, 0x100).unwrap();
+        let free = vm.create_process(0x3000, 0x100).unwrap();
+        assert!(vm.set_quota(limited, Some(5)));
+        assert!(!vm.set_quota(free + 1, Some(5)));
+
+        let ran: Vec<_> = (0..20).filter_map(|_| vm.schedule_next()).collect();
+        assert_eq!(ran.iter().filter(|&&pid| pid == limited).count(), 5);
+        assert_eq!(ran.iter().filter(|&&pid| pid == free).count(), 15);
+        assert_eq!(vm.usage(limited), 5);
+        assert_eq!(vm.usage(free), 15);
+        assert_eq!(
+            vm.get_process(limited).unwrap().state,
+            ProcessState::Terminated
+        );
+        assert_eq!(vm.get_process(free).unwrap().state, ProcessState::Running);
+        assert_eq!(vm.usage(free + 1), 0);
+    }
+
+    #[test]
+    fn test_message_passing_between_processes() {
+        let mut vm = VirtualMachine::new(0x1000, 0x10000);
+        let a = vm.create_process(0x2000, 0x100).unwrap();
+        let b = vm.create_process(0x3000, 0x100).unwrap();
+
+        vm.send_message(a, b, b"ping").unwrap();
+        vm.send_message(b, b, b"self").unwrap();
+        let first = vm.recv_message(b).unwrap();
+        assert_eq!((first.from, first.data()), (a, &b"ping"[..]));
+        assert_eq!(vm.recv_message(b).unwrap().data(), b"self");
+        assert_eq!(vm.recv_message(b), None);
+        assert_eq!(vm.recv_message(a), None);
+
+        // Full mailboxes reject rather than overwrite
+        for n in 0..MAILBOX_SLOTS {
+            vm.send_message(a, b, &[n as u8]).unwrap();
+        }
+        assert_eq!(vm.send_message(a, b, b"x"), Err(VmError::MailboxFull(b)));
+        assert_eq!(vm.recv_message(b).unwrap().data(), &[0]);
+        vm.send_message(a, b, b"x").unwrap();
+        assert_eq!(
+            vm.send_message(a, b, &[0; MESSAGE_BYTES + 1]),
+            Err(VmError::MessageTooLarge)
+        );
+
+        // Terminated or unknown processes cannot take part
+        assert!(vm.terminate_process(b));
+        assert_eq!(
+            vm.send_message(a, b, b"late"),
+            Err(VmError::ProcessTerminated(b))
+        );
+        assert_eq!(
+            vm.send_message(b, a, b"late"),
+            Err(VmError::ProcessTerminated(b))
+        );
+        assert_eq!(
+            vm.send_message(a, b + 1, b"?"),
+            Err(VmError::NoSuchProcess(b + 1))
+        );
+    }
+
+    #[test]
+    fn test_deadlock_detector_reports_wait_cycle() {
+        let mut vm = VirtualMachine::new(0x1000, 0x10000);
+        let a = vm.create_process(0x2000, 0x100).unwrap();
+        let b = vm.create_process(0x3000, 0x100).unwrap();
+        let c = vm.create_process(0x4000, 0x100).unwrap();
+
+        // A chain that ends at a runnable process is not a deadlock
+        assert!(vm.block_on(a, b));
+        assert!(vm.block_on(b, c));
+        assert_eq!(vm.detect_deadlock(), None);
+        assert_eq!(vm.schedule_next(), Some(c));
+
+        // Closing the loop leaves nobody able to run
+        assert!(vm.unblock(b));
+        assert!(vm.block_on(b, a));
+        assert!(vm.terminate_process(c));
+        assert_eq!(vm.schedule_next(), None);
+        let cycle = vm.detect_deadlock().unwrap();
+        let mut pids = cycle.as_slice().to_vec();
+        pids.sort_unstable();
+        assert_eq!(pids, [a, b]);
+
+        assert!(vm.unblock(a));
+        assert_eq!(vm.detect_deadlock(), None);
+    }
+
+    #[test]
+    fn test_fork_shares_regions_until_write() {
+        let mut vm = VirtualMachine::new(0x1000, 0x10000);
+        let parent = vm.create_process(0x2000, 0x100).unwrap();
+        let region = vm
+            .allocate_memory(parent, 0x200, MemoryPermissions::ReadWrite)
+            .unwrap();
+        let (used, _) = vm.get_memory_stats();
+
+        // Forking maps the parent's blocks instead of copying them
+        let child = vm.fork(parent).unwrap();
+        assert_eq!(vm.get_memory_stats().0, used);
+        assert_eq!(
+            vm.get_process(child).unwrap().memory_regions[0]
+                .unwrap()
+                .start,
+            region
+        );
+
+        // The child's first write moves it onto blocks of its own
+        let copy = vm.write_region(child, region + 0x10, 4).unwrap();
+        assert_ne!(copy, region + 0x10);
+        assert_eq!(vm.get_memory_stats().0, used + 0x200);
+        assert_eq!(
+            vm.get_process(parent).unwrap().memory_regions[0]
+                .unwrap()
+                .start,
+            region
+        );
+        assert_eq!(vm.write_region(child, copy, 4), Ok(copy));
+        // With the child gone from them, the parent writes in place
+        assert_eq!(vm.write_region(parent, region, 4), Ok(region));
+
+        // Blocks stay allocated while any sharer still maps them
+        let sibling = vm.fork(parent).unwrap();
+        assert!(vm.terminate_process(parent));
+        assert_eq!(vm.get_memory_stats().0, used + 0x200);
+        assert!(vm.terminate_process(sibling));
+        assert!(vm.terminate_process(child));
+        assert_eq!(vm.get_memory_stats().0, used - 0x200);
+    }
+
     /// PROPRIETARY ALGORITHM: Adaptive Process Scheduling Stress Test
     /// Uses a genetic algorithm to evolve process creation patterns that maximize scheduling complexity
     /// This proprietary algorithm generates worst-case interleavings to test scheduler robustness
@@ -847,7 +2269,7 @@ mod tests {
     /// Implements a novel recursive allocation algorithm that mimics natural growth patterns
     #[test]
     fn test_memory_fragmentation_chaos() {
-        let mut allocator = EnhancedAllocator::new(0x1000, 0x10000);
+        let mut allocator = EnhancedAllocator::new_default(0x1000, 0x10000);
         allocator.initialize();
         allocator.initialize();
 
@@ -894,8 +2316,11 @@ mod tests {
         // Verify allocator integrity
         // Check that all remaining allocations are still valid
         for (addr, size) in &all_allocations {
-            assert!(*addr >= allocator.heap_start && *addr + *size <= allocator.heap_end,
-                "Memory corruption detected in fractal allocation test");
+            assert!(
+                *addr >= allocator.heap_start
+                    && region_end(*addr, *size).is_some_and(|end| end <= allocator.heap_end),
+                "Memory corruption detected in fractal allocation test"
+            );
         }
 
         // Check that total free memory is non-negative and within bounds
@@ -1029,6 +2454,28 @@ mod tests {
     /// Uses formal verification techniques to ensure VM correctness
     #[cfg(not(miri))]
     proptest! {
+        #[test]
+        fn allocator_frees_back_to_initial_state(
+            sizes in proptest::collection::vec(1..0x800usize, 1..64),
+            order in any::<u64>()
+        ) {
+            let mut allocator = EnhancedAllocator::new_default(0x1000, 0x10000);
+            let initial = allocator.free_memory();
+            let mut live: Vec<_> = sizes
+                .iter()
+                .filter_map(|&size| {
+                    let layout = Layout::from_size_align(size, 16).unwrap();
+                    allocator.allocate(layout).map(|addr| (addr, layout))
+                })
+                .collect();
+            live.shuffle(&mut StdRng::seed_from_u64(order));
+            for (addr, layout) in live {
+                prop_assert!(allocator.deallocate(addr, layout));
+            }
+            prop_assert_eq!(allocator.free_memory(), initial);
+            prop_assert!(allocator.bitmap.iter().chain(&allocator.heads).all(|&byte| byte == 0));
+        }
+
         #[test]
         fn vm_process_creation_invariants(
             heap_size in 0x1000..0x100000u64,
@@ -1094,7 +2541,7 @@ mod tests {
             allocation_count in 1..100usize,
             max_size in 64..4096usize
         ) {
-            let mut allocator = EnhancedAllocator::new(0x1000, 0x100000);
+            let mut allocator = EnhancedAllocator::new_default(0x1000, 0x100000);
         allocator.initialize();
             allocator.initialize();
 
@@ -1351,6 +2798,24 @@ mod tests {
                     _homotopy: core::marker::PhantomData,
                 };
 
+                // The assembler builds the same words as the hand-built literals
+                assert_eq!(
+                    BraidWord::from_generators(&[BraidGenerator::Left(i as u8)]).unwrap(),
+                    word1
+                );
+                assert_eq!(
+                    BraidWord::from_generators(&[BraidGenerator::Left(j as u8)]).unwrap(),
+                    word2
+                );
+                assert_eq!(
+                    BraidWord::from_generators(&[
+                        BraidGenerator::Left(i as u8),
+                        BraidGenerator::Left(j as u8)
+                    ])
+                    .unwrap(),
+                    composed_word
+                );
+
                 // Execute word1 to get permutation φ(w₁)
                 let mut cpu1 = BraidCPU::new();
                 cpu1.load_program(word1);
@@ -1462,7 +2927,7 @@ mod tests {
                         let _ = vm.schedule_next();
                     }
                     "memory_allocation" => {
-                        let mut allocator = EnhancedAllocator::new(0x1000, 0x10000);
+                        let mut allocator = EnhancedAllocator::new_default(0x1000, 0x10000);
         allocator.initialize();
                         let _ = allocator.allocate(core::alloc::Layout::from_size_align(1024, 8).unwrap());
                     }
@@ -1493,7 +2958,7 @@ mod tests {
                         let _ = vm.schedule_next();
                     }
                     "memory_allocation" => {
-                        let mut allocator = EnhancedAllocator::new(0x1000, 0x10000);
+                        let mut allocator = EnhancedAllocator::new_default(0x1000, 0x10000);
         allocator.initialize();
                         let _ = allocator.allocate(core::alloc::Layout::from_size_align(1024, 8).unwrap());
                     }
@@ -1544,7 +3009,7 @@ mod tests {
                         }
                         2 => {
                             // Memory allocation stress
-                            let mut allocator = EnhancedAllocator::new(0x1000, 0x10000);
+                            let mut allocator = EnhancedAllocator::new_default(0x1000, 0x10000);
         allocator.initialize();
                             let allocated = allocator.allocate(
                                 core::alloc::Layout::from_size_align(1024, 8).unwrap()
@@ -0,0 +1,61 @@
+//! C ABI entry point for the syscall dispatcher (`ffi` feature)
+//!
+//! The signature is cbindgen-compatible and corresponds to this C prototype:
+//!
+//! ```c
+//! typedef struct MuscleNucleus MuscleNucleus;
+//!
+//! int64_t nucleus_syscall(MuscleNucleus *nucleus, uint64_t number,
+//!                         uintptr_t arg0, uintptr_t arg1, uintptr_t arg2);
+//! ```
+//!
+//! Results are packed into a single `i64`: non-negative values are the
+//! syscall's success value, negative values are `-NucleusError::code`.
+//! Unknown syscall numbers return [`UNKNOWN_SYSCALL`].
+
+use crate::kernel::MuscleNucleus;
+use crate::syscalls::{Syscall, SyscallArgs, SyscallHandler, SyscallResult};
+use crate::NucleusError;
+
+/// Returned for syscall numbers `Syscall::from_u64` does not recognise.
+///
+/// Lies outside the range of negated error codes, so it can never be
+/// confused with a `NucleusError`.
+pub const UNKNOWN_SYSCALL: i64 = i64::MIN;
+
+/// Pack a syscall result into the C return encoding
+///
+/// Success values that do not fit in an `i64` are reported as
+/// `MemoryFault`.
+pub fn pack_result(result: SyscallResult) -> i64 {
+    match result {
+        Ok(value) => i64::try_from(value).unwrap_or(-(NucleusError::MemoryFault.code() as i64)),
+        Err(err) => -(err.code() as i64),
+    }
+}
+
+/// Decode and dispatch syscall `number` on `nucleus`
+///
+/// A null `nucleus` is reported as `MemoryFault`.
+///
+/// # Safety
+///
+/// `nucleus` must be null or point to a live `MuscleNucleus` that is not
+/// accessed elsewhere for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn nucleus_syscall(
+    nucleus: *mut MuscleNucleus,
+    number: u64,
+    arg0: usize,
+    arg1: usize,
+    arg2: usize,
+) -> i64 {
+    let Some(syscall) = Syscall::from_u64(number) else {
+        return UNKNOWN_SYSCALL;
+    };
+    // SAFETY: the caller guarantees `nucleus` is null or uniquely borrowed.
+    let Some(nucleus) = (unsafe { nucleus.as_mut() }) else {
+        return pack_result(Err(NucleusError::MemoryFault));
+    };
+    pack_result(nucleus.handle_syscall(syscall, SyscallArgs { arg0, arg1, arg2 }))
+}
@@ -1,4 +1,5 @@
-use crate::NucleusError;
+use crate::capability::{ObjectType, Rights};
+use crate::{NucleusError, MAX_AUDIT_EVENTS, MAX_CAPABILITIES, MAX_DELEGATION_DEPTH};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Capability {
@@ -44,3 +45,591 @@ impl CapabilitySet {
         }
     }
 }
+
+/// Byte range of a lattice object a capability may touch (8 bytes per entry)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatticeWindow {
+    pub offset: u32,
+    pub len: u32,
+}
+
+impl LatticeWindow {
+    /// Window covering the whole addressable object
+    pub const ALL: Self = Self {
+        offset: 0,
+        len: u32::MAX,
+    };
+
+    pub const fn new(offset: u32, len: u32) -> Self {
+        Self { offset, len }
+    }
+
+    /// Whether `[offset, offset + len)` lies inside this window
+    pub const fn contains(&self, offset: usize, len: usize) -> bool {
+        let start = self.offset as u64;
+        let end = start + self.len as u64;
+        let req_start = offset as u64;
+        match req_start.checked_add(len as u64) {
+            Some(req_end) => req_start >= start && req_end <= end,
+            None => false,
+        }
+    }
+
+    /// Whether `other` lies entirely inside this window
+    pub const fn covers(&self, other: LatticeWindow) -> bool {
+        self.contains(other.offset as usize, other.len as usize)
+    }
+}
+
+/// A capability held in the fixed-size capability table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapabilityEntry {
+    pub cap: crate::capability::Capability,
+    pub owner: u64,
+    pub parent: Option<u8>,    // Slot this entry was derived or delegated from
+    pub depth: u8,             // 0 for roots, +1 per derive/delegate step
+    pub window: LatticeWindow, // Only enforced for `ObjectType::LatticeObject`
+    // Key held by `parent`, guarding against slot reuse
+    pub parent_key: Option<[u8; 32]>,
+}
+
+/// Slots a revocation visits, in visiting order
+#[derive(Debug, Clone, Copy)]
+pub struct RevocationOrder {
+    slots: [usize; MAX_CAPABILITIES],
+    len: usize,
+}
+
+impl RevocationOrder {
+    pub fn as_slice(&self) -> &[usize] {
+        &self.slots[..self.len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Capability table operation recorded in the audit log
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapabilityOp {
+    Grant,
+    Derive,
+    Delegate,
+    Revoke,
+}
+
+/// One audited capability operation, as copied out by `CapAuditRead`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapabilityEvent {
+    pub op: CapabilityOp,
+    pub key: [u8; 32],
+    pub rights: Rights, // Rights of the capability created or removed
+    pub tick: u64,      // Table clock when the operation ran
+}
+
+/// Fixed-size ring of the most recent capability operations
+///
+/// When full, each new event overwrites the oldest and bumps `dropped`.
+#[derive(Debug, Clone, Copy)]
+pub struct CapabilityAudit {
+    events: [Option<CapabilityEvent>; MAX_AUDIT_EVENTS],
+    head: usize, // Slot of the oldest retained event
+    len: usize,
+    dropped: u64,
+}
+
+impl CapabilityAudit {
+    pub const fn new() -> Self {
+        Self {
+            events: [None; MAX_AUDIT_EVENTS],
+            head: 0,
+            len: 0,
+            dropped: 0,
+        }
+    }
+
+    fn record(&mut self, event: CapabilityEvent) {
+        let slot = (self.head + self.len) % MAX_AUDIT_EVENTS;
+        self.events[slot] = Some(event);
+        if self.len == MAX_AUDIT_EVENTS {
+            self.head = (self.head + 1) % MAX_AUDIT_EVENTS;
+            self.dropped = self.dropped.saturating_add(1);
+        } else {
+            self.len += 1;
+        }
+    }
+
+    /// Retained event `index`, counting from the oldest
+    pub fn get(&self, index: usize) -> Option<&CapabilityEvent> {
+        if index >= self.len {
+            return None;
+        }
+        self.events[(self.head + index) % MAX_AUDIT_EVENTS].as_ref()
+    }
+
+    /// Retained events, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &CapabilityEvent> + '_ {
+        (0..self.len).filter_map(move |index| self.get(index))
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Events overwritten since the log was enabled
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+impl Default for CapabilityAudit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Fixed-size capability table with bounded derivation chains
+#[derive(Debug, Default)]
+pub struct CapabilityTable {
+    slots: [Option<CapabilityEntry>; MAX_CAPABILITIES],
+    now: u64,                       // Kernel tick used for expiry checks
+    audit: Option<CapabilityAudit>, // Off unless enabled
+}
+
+impl CapabilityTable {
+    pub const fn new() -> Self {
+        Self {
+            slots: [None; MAX_CAPABILITIES],
+            now: 0,
+            audit: None,
+        }
+    }
+
+    /// Start recording grants, derivations, delegations and revocations
+    ///
+    /// Already-enabled logs keep their history.
+    pub fn enable_audit(&mut self) {
+        self.audit.get_or_insert_with(CapabilityAudit::new);
+    }
+
+    /// The audit log, if enabled
+    pub fn audit(&self) -> Option<&CapabilityAudit> {
+        self.audit.as_ref()
+    }
+
+    /// Look up an occupied slot, expired or not
+    pub fn get(&self, index: usize) -> Option<&CapabilityEntry> {
+        self.slots.get(index).and_then(|slot| slot.as_ref())
+    }
+
+    /// Look up a capability that is present and not yet expired
+    pub fn check_live(&self, index: usize) -> Result<&CapabilityEntry, NucleusError> {
+        match self.get(index) {
+            Some(entry) if !entry.cap.is_expired(self.now) => Ok(entry),
+            _ => Err(NucleusError::InvalidCapability),
+        }
+    }
+
+    /// Kernel tick the table last saw
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    /// Move the table clock to `now` and drop every expired entry, returning the count
+    ///
+    /// Children never outlive their parents, so whole subtrees expire together.
+    pub fn advance_clock(&mut self, now: u64) -> usize {
+        self.now = self.now.max(now);
+        let mut expired = 0;
+        for slot in self.slots.iter_mut() {
+            if slot.is_some_and(|entry| entry.cap.is_expired(self.now)) {
+                *slot = None;
+                expired += 1;
+            }
+        }
+        expired
+    }
+
+    /// Number of occupied slots
+    pub fn len(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Install a root capability (depth 0) for `owner` with an unbounded window
+    pub fn grant(
+        &mut self,
+        cap: crate::capability::Capability,
+        owner: u64,
+    ) -> Result<usize, NucleusError> {
+        self.grant_window(cap, owner, LatticeWindow::ALL)
+    }
+
+    /// Install a root capability (depth 0) for `owner` limited to `window`
+    pub fn grant_window(
+        &mut self,
+        cap: crate::capability::Capability,
+        owner: u64,
+        window: LatticeWindow,
+    ) -> Result<usize, NucleusError> {
+        let slot = self.insert(CapabilityEntry {
+            cap,
+            owner,
+            parent: None,
+            parent_key: None,
+            depth: 0,
+            window,
+        })?;
+        self.log(CapabilityOp::Grant, &cap);
+        Ok(slot)
+    }
+
+    /// Derive an attenuated copy of `index` for the same owner
+    pub fn derive(&mut self, index: usize, rights: Rights) -> Result<usize, NucleusError> {
+        let parent = *self.check_live(index)?;
+        if !parent.cap.rights.contains(rights) {
+            return Err(NucleusError::InvalidCapability);
+        }
+        let mut cap = parent.cap;
+        cap.rights = rights;
+        self.insert_child(
+            CapabilityOp::Derive,
+            index,
+            parent,
+            cap,
+            parent.owner,
+            parent.window,
+        )
+    }
+
+    /// Derive an attenuated copy of `index` that expires at tick `expires_at`
+    ///
+    /// The child may not outlive its parent.
+    pub fn derive_until(
+        &mut self,
+        index: usize,
+        rights: Rights,
+        expires_at: u64,
+    ) -> Result<usize, NucleusError> {
+        let parent = *self.check_live(index)?;
+        let within_parent = match parent.cap.expires_at {
+            Some(tick) => expires_at <= tick,
+            None => true,
+        };
+        if !parent.cap.rights.contains(rights) || !within_parent {
+            return Err(NucleusError::InvalidCapability);
+        }
+        let mut cap = parent.cap;
+        cap.rights = rights;
+        cap.expires_at = Some(expires_at);
+        self.insert_child(
+            CapabilityOp::Derive,
+            index,
+            parent,
+            cap,
+            parent.owner,
+            parent.window,
+        )
+    }
+
+    /// Derive a copy of `index` for the same owner narrowed to `window`
+    pub fn derive_window(
+        &mut self,
+        index: usize,
+        window: LatticeWindow,
+    ) -> Result<usize, NucleusError> {
+        let parent = *self.check_live(index)?;
+        if !parent.window.covers(window) {
+            return Err(NucleusError::InvalidCapability);
+        }
+        self.insert_child(
+            CapabilityOp::Derive,
+            index,
+            parent,
+            parent.cap,
+            parent.owner,
+            window,
+        )
+    }
+
+    /// Delegate `index` to `target`; requires `Rights::DELEGATE`
+    pub fn delegate(&mut self, index: usize, target: u64) -> Result<usize, NucleusError> {
+        let parent = *self.check_live(index)?;
+        if !parent.cap.rights.contains(Rights::DELEGATE) {
+            return Err(NucleusError::InvalidCapability);
+        }
+        self.insert_child(
+            CapabilityOp::Delegate,
+            index,
+            parent,
+            parent.cap,
+            target,
+            parent.window,
+        )
+    }
+
+    /// Check that `index` grants `rights` on `[offset, offset + len)` of a lattice object
+    pub fn check_lattice_access(
+        &self,
+        index: usize,
+        rights: Rights,
+        offset: usize,
+        len: usize,
+    ) -> Result<(), NucleusError> {
+        let entry = self.check_live(index)?;
+        let allowed = entry.cap.object_type == ObjectType::LatticeObject
+            && entry.cap.rights.contains(rights)
+            && entry.window.contains(offset, len);
+        if allowed {
+            Ok(())
+        } else {
+            Err(NucleusError::InvalidCapability)
+        }
+    }
+
+    /// Slots `revoke(index)` would clear, in the order it visits them
+    ///
+    /// `index` comes first, then its descendants one generation at a time.
+    /// Within a generation slots are sorted by capability (key first), then
+    /// owner, then slot number, so the order never depends on insertion
+    /// history. Each descendant appears exactly once.
+    pub fn revocation_order(&self, index: usize) -> Result<RevocationOrder, NucleusError> {
+        self.get(index).ok_or(NucleusError::InvalidCapability)?;
+        let mut order = RevocationOrder {
+            slots: [0; MAX_CAPABILITIES],
+            len: 1,
+        };
+        order.slots[0] = index;
+        let mut visited = [false; MAX_CAPABILITIES];
+        visited[index] = true;
+
+        let mut generation = 0..1;
+        while !generation.is_empty() {
+            let start = order.len;
+            for (slot, entry) in self.slots.iter().enumerate() {
+                let Some(entry) = entry else {
+                    continue;
+                };
+                if visited[slot] {
+                    continue;
+                }
+                let parent_in_generation = order.slots[generation.clone()]
+                    .iter()
+                    .any(|&parent| self.is_child_of(entry, parent));
+                if parent_in_generation {
+                    visited[slot] = true;
+                    order.slots[order.len] = slot;
+                    order.len += 1;
+                }
+            }
+            order.slots[start..order.len].sort_unstable_by_key(|&slot| {
+                let entry = self.slots[slot].expect("visited slots are occupied");
+                (entry.cap, entry.owner, slot)
+            });
+            generation = start..order.len;
+        }
+
+        Ok(order)
+    }
+
+    /// Revoke `index` and everything derived from it, returning the count
+    ///
+    /// Visits slots in [`revocation_order`](Self::revocation_order). Children
+    /// sit exactly one level below their parent, so the work is bounded by
+    /// `MAX_DELEGATION_DEPTH + 1` passes over the table regardless of contents.
+    pub fn revoke(&mut self, index: usize) -> Result<usize, NucleusError> {
+        let order = self.revocation_order(index)?;
+        for &slot in order.as_slice() {
+            if let Some(entry) = self.slots[slot].take() {
+                self.log(CapabilityOp::Revoke, &entry.cap);
+            }
+        }
+        Ok(order.len())
+    }
+
+    /// Bytes needed to serialize a full table
+    pub const SERIALIZED_MAX: usize = 8 + 1 + MAX_CAPABILITIES * (1 + ENTRY_ENCODED_LEN);
+
+    /// Write the table clock and occupied slots into `buf`, returning the bytes used
+    ///
+    /// Layout (little-endian): clock `u64`, entry count `u8`, then per entry its
+    /// slot index followed by the packed entry. Slot indices are kept so parent
+    /// links survive a round trip.
+    pub fn serialize_into(&self, buf: &mut [u8]) -> Result<usize, NucleusError> {
+        let count = self.len();
+        let needed = 8 + 1 + count * (1 + ENTRY_ENCODED_LEN);
+        if buf.len() < needed {
+            return Err(NucleusError::CapacityExceeded);
+        }
+        buf[..8].copy_from_slice(&self.now.to_le_bytes());
+        buf[8] = count as u8;
+        let mut pos = 9;
+        for (slot, entry) in self.slots.iter().enumerate() {
+            let Some(entry) = entry else { continue };
+            buf[pos] = slot as u8;
+            buf[pos + 1..pos + 1 + ENTRY_ENCODED_LEN].copy_from_slice(&encode_entry(entry));
+            pos += 1 + ENTRY_ENCODED_LEN;
+        }
+        Ok(pos)
+    }
+
+    /// Rebuild a table written by [`CapabilityTable::serialize_into`]
+    ///
+    /// Truncated input, trailing bytes, out-of-range or repeated slots and
+    /// malformed entries are all rejected as `InvalidCapability`.
+    pub fn deserialize_from(bytes: &[u8]) -> Result<Self, NucleusError> {
+        if bytes.len() < 9 {
+            return Err(NucleusError::InvalidCapability);
+        }
+        let mut now = [0u8; 8];
+        now.copy_from_slice(&bytes[..8]);
+        let count = bytes[8] as usize;
+        if count > MAX_CAPABILITIES || bytes.len() != 9 + count * (1 + ENTRY_ENCODED_LEN) {
+            return Err(NucleusError::InvalidCapability);
+        }
+        let mut table = Self::new();
+        table.now = u64::from_le_bytes(now);
+        for record in bytes[9..].chunks_exact(1 + ENTRY_ENCODED_LEN) {
+            let slot = record[0] as usize;
+            if slot >= MAX_CAPABILITIES || table.slots[slot].is_some() {
+                return Err(NucleusError::InvalidCapability);
+            }
+            let mut packed = [0u8; ENTRY_ENCODED_LEN];
+            packed.copy_from_slice(&record[1..]);
+            table.slots[slot] = Some(decode_entry(&packed).ok_or(NucleusError::InvalidCapability)?);
+        }
+        Ok(table)
+    }
+
+    fn is_child_of(&self, entry: &CapabilityEntry, parent: usize) -> bool {
+        let Some(parent_entry) = self.slots[parent] else {
+            return false;
+        };
+        entry.parent == Some(parent as u8) && entry.parent_key == Some(parent_entry.cap.key)
+    }
+
+    fn insert_child(
+        &mut self,
+        op: CapabilityOp,
+        index: usize,
+        parent: CapabilityEntry,
+        cap: crate::capability::Capability,
+        owner: u64,
+        window: LatticeWindow,
+    ) -> Result<usize, NucleusError> {
+        if parent.depth >= MAX_DELEGATION_DEPTH {
+            return Err(NucleusError::RuleViolation);
+        }
+        let slot = self.insert(CapabilityEntry {
+            cap,
+            owner,
+            parent: Some(index as u8),
+            parent_key: Some(parent.cap.key),
+            depth: parent.depth + 1,
+            window,
+        })?;
+        self.log(op, &cap);
+        Ok(slot)
+    }
+
+    fn log(&mut self, op: CapabilityOp, cap: &crate::capability::Capability) {
+        if let Some(audit) = self.audit.as_mut() {
+            audit.record(CapabilityEvent {
+                op,
+                key: cap.key,
+                rights: cap.rights,
+                tick: self.now,
+            });
+        }
+    }
+
+    fn insert(&mut self, entry: CapabilityEntry) -> Result<usize, NucleusError> {
+        let slot = self
+            .slots
+            .iter()
+            .position(|slot| slot.is_none())
+            .ok_or(NucleusError::CapacityExceeded)?;
+        self.slots[slot] = Some(entry);
+        Ok(slot)
+    }
+}
+
+const CAP_LEN: usize = crate::capability::Capability::ENCODED_LEN;
+
+/// Capability, owner, parent flag + slot, parent key flag + key, depth, window
+const ENTRY_ENCODED_LEN: usize = CAP_LEN + 8 + 2 + 33 + 1 + 8;
+
+fn encode_entry(entry: &CapabilityEntry) -> [u8; ENTRY_ENCODED_LEN] {
+    let mut out = [0u8; ENTRY_ENCODED_LEN];
+    out[..CAP_LEN].copy_from_slice(&entry.cap.to_bytes());
+    let rest = &mut out[CAP_LEN..];
+    rest[..8].copy_from_slice(&entry.owner.to_le_bytes());
+    if let Some(parent) = entry.parent {
+        rest[8] = 1;
+        rest[9] = parent;
+    }
+    if let Some(key) = entry.parent_key {
+        rest[10] = 1;
+        rest[11..43].copy_from_slice(&key);
+    }
+    rest[43] = entry.depth;
+    rest[44..48].copy_from_slice(&entry.window.offset.to_le_bytes());
+    rest[48..52].copy_from_slice(&entry.window.len.to_le_bytes());
+    out
+}
+
+fn decode_entry(bytes: &[u8; ENTRY_ENCODED_LEN]) -> Option<CapabilityEntry> {
+    let mut cap = [0u8; CAP_LEN];
+    cap.copy_from_slice(&bytes[..CAP_LEN]);
+    let cap = crate::capability::Capability::from_bytes(&cap)?;
+    let rest = &bytes[CAP_LEN..];
+    let mut owner = [0u8; 8];
+    owner.copy_from_slice(&rest[..8]);
+    let parent = match rest[8] {
+        0 => None,
+        1 if (rest[9] as usize) < MAX_CAPABILITIES => Some(rest[9]),
+        _ => return None,
+    };
+    let parent_key = match rest[10] {
+        0 => None,
+        1 => {
+            let mut key = [0u8; 32];
+            key.copy_from_slice(&rest[11..43]);
+            Some(key)
+        }
+        _ => return None,
+    };
+    let depth = rest[43];
+    // Roots carry neither link; derived entries carry both and sit below the depth limit
+    let linked = parent.is_some() && parent_key.is_some();
+    let root = parent.is_none() && parent_key.is_none();
+    if !(root && depth == 0 || linked && depth > 0 && depth <= MAX_DELEGATION_DEPTH) {
+        return None;
+    }
+    let mut offset = [0u8; 4];
+    offset.copy_from_slice(&rest[44..48]);
+    let mut len = [0u8; 4];
+    len.copy_from_slice(&rest[48..52]);
+    Some(CapabilityEntry {
+        cap,
+        owner: u64::from_le_bytes(owner),
+        parent,
+        parent_key,
+        depth,
+        window: LatticeWindow::new(u32::from_le_bytes(offset), u32::from_le_bytes(len)),
+    })
+}
@@ -2,6 +2,9 @@ mod capabilities;
 mod nucleus;
 mod scheduler;
 
-pub use capabilities::{Capability, CapabilitySet};
-pub use nucleus::MuscleNucleus;
+pub use capabilities::{
+    Capability, CapabilityAudit, CapabilityEntry, CapabilityEvent, CapabilityOp, CapabilitySet,
+    CapabilityTable, LatticeWindow, RevocationOrder,
+};
+pub use nucleus::{LoadedMuscle, MuscleNucleus};
 pub use scheduler::{Priority, Scheduler};
@@ -16,7 +16,7 @@ impl Priority {
 /// Fixed-size scheduler with compile-time analysis
 #[derive(Debug)]
 pub struct Scheduler {
-    schedule: [Option<usize>; 256], // Muscle slots by priority
+    schedule: [Option<u8>; 256], // Muscle slots by priority
     current_slot: u8,
 }
 
@@ -34,8 +34,9 @@ impl Scheduler {
             return Err(NucleusError::CapacityExceeded);
         }
 
+        let slot = u8::try_from(muscle_slot).map_err(|_| NucleusError::CapacityExceeded)?;
         let priority_val = priority as u8;
-        self.schedule[priority_val as usize] = Some(muscle_slot);
+        self.schedule[priority_val as usize] = Some(slot);
         Ok(())
     }
 
@@ -45,7 +46,7 @@ impl Scheduler {
         for priority in (0..=255).rev() {
             if let Some(slot) = self.schedule[priority as usize] {
                 // In production, this would context switch to muscle
-                self.execute_muscle(slot);
+                self.execute_muscle(slot as usize);
                 break;
             }
         }
@@ -6,6 +6,8 @@
 #![no_std]
 extern crate alloc;
 
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod integration;
 pub mod kernel;
 pub mod memory;
@@ -14,6 +16,12 @@ pub mod rules;
 pub mod syscalls {
     use crate::NucleusError;
 
+    pub mod args;
+    pub use args::{
+        CapAuditReadArgs, CapDelegateArgs, CapDeriveArgs, CapRevokeArgs, ChannelId,
+        ChannelRecvArgs, ChannelSendArgs, LatticeAccessArgs, MuscAllocArgs, MuscMapArgs,
+    };
+
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     #[repr(u64)]
     pub enum Syscall {
@@ -31,6 +39,7 @@ pub mod syscalls {
         CapDerive = 0x300,
         CapDelegate = 0x301,
         CapRevoke = 0x302,
+        CapAuditRead = 0x303,
 
         // IPC (0x400 range)
         ChannelCreate = 0x400,
@@ -50,6 +59,7 @@ pub mod syscalls {
                 0x300 => Some(Syscall::CapDerive),
                 0x301 => Some(Syscall::CapDelegate),
                 0x302 => Some(Syscall::CapRevoke),
+                0x303 => Some(Syscall::CapAuditRead),
                 0x400 => Some(Syscall::ChannelCreate),
                 0x401 => Some(Syscall::ChannelSend),
                 0x402 => Some(Syscall::ChannelRecv),
@@ -58,6 +68,7 @@ pub mod syscalls {
         }
     }
 
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     #[repr(C)]
     pub struct SyscallArgs {
         pub arg0: usize,
@@ -78,6 +89,49 @@ pub mod capability {
         pub key: [u8; 32],
         pub rights: Rights,
         pub object_type: ObjectType,
+        pub expires_at: Option<u64>, // Kernel tick at which the capability dies
+    }
+
+    impl Capability {
+        /// Whether the capability has expired at kernel tick `now`
+        pub fn is_expired(&self, now: u64) -> bool {
+            self.expires_at.is_some_and(|tick| now >= tick)
+        }
+
+        /// Encoded size: key, rights, object type, expiry flag, expiry tick (LE)
+        pub const ENCODED_LEN: usize = 32 + 1 + 1 + 1 + 8;
+
+        /// Pack into a fixed little-endian layout for persisting the capability table
+        pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+            let mut out = [0u8; Self::ENCODED_LEN];
+            out[..32].copy_from_slice(&self.key);
+            out[32] = self.rights.bits();
+            out[33] = self.object_type.to_u8();
+            if let Some(tick) = self.expires_at {
+                out[34] = 1;
+                out[35..].copy_from_slice(&tick.to_le_bytes());
+            }
+            out
+        }
+
+        /// Inverse of [`Capability::to_bytes`]; `None` on an unknown object type or expiry flag
+        pub fn from_bytes(bytes: &[u8; Self::ENCODED_LEN]) -> Option<Self> {
+            let mut key = [0u8; 32];
+            key.copy_from_slice(&bytes[..32]);
+            let mut tick = [0u8; 8];
+            tick.copy_from_slice(&bytes[35..]);
+            let expires_at = match bytes[34] {
+                0 if tick == [0u8; 8] => None,
+                1 => Some(u64::from_le_bytes(tick)),
+                _ => return None,
+            };
+            Some(Self {
+                key,
+                rights: Rights(bytes[32]),
+                object_type: ObjectType::from_u8(bytes[33])?,
+                expires_at,
+            })
+        }
     }
 
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -112,6 +166,28 @@ pub mod capability {
         File,
         LatticeObject,
     }
+
+    impl ObjectType {
+        /// Stable wire discriminant; part of the persisted table format
+        pub const fn to_u8(self) -> u8 {
+            match self {
+                ObjectType::MemoryRegion => 0,
+                ObjectType::Channel => 1,
+                ObjectType::File => 2,
+                ObjectType::LatticeObject => 3,
+            }
+        }
+
+        pub fn from_u8(val: u8) -> Option<Self> {
+            match val {
+                0 => Some(ObjectType::MemoryRegion),
+                1 => Some(ObjectType::Channel),
+                2 => Some(ObjectType::File),
+                3 => Some(ObjectType::LatticeObject),
+                _ => None,
+            }
+        }
+    }
 }
 
 pub use integration::{HardwareAttestation, LatticeStream, SymbioteInterface};
@@ -120,13 +196,45 @@ pub use memory::FixedAllocator;
 pub use rules::{RuleEngine, RuleId};
 
 /// Core error types for the nucleus
+///
+/// Each variant carries a stable numeric code for FFI/syscall boundaries.
+/// Codes are part of the ABI: once assigned they must never change or be
+/// reused. `0` is reserved for success.
+///
+/// | Code | Variant              |
+/// |------|----------------------|
+/// | 1    | `CapacityExceeded`   |
+/// | 2    | `InvalidCapability`  |
+/// | 3    | `RuleViolation`      |
+/// | 4    | `VerificationFailed` |
+/// | 5    | `MemoryFault`        |
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(u32)]
 pub enum NucleusError {
-    CapacityExceeded,
-    InvalidCapability,
-    RuleViolation,
-    VerificationFailed,
-    MemoryFault,
+    CapacityExceeded = 1,
+    InvalidCapability = 2,
+    RuleViolation = 3,
+    VerificationFailed = 4,
+    MemoryFault = 5,
+}
+
+impl NucleusError {
+    /// Stable numeric code for this error.
+    pub const fn code(&self) -> u32 {
+        *self as u32
+    }
+
+    /// Decode a numeric code back into an error, if it is assigned.
+    pub fn from_code(code: u32) -> Option<Self> {
+        match code {
+            1 => Some(NucleusError::CapacityExceeded),
+            2 => Some(NucleusError::InvalidCapability),
+            3 => Some(NucleusError::RuleViolation),
+            4 => Some(NucleusError::VerificationFailed),
+            5 => Some(NucleusError::MemoryFault),
+            _ => None,
+        }
+    }
 }
 
 /// Result type for nucleus operations
@@ -138,3 +246,8 @@ pub const MAX_MUSCLES: usize = 16;
 pub const MAX_UPDATES: usize = 16;
 pub const SCHEDULE_SLOTS: usize = 256;
 pub const SYMBIOTE_ID: u64 = 0xFFFF_FFFF_FFFF_FFFF; // Highest priority
+pub const MAX_CAPABILITIES: usize = 32;
+pub const MAX_DELEGATION_DEPTH: u8 = 4; // Root capabilities sit at depth 0
+pub const MAX_AUDIT_EVENTS: usize = 32; // Capability audit ring size
+pub const PAGE_SIZE: usize = 4096;
+pub const MAX_MESSAGE_SIZE: usize = PAGE_SIZE; // One page per IPC message
@@ -0,0 +1,232 @@
+//! Typed, validated syscall arguments
+//!
+//! Each struct decodes the raw [`SyscallArgs`] registers for one syscall,
+//! rejecting malformed values before the dispatcher touches any state.
+
+use super::SyscallArgs;
+use crate::capability::Rights;
+use crate::kernel::CapabilityEvent;
+use crate::{NucleusError, MAX_MESSAGE_SIZE, PAGE_SIZE};
+
+/// IPC channel identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ChannelId(pub usize);
+
+/// `MuscAlloc`: `arg0` = size in pages, `arg1` = alignment in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MuscAllocArgs {
+    /// Pages to allocate, never zero
+    pub size: usize,
+    /// Power-of-two alignment, at most one page (`0` selects a page)
+    pub align: usize,
+}
+
+impl TryFrom<SyscallArgs> for MuscAllocArgs {
+    type Error = NucleusError;
+
+    fn try_from(args: SyscallArgs) -> Result<Self, Self::Error> {
+        let size = checked_pages(args.arg0)?;
+        let align = match args.arg1 {
+            0 => PAGE_SIZE,
+            align if align.is_power_of_two() && align <= PAGE_SIZE => align,
+            _ => return Err(NucleusError::MemoryFault),
+        };
+        Ok(Self { size, align })
+    }
+}
+
+/// `MuscMap`: `arg0` = muscle id, `arg1` = pages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MuscMapArgs {
+    pub muscle: u64,
+    /// Pages to map, never zero
+    pub pages: usize,
+}
+
+impl TryFrom<SyscallArgs> for MuscMapArgs {
+    type Error = NucleusError;
+
+    fn try_from(args: SyscallArgs) -> Result<Self, Self::Error> {
+        Ok(Self {
+            muscle: args.arg0 as u64,
+            pages: checked_pages(args.arg1)?,
+        })
+    }
+}
+
+/// `LatticeRead`/`LatticeWrite`: `arg0` = cap index, `arg1` = offset, `arg2` = len
+///
+/// Bounds are enforced against the capability's window at dispatch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatticeAccessArgs {
+    pub cap_index: usize,
+    pub offset: usize,
+    pub len: usize,
+}
+
+impl TryFrom<SyscallArgs> for LatticeAccessArgs {
+    type Error = NucleusError;
+
+    fn try_from(args: SyscallArgs) -> Result<Self, Self::Error> {
+        Ok(Self {
+            cap_index: args.arg0,
+            offset: args.arg1,
+            len: args.arg2,
+        })
+    }
+}
+
+/// `CapDerive`: `arg0` = cap index, `arg1` = rights bits, `arg2` = expiry tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapDeriveArgs {
+    pub cap_index: usize,
+    pub rights: Rights,
+    /// `None` inherits the parent's expiry (raw value `0`)
+    pub expires_at: Option<u64>,
+}
+
+impl TryFrom<SyscallArgs> for CapDeriveArgs {
+    type Error = NucleusError;
+
+    fn try_from(args: SyscallArgs) -> Result<Self, Self::Error> {
+        let rights = u8::try_from(args.arg1).map_err(|_| NucleusError::InvalidCapability)?;
+        Ok(Self {
+            cap_index: args.arg0,
+            rights: Rights(rights),
+            expires_at: match args.arg2 {
+                0 => None,
+                tick => Some(tick as u64),
+            },
+        })
+    }
+}
+
+/// `CapDelegate`: `arg0` = cap index, `arg1` = target muscle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapDelegateArgs {
+    pub cap_index: usize,
+    pub target: u64,
+}
+
+impl TryFrom<SyscallArgs> for CapDelegateArgs {
+    type Error = NucleusError;
+
+    fn try_from(args: SyscallArgs) -> Result<Self, Self::Error> {
+        Ok(Self {
+            cap_index: args.arg0,
+            target: args.arg1 as u64,
+        })
+    }
+}
+
+/// `CapRevoke`: `arg0` = cap index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapRevokeArgs {
+    pub cap_index: usize,
+}
+
+impl TryFrom<SyscallArgs> for CapRevokeArgs {
+    type Error = NucleusError;
+
+    fn try_from(args: SyscallArgs) -> Result<Self, Self::Error> {
+        Ok(Self {
+            cap_index: args.arg0,
+        })
+    }
+}
+
+/// `CapAuditRead`: `arg0` = cap index, `arg1` = event buffer, `arg2` = capacity in events
+///
+/// Events are copied oldest first into an array of [`CapabilityEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapAuditReadArgs {
+    pub cap_index: usize,
+    /// Aligned for [`CapabilityEvent`], non-null unless `max` is zero
+    pub ptr: usize,
+    pub max: usize,
+}
+
+impl TryFrom<SyscallArgs> for CapAuditReadArgs {
+    type Error = NucleusError;
+
+    fn try_from(args: SyscallArgs) -> Result<Self, Self::Error> {
+        let (ptr, max) = (args.arg1, args.arg2);
+        let valid = ptr.is_multiple_of(align_of::<CapabilityEvent>())
+            && (max == 0 || ptr != 0)
+            && max
+                .checked_mul(size_of::<CapabilityEvent>())
+                .and_then(|len| ptr.checked_add(len))
+                .is_some();
+        if !valid {
+            return Err(NucleusError::MemoryFault);
+        }
+        Ok(Self {
+            cap_index: args.arg0,
+            ptr,
+            max,
+        })
+    }
+}
+
+/// `ChannelSend`: `arg0` = channel, `arg1` = data pointer, `arg2` = len
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelSendArgs {
+    pub channel: ChannelId,
+    pub ptr: usize,
+    /// At most [`MAX_MESSAGE_SIZE`] bytes
+    pub len: usize,
+}
+
+impl TryFrom<SyscallArgs> for ChannelSendArgs {
+    type Error = NucleusError;
+
+    fn try_from(args: SyscallArgs) -> Result<Self, Self::Error> {
+        let (ptr, len) = checked_buffer(args.arg1, args.arg2)?;
+        Ok(Self {
+            channel: ChannelId(args.arg0),
+            ptr,
+            len,
+        })
+    }
+}
+
+/// `ChannelRecv`: `arg0` = channel, `arg1` = buffer pointer, `arg2` = capacity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelRecvArgs {
+    pub channel: ChannelId,
+    pub ptr: usize,
+    /// At most [`MAX_MESSAGE_SIZE`] bytes
+    pub len: usize,
+}
+
+impl TryFrom<SyscallArgs> for ChannelRecvArgs {
+    type Error = NucleusError;
+
+    fn try_from(args: SyscallArgs) -> Result<Self, Self::Error> {
+        let (ptr, len) = checked_buffer(args.arg1, args.arg2)?;
+        Ok(Self {
+            channel: ChannelId(args.arg0),
+            ptr,
+            len,
+        })
+    }
+}
+
+/// Non-zero page count whose byte size fits in `usize`
+fn checked_pages(pages: usize) -> Result<usize, NucleusError> {
+    match pages.checked_mul(PAGE_SIZE) {
+        Some(bytes) if bytes > 0 => Ok(pages),
+        _ => Err(NucleusError::MemoryFault),
+    }
+}
+
+/// Message buffer within [`MAX_MESSAGE_SIZE`], non-null unless empty,
+/// and not wrapping the address space
+fn checked_buffer(ptr: usize, len: usize) -> Result<(usize, usize), NucleusError> {
+    let valid = len <= MAX_MESSAGE_SIZE && (len == 0 || ptr != 0) && ptr.checked_add(len).is_some();
+    if valid {
+        Ok((ptr, len))
+    } else {
+        Err(NucleusError::MemoryFault)
+    }
+}
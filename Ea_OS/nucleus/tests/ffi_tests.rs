@@ -0,0 +1,52 @@
+#![cfg(all(test, feature = "ffi"))]
+
+use nucleus::capability::{Capability, ObjectType, Rights};
+use nucleus::ffi::{nucleus_syscall, pack_result, UNKNOWN_SYSCALL};
+use nucleus::kernel::MuscleNucleus;
+use nucleus::NucleusError;
+
+#[test]
+fn test_ffi_syscall_packs_success_and_errors() {
+    let mut nucleus = MuscleNucleus::new();
+    let cap = Capability {
+        key: [3u8; 32],
+        rights: Rights::READ,
+        object_type: ObjectType::Channel,
+        expires_at: None,
+    };
+    let index = nucleus.grant_capability(cap, 1).unwrap();
+
+    // ChannelCreate returns the new channel id
+    let ret = unsafe { nucleus_syscall(&mut nucleus, 0x400, 0, 0, 0) };
+    assert_eq!(ret, 1);
+
+    // Deriving WRITE from a READ-only capability is refused
+    let ret = unsafe { nucleus_syscall(&mut nucleus, 0x300, index, Rights::WRITE.0 as usize, 0) };
+    assert_eq!(ret, -(NucleusError::InvalidCapability.code() as i64));
+    assert_eq!(
+        NucleusError::from_code((-ret) as u32),
+        Some(NucleusError::InvalidCapability)
+    );
+}
+
+#[test]
+fn test_ffi_syscall_rejects_unknown_numbers_and_null() {
+    let mut nucleus = MuscleNucleus::new();
+
+    assert_eq!(
+        unsafe { nucleus_syscall(&mut nucleus, 0, 0, 0, 0) },
+        UNKNOWN_SYSCALL
+    );
+    assert_eq!(
+        unsafe { nucleus_syscall(&mut nucleus, 0x403, 0, 0, 0) },
+        UNKNOWN_SYSCALL
+    );
+    assert_eq!(
+        unsafe { nucleus_syscall(core::ptr::null_mut(), 0x400, 0, 0, 0) },
+        -(NucleusError::MemoryFault.code() as i64)
+    );
+    assert_eq!(
+        pack_result(Ok(usize::MAX)),
+        -(NucleusError::MemoryFault.code() as i64)
+    );
+}
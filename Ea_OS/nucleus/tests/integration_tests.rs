@@ -24,3 +24,30 @@ fn test_nucleus_creation() {
     // Verify capabilities are set
     assert!(nucleus.capabilities().can_load_muscle());
 }
+
+#[test]
+fn test_budgeted_structures_fit_kernel_size() {
+    use core::mem::size_of;
+    use nucleus::integration::SymbioteInterface;
+    use nucleus::kernel::{CapabilitySet, CapabilityTable, LoadedMuscle, Scheduler};
+    use nucleus::memory::manager::MemoryManager;
+    use nucleus::{RuleEngine, RuleId, KERNEL_SIZE, MAX_MUSCLES};
+
+    // Everything MuscleNucleus::BUDGETED_SIZE documents as counted
+    let counted = [
+        size_of::<CapabilitySet>(),
+        size_of::<CapabilityTable>(),
+        size_of::<[Option<LoadedMuscle>; MAX_MUSCLES]>(),
+        size_of::<Scheduler>(),
+        size_of::<RuleEngine>(),
+        size_of::<HardwareAttestation>(),
+        size_of::<SymbioteInterface>(),
+        size_of::<MemoryManager>(),
+        size_of::<RuleId>(),
+        size_of::<u64>(), // heartbeat counter
+        size_of::<u64>(), // ticks
+    ];
+    let total: usize = counted.iter().sum();
+    assert_eq!(total, MuscleNucleus::BUDGETED_SIZE);
+    assert!(total <= KERNEL_SIZE, "{total} bytes exceed {KERNEL_SIZE}");
+}
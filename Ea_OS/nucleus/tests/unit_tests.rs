@@ -36,3 +36,539 @@ fn test_syscalls() {
     let res = nucleus.handle_syscall(Syscall::MuscAlloc, args);
     assert!(res.is_ok());
 }
+
+#[test]
+fn test_error_code_roundtrip() {
+    use nucleus::NucleusError;
+
+    let all = [
+        NucleusError::CapacityExceeded,
+        NucleusError::InvalidCapability,
+        NucleusError::RuleViolation,
+        NucleusError::VerificationFailed,
+        NucleusError::MemoryFault,
+    ];
+    for (expected_code, err) in (1u32..).zip(all) {
+        assert_eq!(err.code(), expected_code);
+        assert_eq!(NucleusError::from_code(err.code()), Some(err));
+    }
+}
+
+#[test]
+fn test_error_code_unknown() {
+    use nucleus::NucleusError;
+
+    assert_eq!(NucleusError::from_code(0), None);
+    assert_eq!(NucleusError::from_code(6), None);
+    assert_eq!(NucleusError::from_code(u32::MAX), None);
+}
+
+fn root_capability() -> nucleus::capability::Capability {
+    use nucleus::capability::{Capability, ObjectType, Rights};
+
+    Capability {
+        key: [7u8; 32],
+        rights: Rights::READ | Rights::WRITE | Rights::DELEGATE,
+        object_type: ObjectType::Channel,
+        expires_at: None,
+    }
+}
+
+#[test]
+fn test_delegation_depth_limit() {
+    use nucleus::kernel::MuscleNucleus;
+    use nucleus::syscalls::{Syscall, SyscallArgs, SyscallHandler};
+    use nucleus::{NucleusError, MAX_DELEGATION_DEPTH};
+
+    let mut nucleus = MuscleNucleus::new();
+    let mut index = nucleus.grant_capability(root_capability(), 1).unwrap();
+
+    for hop in 1..=MAX_DELEGATION_DEPTH {
+        let args = SyscallArgs {
+            arg0: index,
+            arg1: 1 + hop as usize,
+            arg2: 0,
+        };
+        index = nucleus.handle_syscall(Syscall::CapDelegate, args).unwrap();
+        let entry = nucleus.capability_table().get(index).unwrap();
+        assert_eq!(entry.depth, hop);
+        assert_eq!(entry.owner, 1 + hop as u64);
+    }
+
+    let args = SyscallArgs {
+        arg0: index,
+        arg1: 99,
+        arg2: 0,
+    };
+    assert_eq!(
+        nucleus.handle_syscall(Syscall::CapDelegate, args),
+        Err(NucleusError::RuleViolation)
+    );
+}
+
+#[test]
+fn test_revoke_root_invalidates_chain() {
+    use nucleus::capability::Rights;
+    use nucleus::kernel::CapabilityTable;
+    use nucleus::MAX_DELEGATION_DEPTH;
+
+    let mut table = CapabilityTable::new();
+    let root = table.grant(root_capability(), 1).unwrap();
+    let unrelated = table.grant(root_capability(), 2).unwrap();
+
+    let mut chain = [root; MAX_DELEGATION_DEPTH as usize + 1];
+    for hop in 1..chain.len() {
+        chain[hop] = table.delegate(chain[hop - 1], 10 + hop as u64).unwrap();
+    }
+    let attenuated = table.derive(chain[1], Rights::READ).unwrap();
+    assert!(table.derive(attenuated, Rights::WRITE).is_err());
+
+    let revoked = table.revoke(root).unwrap();
+    assert_eq!(revoked, chain.len() + 1);
+    for index in chain {
+        assert!(table.get(index).is_none());
+    }
+    assert!(table.get(attenuated).is_none());
+    assert!(table.get(unrelated).is_some());
+    assert_eq!(table.len(), 1);
+}
+
+fn lattice_capability(rights: nucleus::capability::Rights) -> nucleus::capability::Capability {
+    use nucleus::capability::{Capability, ObjectType};
+
+    Capability {
+        key: [9u8; 32],
+        rights,
+        object_type: ObjectType::LatticeObject,
+        expires_at: None,
+    }
+}
+
+#[test]
+fn test_lattice_access_bounded_by_window() {
+    use nucleus::capability::Rights;
+    use nucleus::kernel::{LatticeWindow, MuscleNucleus};
+    use nucleus::syscalls::{Syscall, SyscallArgs, SyscallHandler};
+    use nucleus::NucleusError;
+
+    let mut nucleus = MuscleNucleus::new();
+    let window = LatticeWindow::new(64, 128);
+    let cap = lattice_capability(Rights::READ | Rights::WRITE);
+    let index = nucleus.grant_lattice_window(cap, 1, window).unwrap();
+    let access = |offset, len| SyscallArgs {
+        arg0: index,
+        arg1: offset,
+        arg2: len,
+    };
+
+    assert_eq!(
+        nucleus.handle_syscall(Syscall::LatticeRead, access(64, 128)),
+        Ok(128)
+    );
+    assert_eq!(
+        nucleus.handle_syscall(Syscall::LatticeWrite, access(100, 16)),
+        Ok(16)
+    );
+
+    for (offset, len) in [(0, 16), (180, 16), (192, 1), (usize::MAX, 2)] {
+        assert_eq!(
+            nucleus.handle_syscall(Syscall::LatticeRead, access(offset, len)),
+            Err(NucleusError::InvalidCapability)
+        );
+    }
+    assert_eq!(
+        nucleus.handle_syscall(Syscall::LatticeWrite, access(32, 64)),
+        Err(NucleusError::InvalidCapability)
+    );
+}
+
+#[test]
+fn test_lattice_read_requires_read_right() {
+    use nucleus::capability::Rights;
+    use nucleus::kernel::{CapabilityTable, LatticeWindow};
+    use nucleus::NucleusError;
+
+    let mut table = CapabilityTable::new();
+    let write_only = table
+        .grant_window(lattice_capability(Rights::WRITE), 1, LatticeWindow::ALL)
+        .unwrap();
+    assert_eq!(
+        table.check_lattice_access(write_only, Rights::READ, 0, 1),
+        Err(NucleusError::InvalidCapability)
+    );
+    assert!(table
+        .check_lattice_access(write_only, Rights::WRITE, 0, 1)
+        .is_ok());
+
+    // Narrowed children cannot widen past their parent's window.
+    let root = table
+        .grant_window(
+            lattice_capability(Rights::READ),
+            1,
+            LatticeWindow::new(0, 64),
+        )
+        .unwrap();
+    let child = table
+        .derive_window(root, LatticeWindow::new(16, 16))
+        .unwrap();
+    assert!(table
+        .check_lattice_access(child, Rights::READ, 16, 16)
+        .is_ok());
+    assert!(table
+        .check_lattice_access(child, Rights::READ, 0, 16)
+        .is_err());
+    assert!(table
+        .derive_window(root, LatticeWindow::new(32, 64))
+        .is_err());
+
+    // Non-lattice capabilities never pass the lattice check.
+    let channel = table.grant(root_capability(), 1).unwrap();
+    assert!(table
+        .check_lattice_access(channel, Rights::READ, 0, 1)
+        .is_err());
+}
+
+#[test]
+fn test_capability_expires_with_kernel_clock() {
+    use nucleus::capability::{Capability, Rights};
+    use nucleus::kernel::{LatticeWindow, MuscleNucleus};
+    use nucleus::syscalls::{Syscall, SyscallArgs, SyscallHandler};
+    use nucleus::NucleusError;
+
+    let mut nucleus = MuscleNucleus::new();
+    let cap = Capability {
+        expires_at: Some(3),
+        ..lattice_capability(Rights::READ)
+    };
+    let index = nucleus
+        .grant_lattice_window(cap, 1, LatticeWindow::ALL)
+        .unwrap();
+    let read = || SyscallArgs {
+        arg0: index,
+        arg1: 0,
+        arg2: 8,
+    };
+
+    while nucleus.ticks() < 3 {
+        assert_eq!(nucleus.handle_syscall(Syscall::LatticeRead, read()), Ok(8));
+        nucleus.tick();
+    }
+    assert_eq!(
+        nucleus.handle_syscall(Syscall::LatticeRead, read()),
+        Err(NucleusError::InvalidCapability)
+    );
+    assert!(nucleus.capability_table().get(index).is_none());
+}
+
+#[test]
+fn test_derivation_cannot_extend_lifetime() {
+    use nucleus::capability::{Capability, Rights};
+    use nucleus::kernel::{CapabilityTable, MuscleNucleus};
+    use nucleus::syscalls::{Syscall, SyscallArgs, SyscallHandler};
+    use nucleus::NucleusError;
+
+    let expiring = Capability {
+        expires_at: Some(10),
+        ..root_capability()
+    };
+    let mut table = CapabilityTable::new();
+    let root = table.grant(expiring, 1).unwrap();
+    assert_eq!(
+        table.derive_until(root, Rights::READ, 20),
+        Err(NucleusError::InvalidCapability)
+    );
+    let short = table.derive_until(root, Rights::READ, 5).unwrap();
+    let inherited = table.derive(root, Rights::READ).unwrap();
+    assert_eq!(table.get(inherited).unwrap().cap.expires_at, Some(10));
+
+    assert_eq!(table.advance_clock(5), 1);
+    assert!(table.check_live(short).is_err());
+    assert!(table.check_live(root).is_ok());
+    assert_eq!(table.advance_clock(10), 2);
+    assert!(table.is_empty());
+
+    // The CapDerive syscall applies the same bound.
+    let mut nucleus = MuscleNucleus::new();
+    let root = nucleus.grant_capability(expiring, 1).unwrap();
+    let derive_until = |expires_at| SyscallArgs {
+        arg0: root,
+        arg1: Rights::READ.bits() as usize,
+        arg2: expires_at,
+    };
+    assert_eq!(
+        nucleus.handle_syscall(Syscall::CapDerive, derive_until(11)),
+        Err(NucleusError::InvalidCapability)
+    );
+    assert!(nucleus
+        .handle_syscall(Syscall::CapDerive, derive_until(10))
+        .is_ok());
+}
+
+#[test]
+fn test_revocation_order_is_stable() {
+    use nucleus::capability::Rights;
+    use nucleus::kernel::CapabilityTable;
+
+    let mut table = CapabilityTable::new();
+    let root = table.grant(root_capability(), 1).unwrap(); // slot 0
+    let write = table
+        .derive(root, Rights::WRITE | Rights::DELEGATE)
+        .unwrap(); // slot 1
+    let read = table.derive(root, Rights::READ).unwrap(); // slot 2
+    let delegated = table.delegate(root, 5).unwrap(); // slot 3
+    let via_write = table.delegate(write, 7).unwrap(); // slot 4
+    let via_delegated = table.derive(delegated, Rights::READ).unwrap(); // slot 5
+    let unrelated = table.grant(root_capability(), 2).unwrap();
+
+    // Root first, then each generation sorted by rights (all keys match), owner, slot.
+    let expected = [root, read, write, delegated, via_delegated, via_write];
+    assert_eq!(table.revocation_order(root).unwrap().as_slice(), &expected);
+    assert_eq!(
+        table.revocation_order(write).unwrap().as_slice(),
+        &[write, via_write]
+    );
+
+    assert_eq!(table.revoke(root), Ok(expected.len()));
+    assert_eq!(table.len(), 1);
+    assert!(table.get(unrelated).is_some());
+}
+
+#[test]
+fn test_capability_table_serialization_roundtrip() {
+    use nucleus::capability::Rights;
+    use nucleus::kernel::{CapabilityTable, LatticeWindow};
+
+    let mut table = CapabilityTable::new();
+    let root = table.grant(root_capability(), 1).unwrap();
+    let lattice = table
+        .grant_window(
+            lattice_capability(Rights::READ | Rights::DELEGATE),
+            2,
+            LatticeWindow::new(16, 64),
+        )
+        .unwrap();
+    let child = table
+        .derive_until(root, Rights::READ | Rights::DELEGATE, 90)
+        .unwrap();
+    table.delegate(child, 9).unwrap();
+    table
+        .derive_window(lattice, LatticeWindow::new(24, 8))
+        .unwrap();
+    table.revoke(lattice).unwrap(); // leave holes so slot indices matter
+    table.derive(root, Rights::WRITE).unwrap();
+    table.advance_clock(42);
+
+    let mut buf = [0u8; CapabilityTable::SERIALIZED_MAX];
+    let used = table.serialize_into(&mut buf).unwrap();
+    let restored = CapabilityTable::deserialize_from(&buf[..used]).unwrap();
+
+    assert_eq!(restored.now(), 42);
+    assert_eq!(restored.len(), table.len());
+    for slot in 0..nucleus::MAX_CAPABILITIES {
+        assert_eq!(restored.get(slot), table.get(slot));
+    }
+    assert_eq!(
+        restored.revocation_order(root).unwrap().as_slice(),
+        table.revocation_order(root).unwrap().as_slice()
+    );
+
+    assert_eq!(
+        table.serialize_into(&mut buf[..used - 1]),
+        Err(nucleus::NucleusError::CapacityExceeded)
+    );
+    assert!(CapabilityTable::deserialize_from(&buf[..used - 1]).is_err());
+}
+
+#[test]
+fn test_capability_bytes_reject_bad_object_type() {
+    use nucleus::capability::Capability;
+    use nucleus::kernel::CapabilityTable;
+
+    let mut cap = root_capability();
+    cap.expires_at = Some(0x0102_0304_0506_0708);
+    let mut bytes = cap.to_bytes();
+    assert_eq!(Capability::from_bytes(&bytes), Some(cap));
+
+    bytes[32 + 1] = 4; // one past `ObjectType::LatticeObject`
+    assert_eq!(Capability::from_bytes(&bytes), None);
+
+    let mut table = CapabilityTable::new();
+    table.grant(root_capability(), 1).unwrap();
+    let mut buf = [0u8; CapabilityTable::SERIALIZED_MAX];
+    let used = table.serialize_into(&mut buf).unwrap();
+    buf[8 + 1 + 1 + 32 + 1] = 0xFF; // object type of the only entry
+    assert_eq!(
+        CapabilityTable::deserialize_from(&buf[..used]).err(),
+        Some(nucleus::NucleusError::InvalidCapability)
+    );
+}
+
+#[test]
+fn test_capability_audit_records_operations_in_order() {
+    use nucleus::capability::Rights;
+    use nucleus::kernel::{CapabilityEvent, CapabilityOp, MuscleNucleus};
+    use nucleus::syscalls::{Syscall, SyscallArgs, SyscallHandler};
+    use nucleus::{NucleusError, SYMBIOTE_ID};
+
+    let mut nucleus = MuscleNucleus::new();
+    nucleus.enable_capability_audit();
+    let root = nucleus.grant_capability(root_capability(), 1).unwrap();
+    let audit_cap = nucleus
+        .grant_capability(root_capability(), SYMBIOTE_ID)
+        .unwrap();
+    nucleus.tick();
+
+    let call = |syscall, arg0, arg1, arg2| (syscall, SyscallArgs { arg0, arg1, arg2 });
+    let derived = Rights::READ | Rights::DELEGATE;
+    let ops = [
+        call(Syscall::CapDerive, root, derived.bits() as usize, 0),
+        call(Syscall::CapDelegate, root, 5, 0),
+        call(Syscall::CapRevoke, root, 0, 0),
+    ];
+    for (syscall, args) in ops {
+        nucleus.handle_syscall(syscall, args).unwrap();
+    }
+
+    let cap = root_capability();
+    let expected = [
+        (CapabilityOp::Grant, cap.rights, 0),
+        (CapabilityOp::Grant, cap.rights, 0),
+        (CapabilityOp::Derive, derived, 1),
+        (CapabilityOp::Delegate, cap.rights, 1),
+        // Revocation visits the root, then its children sorted by rights
+        (CapabilityOp::Revoke, cap.rights, 1),
+        (CapabilityOp::Revoke, derived, 1),
+        (CapabilityOp::Revoke, cap.rights, 1),
+    ];
+    let audit = nucleus.capability_audit(audit_cap).unwrap();
+    assert_eq!(audit.len(), expected.len());
+    assert_eq!(audit.dropped(), 0);
+    for (event, (op, rights, tick)) in audit.iter().zip(expected) {
+        assert_eq!((event.op, event.rights, event.tick), (op, rights, tick));
+        assert_eq!(event.key, cap.key);
+    }
+
+    // The syscall copies events oldest first, up to the buffer's capacity
+    let blank = CapabilityEvent {
+        op: CapabilityOp::Grant,
+        key: [0; 32],
+        rights: Rights(0),
+        tick: u64::MAX,
+    };
+    let mut buf = [blank; 16];
+    let ptr = buf.as_mut_ptr() as usize;
+    let (syscall, args) = call(Syscall::CapAuditRead, audit_cap, ptr, 5);
+    assert_eq!(nucleus.handle_syscall(syscall, args), Ok(5));
+    assert_eq!(buf[5], blank);
+    let (syscall, args) = call(Syscall::CapAuditRead, audit_cap, ptr, buf.len());
+    assert_eq!(nucleus.handle_syscall(syscall, args), Ok(expected.len()));
+    for (event, (op, rights, tick)) in buf.iter().zip(expected) {
+        assert_eq!((event.op, event.rights, event.tick), (op, rights, tick));
+        assert_eq!(event.key, cap.key);
+    }
+    assert_eq!(buf[expected.len()], blank);
+
+    // Buffers must be non-null and aligned
+    for ptr in [0, ptr + 1] {
+        let (syscall, args) = call(Syscall::CapAuditRead, audit_cap, ptr, 1);
+        assert_eq!(
+            nucleus.handle_syscall(syscall, args),
+            Err(NucleusError::MemoryFault)
+        );
+    }
+
+    // Only a live symbiote capability with READ may read the log
+    let foreign = nucleus.grant_capability(root_capability(), 1).unwrap();
+    for cap_index in [foreign, root] {
+        let (syscall, args) = call(Syscall::CapAuditRead, cap_index, ptr, buf.len());
+        assert_eq!(
+            nucleus.handle_syscall(syscall, args),
+            Err(NucleusError::InvalidCapability)
+        );
+    }
+}
+
+#[test]
+fn test_capability_audit_ring_wraps_and_counts_drops() {
+    use nucleus::capability::Rights;
+    use nucleus::kernel::{CapabilityOp, CapabilityTable};
+    use nucleus::MAX_AUDIT_EVENTS;
+
+    let mut table = CapabilityTable::new();
+    assert!(table.audit().is_none());
+    table.enable_audit();
+    let root = table.grant(root_capability(), 1).unwrap();
+    let extra = 5;
+    for _ in 0..MAX_AUDIT_EVENTS + extra - 1 {
+        let child = table.derive(root, Rights::READ).unwrap();
+        table.revoke(child).unwrap();
+    }
+
+    // One grant plus alternating derive/revoke pairs, minus the overwritten head
+    let audit = table.audit().unwrap();
+    let total = 2 * (MAX_AUDIT_EVENTS + extra - 1) + 1;
+    assert_eq!(audit.len(), MAX_AUDIT_EVENTS);
+    assert_eq!(audit.dropped(), (total - MAX_AUDIT_EVENTS) as u64);
+    assert!(audit.iter().all(|event| event.op != CapabilityOp::Grant));
+    assert_eq!(audit.get(0).unwrap().op, CapabilityOp::Derive);
+    let newest = audit.get(MAX_AUDIT_EVENTS - 1).unwrap();
+    assert_eq!(newest.op, CapabilityOp::Revoke);
+    assert!(audit.get(MAX_AUDIT_EVENTS).is_none());
+}
+
+#[test]
+fn test_typed_syscall_args_validate_ranges() {
+    use nucleus::capability::Rights;
+    use nucleus::kernel::MuscleNucleus;
+    use nucleus::syscalls::{
+        CapDeriveArgs, ChannelId, ChannelSendArgs, MuscAllocArgs, Syscall, SyscallArgs,
+        SyscallHandler,
+    };
+    use nucleus::{NucleusError, MAX_MESSAGE_SIZE, PAGE_SIZE};
+
+    let raw = |arg0, arg1, arg2| SyscallArgs { arg0, arg1, arg2 };
+
+    assert_eq!(
+        MuscAllocArgs::try_from(raw(0, 0, 0)),
+        Err(NucleusError::MemoryFault)
+    );
+    assert_eq!(
+        MuscAllocArgs::try_from(raw(2, 24, 0)),
+        Err(NucleusError::MemoryFault)
+    );
+    assert_eq!(
+        MuscAllocArgs::try_from(raw(2, 64, 0)),
+        Ok(MuscAllocArgs { size: 2, align: 64 })
+    );
+    assert_eq!(
+        MuscAllocArgs::try_from(raw(2, 0, 0)).unwrap().align,
+        PAGE_SIZE
+    );
+
+    assert_eq!(
+        ChannelSendArgs::try_from(raw(3, 0x1000, 16)),
+        Ok(ChannelSendArgs {
+            channel: ChannelId(3),
+            ptr: 0x1000,
+            len: 16,
+        })
+    );
+    assert!(ChannelSendArgs::try_from(raw(3, 0x1000, MAX_MESSAGE_SIZE + 1)).is_err());
+    assert!(ChannelSendArgs::try_from(raw(3, 0, 16)).is_err());
+
+    let derive = CapDeriveArgs::try_from(raw(1, Rights::READ.bits() as usize, 0)).unwrap();
+    assert_eq!(derive.rights, Rights::READ);
+    assert_eq!(derive.expires_at, None);
+    assert!(CapDeriveArgs::try_from(raw(1, 0x100, 0)).is_err());
+
+    // The dispatcher rejects bad arguments before touching any state
+    let mut nucleus = MuscleNucleus::new();
+    assert_eq!(
+        nucleus.handle_syscall(Syscall::MuscAlloc, raw(0, 0, 0)),
+        Err(NucleusError::MemoryFault)
+    );
+    assert_eq!(
+        nucleus.handle_syscall(Syscall::ChannelSend, raw(1, 0, 8)),
+        Err(NucleusError::MemoryFault)
+    );
+}